@@ -0,0 +1,223 @@
+use std::io::{self, Write};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use termion::event::Key;
+use termion::input::TermRead;
+use termion::raw::IntoRawMode;
+use termion::screen::AlternateScreen;
+use termion::{clear, color, cursor, style};
+
+use crate::error::ParseError;
+use crate::simulation::Simulation;
+use crate::{day06, day11, day13};
+
+/// The days with a [`Simulation`] that can be animated.
+pub const ANIMATED_DAYS: &[u8] = &[6, 11, 13];
+
+pub const MIN_DELAY: Duration = Duration::from_millis(1);
+pub const MAX_DELAY: Duration = Duration::from_secs(8);
+
+const HELP: &str = "space: pause/resume  n: single step  +/-: faster/slower  q: quit";
+
+/// Parses `puzzle_input` for `day` into a simulation starting from the
+/// initial state, or returns `None` if the day cannot be animated.
+pub fn load(day: u8, puzzle_input: String) -> Option<Result<Box<dyn Simulation>, ParseError>> {
+    fn boxed<S: Simulation + 'static>(simulation: S) -> Box<dyn Simulation> {
+        Box::new(simulation)
+    }
+    match day {
+        6 => Some(
+            day06::get_lanternfish(puzzle_input)
+                .map(|lanternfish| boxed(day06::LanternfishPopulation::from(lanternfish))),
+        ),
+        11 => Some(day11::load_octopodes(puzzle_input).map(boxed)),
+        13 => Some(
+            day13::parse_input(puzzle_input)
+                .map(|transparent_paper| boxed(day13::FoldSequence::from(transparent_paper))),
+        ),
+        _ => None,
+    }
+}
+
+/// Steps through a [`Simulation`] at a steady pace, and handles the
+/// keys for pausing, single-stepping and changing speed.
+pub struct Stepper {
+    simulation: Box<dyn Simulation>,
+    steps: u64,
+    paused: bool,
+    finished: bool,
+    delay: Duration,
+}
+
+impl Stepper {
+    pub fn new(simulation: Box<dyn Simulation>, delay: Duration) -> Self {
+        Stepper {
+            simulation,
+            steps: 0,
+            paused: false,
+            finished: false,
+            delay: delay.clamp(MIN_DELAY, MAX_DELAY),
+        }
+    }
+
+    #[cfg(test)]
+    pub fn steps(&self) -> u64 {
+        self.steps
+    }
+
+    #[cfg(test)]
+    pub fn is_paused(&self) -> bool {
+        self.paused
+    }
+
+    pub fn delay(&self) -> Duration {
+        self.delay
+    }
+
+    /// Takes a step unless paused or finished.
+    pub fn tick(&mut self) {
+        if !self.paused {
+            self.step();
+        }
+    }
+
+    fn step(&mut self) {
+        if self.finished {
+            return;
+        }
+        match self.simulation.step() {
+            true => self.steps += 1,
+            false => self.finished = true,
+        }
+    }
+
+    /// Responds to a key press. Returns false if the user asked to
+    /// quit.
+    pub fn handle_key(&mut self, key: Key) -> bool {
+        match key {
+            Key::Char('q') | Key::Esc | Key::Ctrl('c') => return false,
+            Key::Char(' ') => self.paused = !self.paused,
+            Key::Char('n') | Key::Right => {
+                self.paused = true;
+                self.step();
+            }
+            Key::Char('+') | Key::Char('=') => self.delay = (self.delay / 2).max(MIN_DELAY),
+            Key::Char('-') => self.delay = (self.delay * 2).min(MAX_DELAY),
+            _ => {}
+        }
+        true
+    }
+
+    /// Redraws the whole screen, cutting the simulation off at the
+    /// edges of the terminal.
+    pub fn draw<W: Write>(
+        &self,
+        out: &mut W,
+        title: &str,
+        (width, height): (u16, u16),
+    ) -> io::Result<()> {
+        let state = match (self.finished, self.paused) {
+            (true, _) => "finished",
+            (false, true) => "paused",
+            (false, false) => "running",
+        };
+        write!(
+            out,
+            "{}{}{}{} - step {} - {} - {:?} per step{}",
+            clear::All,
+            cursor::Goto(1, 1),
+            style::Bold,
+            title,
+            self.steps,
+            state,
+            self.delay,
+            style::Reset
+        )?;
+        let rendered = self.simulation.to_string();
+        let visible_lines = (height as usize).saturating_sub(2);
+        for (y, line) in rendered.lines().take(visible_lines).enumerate() {
+            write!(out, "{}", cursor::Goto(1, y as u16 + 2))?;
+            self.draw_line(out, line, width)?;
+        }
+        write!(out, "{}{}", cursor::Goto(1, height), HELP)?;
+        out.flush()
+    }
+
+    fn draw_line<W: Write>(&self, out: &mut W, line: &str, width: u16) -> io::Result<()> {
+        let mut current_colour = None;
+        for character in line.chars().take(width as usize) {
+            let colour = self.simulation.colour(character);
+            if colour != current_colour {
+                match colour {
+                    Some(colour) => write!(out, "{}", color::Fg(colour))?,
+                    None => write!(out, "{}", color::Fg(color::Reset))?,
+                }
+                current_colour = colour;
+            }
+            write!(out, "{}", character)?;
+        }
+        write!(out, "{}", color::Fg(color::Reset))
+    }
+}
+
+/// Animates `simulation` full-screen until the user quits, taking a
+/// step every `delay` unless paused.
+pub fn run(title: &str, simulation: Box<dyn Simulation>, delay: Duration) -> io::Result<()> {
+    let mut stepper = Stepper::new(simulation, delay);
+    let mut screen = AlternateScreen::from(io::stdout().into_raw_mode()?);
+    let mut keys = termion::async_stdin().keys();
+    write!(screen, "{}", cursor::Hide)?;
+    'animation: loop {
+        stepper.draw(&mut screen, title, termion::terminal_size()?)?;
+        let next_step = Instant::now() + stepper.delay();
+        while Instant::now() < next_step {
+            let mut pressed_any_key = false;
+            for key in keys.by_ref() {
+                if !stepper.handle_key(key?) {
+                    break 'animation;
+                }
+                pressed_any_key = true;
+            }
+            if pressed_any_key {
+                stepper.draw(&mut screen, title, termion::terminal_size()?)?;
+            }
+            thread::sleep(
+                next_step
+                    .saturating_duration_since(Instant::now())
+                    .min(Duration::from_millis(10)),
+            );
+        }
+        stepper.tick();
+    }
+    write!(screen, "{}", cursor::Show)?;
+    screen.flush()
+}
+
+#[test]
+fn test_stepper_controls() {
+    let lanternfish = day06::get_lanternfish(String::from("3,4,3,1,2")).unwrap();
+    let simulation = Box::new(day06::LanternfishPopulation::from(lanternfish));
+    let mut stepper = Stepper::new(simulation, Duration::from_millis(100));
+    stepper.tick();
+    assert_eq!(stepper.steps(), 1);
+    assert!(stepper.handle_key(Key::Char(' ')));
+    stepper.tick();
+    assert_eq!(stepper.steps(), 1);
+    stepper.handle_key(Key::Char('n'));
+    assert!(stepper.is_paused());
+    assert_eq!(stepper.steps(), 2);
+    stepper.handle_key(Key::Char('+'));
+    assert_eq!(stepper.delay(), Duration::from_millis(50));
+    for _ in 0..20 {
+        stepper.handle_key(Key::Char('-'));
+    }
+    assert_eq!(stepper.delay(), MAX_DELAY);
+    assert!(!stepper.handle_key(Key::Char('q')));
+
+    let mut screen = Vec::new();
+    stepper.draw(&mut screen, "day 6", (80, 24)).unwrap();
+    let screen = String::from_utf8(screen).unwrap();
+    assert!(screen.contains("step 2 - paused"));
+    assert!(screen.contains(HELP));
+}
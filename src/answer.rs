@@ -0,0 +1,98 @@
+use std::convert::TryFrom;
+use std::fmt;
+
+/// The answer to one part of a puzzle.
+///
+/// Integers are always stored in the narrowest variant that holds
+/// them, so two answers with the same value compare equal however
+/// they were produced.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Answer {
+    /// An integer that fits in an `i64`.
+    Integer(i64),
+    /// An integer too large for [`Answer::Integer`].
+    BigInteger(i128),
+    /// Anything else, such as letters read off a display.
+    Text(String),
+}
+
+impl Answer {
+    /// `"integer"`, `"big-integer"` or `"text"`.
+    pub fn answer_type(&self) -> &'static str {
+        match self {
+            Answer::Integer(_) => "integer",
+            Answer::BigInteger(_) => "big-integer",
+            Answer::Text(_) => "text",
+        }
+    }
+}
+
+impl fmt::Display for Answer {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Answer::Integer(integer) => write!(f, "{}", integer),
+            Answer::BigInteger(big_integer) => write!(f, "{}", big_integer),
+            Answer::Text(text) => write!(f, "{}", text),
+        }
+    }
+}
+
+macro_rules! impl_from_narrow_integer {
+    ($($integer:ty),*) => {
+        $(
+            impl From<$integer> for Answer {
+                fn from(integer: $integer) -> Self {
+                    Answer::Integer(i64::from(integer))
+                }
+            }
+        )*
+    };
+}
+
+impl_from_narrow_integer!(u8, u16, u32, i8, i16, i32, i64);
+
+impl From<i128> for Answer {
+    fn from(integer: i128) -> Self {
+        match i64::try_from(integer) {
+            Ok(integer) => Answer::Integer(integer),
+            Err(_) => Answer::BigInteger(integer),
+        }
+    }
+}
+
+impl From<u64> for Answer {
+    fn from(integer: u64) -> Self {
+        Answer::from(i128::from(integer))
+    }
+}
+
+impl From<usize> for Answer {
+    fn from(integer: usize) -> Self {
+        Answer::from(integer as u64)
+    }
+}
+
+impl From<String> for Answer {
+    fn from(text: String) -> Self {
+        Answer::Text(text)
+    }
+}
+
+impl From<&str> for Answer {
+    fn from(text: &str) -> Self {
+        Answer::Text(text.to_string())
+    }
+}
+
+#[test]
+fn test_integers_use_narrowest_variant() {
+    assert_eq!(Answer::from(1676_u32), Answer::Integer(1676));
+    assert_eq!(Answer::from(1676_u64), Answer::from(1676_i128));
+    assert_eq!(
+        Answer::from(u64::MAX),
+        Answer::BigInteger(18446744073709551615)
+    );
+    assert_eq!(Answer::from(u64::MAX).answer_type(), "big-integer");
+    assert_eq!(Answer::from(-7_i64).to_string(), "-7");
+    assert_eq!(Answer::from("HLBUBGFR").answer_type(), "text");
+}
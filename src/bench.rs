@@ -0,0 +1,300 @@
+use std::fmt;
+use std::fs;
+use std::io;
+use std::time::{Duration, Instant};
+
+use crate::error::Error;
+use crate::solver::DynSolver;
+
+/// A median this much slower than the saved baseline counts as a
+/// regression.
+pub const REGRESSION_THRESHOLD_PERCENT: f64 = 10.0;
+
+/// Summary statistics over repeated timings of one step.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Stats {
+    pub runs: usize,
+    pub min: Duration,
+    pub median: Duration,
+    pub mean: Duration,
+    pub stddev: Duration,
+}
+
+impl Stats {
+    /// Summarises `samples`, which must not be empty. The standard
+    /// deviation is the sample standard deviation.
+    pub fn from_samples(samples: &[Duration]) -> Self {
+        assert!(!samples.is_empty(), "At least one sample is required");
+        let mut sorted = samples.to_vec();
+        sorted.sort();
+        let middle = sorted.len() / 2;
+        let median = match sorted.len() % 2 {
+            0 => (sorted[middle - 1] + sorted[middle]) / 2,
+            _ => sorted[middle],
+        };
+        let seconds = samples
+            .iter()
+            .map(|sample| sample.as_secs_f64())
+            .collect::<Vec<f64>>();
+        let mean = seconds.iter().sum::<f64>() / seconds.len() as f64;
+        let variance = match seconds.len() {
+            1 => 0.0,
+            n => {
+                seconds
+                    .iter()
+                    .map(|sample| (sample - mean).powi(2))
+                    .sum::<f64>()
+                    / (n - 1) as f64
+            }
+        };
+        Stats {
+            runs: samples.len(),
+            min: sorted[0],
+            median,
+            mean: Duration::from_secs_f64(mean),
+            stddev: Duration::from_secs_f64(variance.sqrt()),
+        }
+    }
+}
+
+/// Which step of solving a puzzle was timed.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Stage {
+    Parse,
+    Solve,
+}
+
+impl fmt::Display for Stage {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Stage::Parse => write!(f, "parse"),
+            Stage::Solve => write!(f, "solve"),
+        }
+    }
+}
+
+/// Timings for one stage of one part of one day on one input. This is
+/// also what a saved benchmark file holds, one per line.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Measurement {
+    pub day: u8,
+    pub part: u8,
+    pub input_path: String,
+    pub stage: Stage,
+    pub stats: Stats,
+}
+
+impl Measurement {
+    fn same_benchmark(&self, other: &Measurement) -> bool {
+        self.day == other.day
+            && self.part == other.part
+            && self.input_path == other.input_path
+            && self.stage == other.stage
+    }
+}
+
+/// Parses `puzzle_input` and solves `part` with `solver` `warm_up_runs`
+/// times without timing them, then `runs` more times, timing parsing
+/// and solving separately.
+pub fn bench(
+    solver: &dyn DynSolver,
+    part: u8,
+    input_path: &str,
+    puzzle_input: &str,
+    warm_up_runs: usize,
+    runs: usize,
+) -> Result<[Measurement; 2], Error> {
+    let mut parse_samples = Vec::with_capacity(runs);
+    let mut solve_samples = Vec::with_capacity(runs);
+    for run in 0..warm_up_runs + runs {
+        let start = Instant::now();
+        let input = solver.parse(puzzle_input.to_string())?;
+        let parse_time = start.elapsed();
+        let start = Instant::now();
+        solver.solve(input.as_ref(), part)?;
+        let solve_time = start.elapsed();
+        if run >= warm_up_runs {
+            parse_samples.push(parse_time);
+            solve_samples.push(solve_time);
+        }
+    }
+    let measurement = |stage, samples: &[Duration]| Measurement {
+        day: solver.day(),
+        part,
+        input_path: input_path.to_string(),
+        stage,
+        stats: Stats::from_samples(samples),
+    };
+    Ok([
+        measurement(Stage::Parse, &parse_samples),
+        measurement(Stage::Solve, &solve_samples),
+    ])
+}
+
+const SAVED_HEADER: &str = "# day\tpart\tinput\tstage\truns\tmin_ns\tmedian_ns\tmean_ns\tstddev_ns";
+
+fn format_saved(measurements: &[Measurement]) -> String {
+    let mut saved = String::from(SAVED_HEADER);
+    saved.push('\n');
+    for measurement in measurements {
+        let stats = &measurement.stats;
+        saved.push_str(&format!(
+            "{}\t{}\t{}\t{}\t{}\t{}\t{}\t{}\t{}\n",
+            measurement.day,
+            measurement.part,
+            measurement.input_path,
+            measurement.stage,
+            stats.runs,
+            stats.min.as_nanos(),
+            stats.median.as_nanos(),
+            stats.mean.as_nanos(),
+            stats.stddev.as_nanos()
+        ));
+    }
+    saved
+}
+
+fn parse_saved(saved: &str) -> Result<Vec<Measurement>, String> {
+    saved
+        .lines()
+        .enumerate()
+        .filter(|(_, line)| !line.trim().is_empty() && !line.starts_with('#'))
+        .map(|(index, line)| {
+            let invalid = || {
+                format!(
+                    "line {}: expected a saved benchmark, found {:?}",
+                    index + 1,
+                    line
+                )
+            };
+            let fields = line.split('\t').collect::<Vec<&str>>();
+            if fields.len() != 9 {
+                return Err(invalid());
+            }
+            let nanos = |field: &str| {
+                field
+                    .parse::<u64>()
+                    .map(Duration::from_nanos)
+                    .map_err(|_| invalid())
+            };
+            Ok(Measurement {
+                day: fields[0].parse().map_err(|_| invalid())?,
+                part: fields[1].parse().map_err(|_| invalid())?,
+                input_path: fields[2].to_string(),
+                stage: match fields[3] {
+                    "parse" => Stage::Parse,
+                    "solve" => Stage::Solve,
+                    _ => return Err(invalid()),
+                },
+                stats: Stats {
+                    runs: fields[4].parse().map_err(|_| invalid())?,
+                    min: nanos(fields[5])?,
+                    median: nanos(fields[6])?,
+                    mean: nanos(fields[7])?,
+                    stddev: nanos(fields[8])?,
+                },
+            })
+        })
+        .collect()
+}
+
+/// Loads measurements saved by [`save`].
+pub fn load(path: &str) -> io::Result<Vec<Measurement>> {
+    parse_saved(&fs::read_to_string(path)?)
+        .map_err(|message| io::Error::new(io::ErrorKind::InvalidData, message))
+}
+
+/// Saves `measurements` to `path`, replacing any earlier measurements
+/// of the same benchmarks and keeping the rest.
+pub fn save(path: &str, measurements: &[Measurement]) -> io::Result<()> {
+    let mut saved = match load(path) {
+        Ok(saved) => saved,
+        Err(error) if error.kind() == io::ErrorKind::NotFound => Vec::new(),
+        Err(error) => return Err(error),
+    };
+    saved.retain(|old| !measurements.iter().any(|new| new.same_benchmark(old)));
+    saved.extend(measurements.iter().cloned());
+    fs::write(path, format_saved(&saved))
+}
+
+/// How a measurement compares with a saved baseline.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Comparison {
+    pub baseline_median: Duration,
+    /// Percentage change in the median; positive is slower.
+    pub change_percent: f64,
+}
+
+impl Comparison {
+    pub fn is_regression(&self) -> bool {
+        self.change_percent > REGRESSION_THRESHOLD_PERCENT
+    }
+}
+
+/// Compares `measurement` with the matching entry of `baseline`, if
+/// there is one.
+pub fn compare(measurement: &Measurement, baseline: &[Measurement]) -> Option<Comparison> {
+    let old = baseline
+        .iter()
+        .find(|old| old.same_benchmark(measurement))?;
+    let old_median = old.stats.median.as_secs_f64();
+    let new_median = measurement.stats.median.as_secs_f64();
+    Some(Comparison {
+        baseline_median: old.stats.median,
+        change_percent: match old_median > 0.0 {
+            true => (new_median - old_median) / old_median * 100.0,
+            false => 0.0,
+        },
+    })
+}
+
+#[test]
+fn test_stats_from_samples() {
+    let samples = [5, 1, 3, 2]
+        .iter()
+        .map(|millis| Duration::from_millis(*millis))
+        .collect::<Vec<Duration>>();
+    let stats = Stats::from_samples(&samples);
+    assert_eq!(stats.runs, 4);
+    assert_eq!(stats.min, Duration::from_millis(1));
+    assert_eq!(stats.median, Duration::from_micros(2500));
+    assert_eq!(stats.mean.as_micros(), 2750);
+    // Sample variance is 2.917ms², so the standard deviation is 1.708ms.
+    assert_eq!(stats.stddev.as_micros(), 1707);
+    assert_eq!(
+        Stats::from_samples(&[Duration::from_millis(7)]).stddev,
+        Duration::from_millis(0)
+    );
+}
+
+#[test]
+fn test_saved_round_trip_and_compare() {
+    let stats = Stats::from_samples(&[Duration::from_millis(10), Duration::from_millis(12)]);
+    let measurements = vec![
+        Measurement {
+            day: 15,
+            part: 1,
+            input_path: String::from("input/15.txt"),
+            stage: Stage::Parse,
+            stats,
+        },
+        Measurement {
+            day: 15,
+            part: 1,
+            input_path: String::from("input/15.txt"),
+            stage: Stage::Solve,
+            stats,
+        },
+    ];
+    let saved = parse_saved(&format_saved(&measurements)).unwrap();
+    assert_eq!(saved, measurements);
+
+    let mut slower = measurements[1].clone();
+    slower.stats.median = Duration::from_millis(13);
+    let comparison = compare(&slower, &saved).unwrap();
+    assert_eq!(comparison.baseline_median, Duration::from_millis(11));
+    assert!(comparison.is_regression());
+    assert!(!compare(&measurements[0], &saved).unwrap().is_regression());
+    slower.part = 2;
+    assert_eq!(compare(&slower, &saved), None);
+}
@@ -0,0 +1,51 @@
+use std::cell::RefCell;
+use std::panic;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+
+thread_local! {
+    static CANCELLATION_FLAG: RefCell<Option<Arc<AtomicBool>>> = const { RefCell::new(None) };
+}
+
+/// The payload a cancelled solver unwinds with.
+#[derive(Debug)]
+pub struct Cancelled;
+
+/// Makes [`checkpoint`] on the current thread give up once `flag` is
+/// set.
+pub fn set_cancellation_flag(flag: Arc<AtomicBool>) {
+    CANCELLATION_FLAG.with(|cancellation_flag| *cancellation_flag.borrow_mut() = Some(flag));
+}
+
+/// Gives up on the current solver if the runner has asked it to stop,
+/// by unwinding with a [`Cancelled`] payload.
+///
+/// Long-running loops in solvers should call this every so often. It
+/// is cheap, and does nothing unless the solver is running under a
+/// `--timeout`. Unwinding this way does not print a panic message.
+pub fn checkpoint() {
+    let cancelled = CANCELLATION_FLAG.with(|cancellation_flag| {
+        cancellation_flag
+            .borrow()
+            .as_ref()
+            .is_some_and(|flag| flag.load(Ordering::Relaxed))
+    });
+    if cancelled {
+        panic::resume_unwind(Box::new(Cancelled));
+    }
+}
+
+#[test]
+fn test_checkpoint_unwinds_once_cancelled() {
+    std::thread::spawn(|| {
+        checkpoint();
+        let flag = Arc::new(AtomicBool::new(false));
+        set_cancellation_flag(Arc::clone(&flag));
+        checkpoint();
+        flag.store(true, Ordering::Relaxed);
+        let payload = panic::catch_unwind(checkpoint).unwrap_err();
+        assert!(payload.is::<Cancelled>());
+    })
+    .join()
+    .unwrap();
+}
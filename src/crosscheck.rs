@@ -0,0 +1,324 @@
+use std::any::Any;
+use std::panic::{self, AssertUnwindSafe};
+
+use crate::answer::Answer;
+use crate::error::Error;
+use crate::generate;
+use crate::runner;
+use crate::solver;
+use crate::{day07, day17, day21, day22};
+
+/// A naive solution to one part of a day's puzzle, to check the real
+/// solver against.
+pub struct ReferenceSolver {
+    pub day: u8,
+    pub part: u8,
+    /// Solves input parsed by the day's solver, returning `None` if the
+    /// puzzle has no answer.
+    pub solve: fn(&dyn Any) -> Option<Answer>,
+    /// The size of input to generate, if the reference solver is too
+    /// slow for inputs the size of a real puzzle input.
+    pub generated_size: Option<usize>,
+}
+
+fn downcast<T: 'static>(input: &dyn Any) -> &T {
+    input
+        .downcast_ref::<T>()
+        .expect("Input was not parsed by this day's solver")
+}
+
+/// Every reference solver, in order of day and part.
+pub static REFERENCE_SOLVERS: &[ReferenceSolver] = &[
+    ReferenceSolver {
+        day: 7,
+        part: 1,
+        solve: |input| Some(day07::reference_part_1(downcast::<Vec<i64>>(input)).into()),
+        generated_size: None,
+    },
+    ReferenceSolver {
+        day: 17,
+        part: 1,
+        solve: |input| day17::reference_part_1(downcast(input)).map(Answer::from),
+        generated_size: None,
+    },
+    ReferenceSolver {
+        day: 17,
+        part: 2,
+        solve: |input| Some(day17::reference_part_2(downcast(input)).into()),
+        generated_size: None,
+    },
+    ReferenceSolver {
+        day: 21,
+        part: 1,
+        solve: |input| Some(day21::reference_part_1(downcast(input)).into()),
+        generated_size: None,
+    },
+    ReferenceSolver {
+        day: 21,
+        part: 2,
+        solve: |input| Some(day21::reference_part_2(downcast(input)).into()),
+        generated_size: None,
+    },
+    ReferenceSolver {
+        day: 22,
+        part: 2,
+        solve: |input| {
+            Some(day22::reference_part_2(downcast::<Vec<day22::RebootStep>>(input)).into())
+        },
+        generated_size: Some(20),
+    },
+];
+
+/// The days with at least one reference solver, in order.
+pub fn reference_days() -> Vec<u8> {
+    let mut days = REFERENCE_SOLVERS
+        .iter()
+        .map(|reference| reference.day)
+        .collect::<Vec<u8>>();
+    days.dedup();
+    days
+}
+
+/// A part for which the solver and the reference solver disagree.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Disagreement {
+    pub part: u8,
+    /// The solver's answer, or why it had none.
+    pub solver: String,
+    /// The reference solver's answer, or why it had none.
+    pub reference: String,
+}
+
+/// Parses `puzzle_input` for `day` and solves every part that has a
+/// reference solver both ways. Returns the parts whose answers differ,
+/// or `None` if the input does not parse.
+///
+/// Having no answer counts as agreeing only if the solver reports the
+/// puzzle as unsolvable. A panic never agrees with anything.
+pub fn crosscheck(day: u8, puzzle_input: &str) -> Option<Vec<Disagreement>> {
+    let solver = solver::find_solver(day)?;
+    let input = panic::catch_unwind(AssertUnwindSafe(|| solver.parse(puzzle_input.to_string())))
+        .ok()?
+        .ok()?;
+    let mut disagreements = Vec::new();
+    for reference in REFERENCE_SOLVERS
+        .iter()
+        .filter(|reference| reference.day == day)
+    {
+        let answer = panic::catch_unwind(AssertUnwindSafe(|| {
+            solver.solve(input.as_ref(), reference.part)
+        }));
+        let reference_answer =
+            panic::catch_unwind(AssertUnwindSafe(|| (reference.solve)(input.as_ref())));
+        let agree = match (&answer, &reference_answer) {
+            (Ok(Ok(answer)), Ok(Some(reference_answer))) => answer == reference_answer,
+            (Ok(Err(Error::Unsolvable { .. })), Ok(None)) => true,
+            _ => false,
+        };
+        if !agree {
+            disagreements.push(Disagreement {
+                part: reference.part,
+                solver: match answer {
+                    Ok(Ok(answer)) => answer.to_string(),
+                    Ok(Err(error)) => error.to_string(),
+                    Err(_) => String::from("panicked"),
+                },
+                reference: match reference_answer {
+                    Ok(Some(answer)) => answer.to_string(),
+                    Ok(None) => String::from("no answer"),
+                    Err(_) => String::from("panicked"),
+                },
+            });
+        }
+    }
+    Some(disagreements)
+}
+
+fn disagrees_on(day: u8, part: u8, puzzle_input: &str) -> bool {
+    crosscheck(day, puzzle_input).is_some_and(|disagreements| {
+        disagreements
+            .iter()
+            .any(|disagreement| disagreement.part == part)
+    })
+}
+
+/// Removes as many lines from `puzzle_input` as it can while
+/// `still_fails` holds, first in large chunks and then line by line.
+pub fn minimize_lines(puzzle_input: &str, still_fails: impl Fn(&str) -> bool) -> String {
+    let join = |lines: &[&str]| lines.join("\n") + "\n";
+    let mut lines = puzzle_input.lines().collect::<Vec<&str>>();
+    let mut chunk_size = lines.len() / 2;
+    while chunk_size > 0 {
+        let mut start = 0;
+        while start < lines.len() {
+            let end = (start + chunk_size).min(lines.len());
+            let candidate = [&lines[..start], &lines[end..]].concat();
+            match !candidate.is_empty() && still_fails(&join(&candidate)) {
+                true => lines = candidate,
+                false => start = end,
+            }
+        }
+        chunk_size /= 2;
+    }
+    join(&lines)
+}
+
+/// Where the inputs to cross-check come from.
+#[derive(Debug, Clone)]
+pub enum Inputs {
+    /// `count` generated inputs, with seeds counting up from
+    /// `first_seed`. `size` overrides the size each day generates.
+    Generated {
+        first_seed: u64,
+        count: u64,
+        size: Option<usize>,
+    },
+    /// Input files for a single day.
+    Files(Vec<String>),
+}
+
+/// Cross-checks every day in `days` on `inputs`, printing each
+/// disagreement along with the smallest input found that still shows
+/// it, and a summary for each day.
+///
+/// Returns false if there was any disagreement or an input could not
+/// be read or parsed.
+pub fn run(days: &[u8], inputs: &Inputs) -> bool {
+    let mut all_agree = true;
+    for day in days {
+        let cases = match inputs {
+            Inputs::Generated {
+                first_seed,
+                count,
+                size,
+            } => (*first_seed..first_seed + count)
+                .map(|seed| {
+                    let (puzzle_input, size) = generated_input(*day, seed, *size);
+                    (
+                        format!("seed {}", seed),
+                        Ok(puzzle_input),
+                        size.map(|size| (seed, size)),
+                    )
+                })
+                .collect::<Vec<_>>(),
+            Inputs::Files(paths) => paths
+                .iter()
+                .map(|path| (path.clone(), runner::read_puzzle_input(path), None))
+                .collect(),
+        };
+        let mut disagreement_count = 0;
+        for (label, puzzle_input, generated_from) in cases.iter() {
+            let puzzle_input = match puzzle_input {
+                Ok(puzzle_input) => puzzle_input,
+                Err(error) => {
+                    eprintln!("error: {}: {}", label, error);
+                    all_agree = false;
+                    continue;
+                }
+            };
+            let disagreements = match crosscheck(*day, puzzle_input) {
+                Some(disagreements) => disagreements,
+                None => {
+                    eprintln!("error: day {} ({}): the input does not parse", day, label);
+                    all_agree = false;
+                    continue;
+                }
+            };
+            for disagreement in disagreements {
+                disagreement_count += 1;
+                println!(
+                    "DISAGREE day {} part {} ({}): solver says {}, reference says {}",
+                    day, disagreement.part, label, disagreement.solver, disagreement.reference
+                );
+                let (shrunk_label, shrunk_input) = match generated_from {
+                    Some((seed, size)) => {
+                        smallest_generated_input(*day, disagreement.part, *seed, *size)
+                    }
+                    None => (label.clone(), puzzle_input.clone()),
+                };
+                let minimal_input = minimize_lines(&shrunk_input, |puzzle_input| {
+                    disagrees_on(*day, disagreement.part, puzzle_input)
+                });
+                println!(
+                    "smallest failing input found ({}, {} of {} lines):\n{}",
+                    shrunk_label,
+                    minimal_input.lines().count(),
+                    shrunk_input.lines().count(),
+                    minimal_input.trim_end()
+                );
+            }
+        }
+        println!(
+            "day {}: {} inputs checked, {} disagreements",
+            day,
+            cases.len(),
+            disagreement_count
+        );
+        all_agree &= disagreement_count == 0;
+    }
+    all_agree
+}
+
+/// Generates the input for `day` and `seed`, returning it along with
+/// the size used.
+fn generated_input(day: u8, seed: u64, size: Option<usize>) -> (String, Option<usize>) {
+    let size = size
+        .or_else(|| {
+            REFERENCE_SOLVERS
+                .iter()
+                .find(|reference| reference.day == day)?
+                .generated_size
+        })
+        .or_else(|| Some(generate::find_generator(day)?.default_size));
+    (
+        generate::generate(day, seed, size).unwrap_or_default(),
+        size,
+    )
+}
+
+/// Finds the smallest size for which the input generated from `seed`
+/// still disagrees on `part`, up to `size`.
+fn smallest_generated_input(day: u8, part: u8, seed: u64, size: usize) -> (String, String) {
+    for smaller_size in 0..size {
+        let (puzzle_input, _) = generated_input(day, seed, Some(smaller_size));
+        if disagrees_on(day, part, &puzzle_input) {
+            return (
+                format!("seed {}, size {}", seed, smaller_size),
+                puzzle_input,
+            );
+        }
+    }
+    let (puzzle_input, _) = generated_input(day, seed, Some(size));
+    (format!("seed {}, size {}", seed, size), puzzle_input)
+}
+
+#[test]
+fn test_reference_solvers_agree() {
+    for day in reference_days() {
+        for seed in 0..3 {
+            let (puzzle_input, _) = generated_input(day, seed, None);
+            assert_eq!(
+                crosscheck(day, &puzzle_input),
+                Some(vec![]),
+                "day {} seed {}",
+                day,
+                seed
+            );
+        }
+    }
+    // No x velocity leaves the probe stopped in this target area.
+    let (puzzle_input, _) = generated_input(17, 7, None);
+    assert_eq!(crosscheck(17, &puzzle_input), Some(vec![]));
+    let puzzle_input =
+        std::fs::read_to_string(crate::inputs::input_path(22, Some("sample2"))).unwrap();
+    assert_eq!(crosscheck(22, &puzzle_input), Some(vec![]));
+}
+
+#[test]
+fn test_minimize_lines() {
+    let still_fails = |puzzle_input: &str| {
+        puzzle_input.lines().any(|line| line == "b") && puzzle_input.lines().any(|line| line == "e")
+    };
+    assert_eq!(minimize_lines("a\nb\nc\nd\ne\nf\n", still_fails), "b\ne\n");
+    assert_eq!(minimize_lines("b\n", |_| true), "b\n");
+}
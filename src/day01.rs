@@ -1,39 +1,47 @@
-pub(crate) fn solve_part_1(puzzle_input: String) -> u32 {
-    let mut sonar_sweep_report = puzzle_input.split("\n");
+use crate::answer::Answer;
+use crate::error::{Error, ParseContext, ParseError};
+use crate::generate::Rng;
+use crate::solver::Solver;
+
+pub fn parse_sonar_sweep_report(puzzle_input: String) -> Result<Vec<u32>, ParseError> {
+    let context = ParseContext::new(1, &puzzle_input);
+    puzzle_input
+        .split("\n")
+        .take_while(|reading| !reading.is_empty())
+        .map(|reading| context.parse::<u32>(reading, "a sonar sweep reading"))
+        .collect()
+}
+
+pub fn solve_part_1(sonar_sweep_report: &[u32]) -> u32 {
+    let mut sonar_sweep_report = sonar_sweep_report.iter();
     let mut increases: u32 = 0;
-    let mut previous_reading: u32 = sonar_sweep_report
-        .next()
-        .expect("Failed to find the first reading in the sonar sweep report.")
-        .parse::<u32>()
-        .expect("Failed to parse the first reading in the sonar sweep report.");
-
-    for current_reading in sonar_sweep_report {
-        if current_reading == "" {
-            break;
-        }
-        let current_reading = current_reading
-            .parse::<u32>()
-            .expect("Failed to parse subsequent reading in the sonar sweep report.");
+    let mut previous_reading: u32 = match sonar_sweep_report.next() {
+        Some(first_reading) => *first_reading,
+        None => return 0,
+    };
+
+    for &current_reading in sonar_sweep_report {
         if current_reading > previous_reading {
             increases += 1;
         }
         previous_reading = current_reading;
     }
-    return increases;
+    increases
 }
 
-pub(crate) fn solve_part_2(puzzle_input: String) -> u32 {
-    let mut sonar_sweep_report = puzzle_input
-        .split("\n")
-        .filter_map(|reading: &str| -> Option<u32> { reading.parse::<u32>().ok() });
+pub fn solve_part_2(sonar_sweep_report: &[u32]) -> u32 {
+    let mut sonar_sweep_report = sonar_sweep_report.iter().copied();
 
     let mut increases: u32 = 0;
 
-    let mut previous_window = [
-        sonar_sweep_report.next().unwrap(),
-        sonar_sweep_report.next().unwrap(),
-        sonar_sweep_report.next().unwrap(),
-    ];
+    let mut previous_window = match (
+        sonar_sweep_report.next(),
+        sonar_sweep_report.next(),
+        sonar_sweep_report.next(),
+    ) {
+        (Some(first), Some(second), Some(third)) => [first, second, third],
+        _ => return 0,
+    };
 
     for new_reading in sonar_sweep_report {
         let current_window = [previous_window[1], previous_window[2], new_reading];
@@ -46,15 +54,48 @@ pub(crate) fn solve_part_2(puzzle_input: String) -> u32 {
     increases
 }
 
+/// Generates `size` depths that wander up and down but mostly
+/// increase, like a real sonar sweep.
+pub fn generate_input(rng: &mut Rng, size: usize) -> String {
+    let mut depth = rng.range(100..=200);
+    let mut report = String::new();
+    for _ in 0..size {
+        report.push_str(&format!("{}\n", depth));
+        depth = (depth + rng.range(-20..=30)).max(0);
+    }
+    report
+}
+
+pub struct Day01;
+
+impl Solver for Day01 {
+    const DAY: u8 = 1;
+    type Input = Vec<u32>;
+
+    fn parse(&self, puzzle_input: String) -> Result<Vec<u32>, ParseError> {
+        parse_sonar_sweep_report(puzzle_input)
+    }
+
+    fn solve_part_1(&self, sonar_sweep_report: &Vec<u32>) -> Result<Answer, Error> {
+        Ok(solve_part_1(sonar_sweep_report).into())
+    }
+
+    fn solve_part_2(&self, sonar_sweep_report: &Vec<u32>) -> Result<Answer, Error> {
+        Ok(solve_part_2(sonar_sweep_report).into())
+    }
+}
+
 #[cfg(test)]
 use super::test_helpers;
 
 #[test]
 fn test_part_1() {
-    assert_eq!(solve_part_1(test_helpers::load_puzzle_input(1)), 1676);
+    let sonar_sweep_report = parse_sonar_sweep_report(test_helpers::load_puzzle_input(1)).unwrap();
+    assert_eq!(solve_part_1(&sonar_sweep_report), 1676);
 }
 
 #[test]
 fn test_part_2() {
-    assert_eq!(solve_part_2(test_helpers::load_puzzle_input(1)), 1706);
+    let sonar_sweep_report = parse_sonar_sweep_report(test_helpers::load_puzzle_input(1)).unwrap();
+    assert_eq!(solve_part_2(&sonar_sweep_report), 1706);
 }
@@ -1,37 +1,35 @@
-enum SubMovement {
+use crate::answer::Answer;
+use crate::error::{Error, ParseContext, ParseError};
+use crate::generate::Rng;
+use crate::solver::Solver;
+
+pub enum SubMovement {
     Forward(u32),
     Down(u32),
     Up(u32),
 }
 
-fn parse_sub_movements(puzzle_input: String) -> Vec<SubMovement> {
+pub fn parse_sub_movements(puzzle_input: String) -> Result<Vec<SubMovement>, ParseError> {
+    let context = ParseContext::new(2, &puzzle_input);
     puzzle_input
         .split("\n")
-        .filter_map(|movement: &str| -> Option<SubMovement> {
-            if movement == "" {
-                return None;
-            }
+        .filter(|movement| !movement.is_empty())
+        .map(|movement: &str| -> Result<SubMovement, ParseError> {
             let mut components = movement.split(" ");
-            let direction = components
-                .next()
-                .expect("Failed to find direction in component");
-            let magnitude = components
-                .next()
-                .expect("Failed to find magnitude in component")
-                .parse::<u32>()
-                .expect("Failed to parse numeric magnitude");
+            let direction = context.next(&mut components, movement, "a direction")?;
+            let magnitude = context.next(&mut components, movement, "a magnitude")?;
+            let magnitude = context.parse::<u32>(magnitude, "a magnitude")?;
             match direction {
-                "forward" => Some(SubMovement::Forward(magnitude)),
-                "down" => Some(SubMovement::Down(magnitude)),
-                "up" => Some(SubMovement::Up(magnitude)),
-                _ => panic!("Unsupported direction in component"),
+                "forward" => Ok(SubMovement::Forward(magnitude)),
+                "down" => Ok(SubMovement::Down(magnitude)),
+                "up" => Ok(SubMovement::Up(magnitude)),
+                _ => Err(context.error(direction, "\"forward\", \"down\" or \"up\"")),
             }
         })
         .collect()
 }
 
-pub(crate) fn solve_part_1(puzzle_input: String) -> u32 {
-    let sub_directions = parse_sub_movements(puzzle_input);
+pub fn solve_part_1(sub_directions: &[SubMovement]) -> u32 {
     let sub_directions = sub_directions.iter();
 
     let mut depth: u32 = 0;
@@ -53,8 +51,7 @@ pub(crate) fn solve_part_1(puzzle_input: String) -> u32 {
     depth * horizontal_position
 }
 
-pub(crate) fn solve_part_2(puzzle_input: String) -> u32 {
-    let sub_directions = parse_sub_movements(puzzle_input);
+pub fn solve_part_2(sub_directions: &[SubMovement]) -> u32 {
     let sub_directions = sub_directions.iter();
 
     let mut aim: i32 = 0;
@@ -78,15 +75,59 @@ pub(crate) fn solve_part_2(puzzle_input: String) -> u32 {
     depth * horizontal_position
 }
 
+/// Generates `size` commands that never take the submarine above the
+/// surface.
+pub fn generate_input(rng: &mut Rng, size: usize) -> String {
+    let mut depth = 0;
+    let mut commands = String::new();
+    for _ in 0..size {
+        let units = rng.range(1..=9);
+        let direction = match rng.below(3) {
+            0 => "forward",
+            1 if depth >= units => {
+                depth -= units;
+                "up"
+            }
+            _ => {
+                depth += units;
+                "down"
+            }
+        };
+        commands.push_str(&format!("{} {}\n", direction, units));
+    }
+    commands
+}
+
+pub struct Day02;
+
+impl Solver for Day02 {
+    const DAY: u8 = 2;
+    type Input = Vec<SubMovement>;
+
+    fn parse(&self, puzzle_input: String) -> Result<Vec<SubMovement>, ParseError> {
+        parse_sub_movements(puzzle_input)
+    }
+
+    fn solve_part_1(&self, sub_directions: &Vec<SubMovement>) -> Result<Answer, Error> {
+        Ok(solve_part_1(sub_directions).into())
+    }
+
+    fn solve_part_2(&self, sub_directions: &Vec<SubMovement>) -> Result<Answer, Error> {
+        Ok(solve_part_2(sub_directions).into())
+    }
+}
+
 #[cfg(test)]
 use super::test_helpers;
 
 #[test]
 fn test_part_1() {
-    assert_eq!(solve_part_1(test_helpers::load_puzzle_input(2)), 1804520);
+    let sub_directions = parse_sub_movements(test_helpers::load_puzzle_input(2)).unwrap();
+    assert_eq!(solve_part_1(&sub_directions), 1804520);
 }
 
 #[test]
 fn test_part_2() {
-    assert_eq!(solve_part_2(test_helpers::load_puzzle_input(2)), 1971095320);
+    let sub_directions = parse_sub_movements(test_helpers::load_puzzle_input(2)).unwrap();
+    assert_eq!(solve_part_2(&sub_directions), 1971095320);
 }
@@ -1,7 +1,30 @@
-pub(crate) fn solve_part_1(puzzle_input: String) -> u32 {
+use crate::answer::Answer;
+use crate::error::{Error, ParseContext, ParseError};
+use crate::generate::Rng;
+use crate::solver::Solver;
+
+pub fn parse_diagnostic_report(puzzle_input: String) -> Result<Vec<String>, ParseError> {
+    let context = ParseContext::new(3, &puzzle_input);
     let diagnostic_report = puzzle_input
         .split("\n")
-        .filter(|report_number| (report_number.len() != 0));
+        .filter(|report_number| !report_number.is_empty())
+        .map(|report_number| {
+            if let Some(position) = report_number.find(|c| c != '0' && c != '1') {
+                return Err(context.error(&report_number[position..=position], "a binary digit"));
+            }
+            if report_number.len() != 12 {
+                return Err(context.error(report_number, "a 12 bit binary number"));
+            }
+            Ok(String::from(report_number))
+        })
+        .collect::<Result<Vec<String>, ParseError>>()?;
+    if diagnostic_report.is_empty() {
+        return Err(context.missing(&puzzle_input, "a 12 bit binary number"));
+    }
+    Ok(diagnostic_report)
+}
+
+pub fn solve_part_1(diagnostic_report: &[String]) -> u32 {
     let mut report_size = 0;
     let mut bit_counts: [u32; 12] = [0; 12];
     for report_entry in diagnostic_report {
@@ -24,11 +47,10 @@ pub(crate) fn solve_part_1(puzzle_input: String) -> u32 {
     epsilon_rate * gamma_rate
 }
 
-pub(crate) fn solve_part_2(puzzle_input: String) -> u32 {
-    let diagnostic_report: Vec<&str> = puzzle_input
-        .split("\n")
-        .filter(|report_number| -> bool { report_number.len() != 0 })
-        .collect();
+/// Returns `None` if the bit criteria eliminate every number from the
+/// report.
+pub fn solve_part_2(diagnostic_report: &[String]) -> Option<u32> {
+    let diagnostic_report: Vec<&str> = diagnostic_report.iter().map(String::as_str).collect();
     let mut oxygen_generator_possibilities = diagnostic_report.clone();
     for bit_position in 0..12 {
         oxygen_generator_possibilities =
@@ -47,8 +69,10 @@ pub(crate) fn solve_part_2(puzzle_input: String) -> u32 {
         }
     }
 
-    u32::from_str_radix(oxygen_generator_possibilities[0], 2).unwrap()
-        * u32::from_str_radix(co2_scrubber_possibilities[0], 2).unwrap()
+    Some(
+        u32::from_str_radix(oxygen_generator_possibilities.first()?, 2).unwrap()
+            * u32::from_str_radix(co2_scrubber_possibilities.first()?, 2).unwrap(),
+    )
 }
 
 fn trim_diagnostic_report(
@@ -78,15 +102,64 @@ fn trim_diagnostic_report(
     trimmed_report
 }
 
+/// Generates `size` distinct random 12 bit binary numbers (at most
+/// 4096) for which the bit criteria leave one number each, so that
+/// part 2 has an answer.
+///
+/// Even distinct numbers can leave the CO2 scrubber rating with none,
+/// so this tries up to 100 reports before settling for the largest
+/// power of two numbers that differ only in their leading bits.
+pub fn generate_input(rng: &mut Rng, size: usize) -> String {
+    let size = size.clamp(2, 1 << 12);
+    let mut numbers = (0..1 << 12).collect::<Vec<u32>>();
+    for _ in 0..100 {
+        rng.shuffle(&mut numbers);
+        let diagnostic_report = numbers[..size]
+            .iter()
+            .map(|number| format!("{:012b}", number))
+            .collect::<Vec<String>>();
+        if solve_part_2(&diagnostic_report).is_some() {
+            return diagnostic_report.join("\n") + "\n";
+        }
+    }
+    let leading_bits = usize::BITS - 1 - size.leading_zeros();
+    (0..1 << leading_bits)
+        .map(|number: u32| format!("{:012b}\n", number << (12 - leading_bits)))
+        .collect()
+}
+
+pub struct Day03;
+
+impl Solver for Day03 {
+    const DAY: u8 = 3;
+    type Input = Vec<String>;
+
+    fn parse(&self, puzzle_input: String) -> Result<Vec<String>, ParseError> {
+        parse_diagnostic_report(puzzle_input)
+    }
+
+    fn solve_part_1(&self, diagnostic_report: &Vec<String>) -> Result<Answer, Error> {
+        Ok(solve_part_1(diagnostic_report).into())
+    }
+
+    fn solve_part_2(&self, diagnostic_report: &Vec<String>) -> Result<Answer, Error> {
+        solve_part_2(diagnostic_report)
+            .map(Answer::from)
+            .ok_or_else(|| Error::unsolvable(3, 2, "the bit criteria eliminate every number"))
+    }
+}
+
 #[cfg(test)]
 use super::test_helpers;
 
 #[test]
 fn test_part_1() {
-    assert_eq!(solve_part_1(test_helpers::load_puzzle_input(3)), 1092896);
+    let diagnostic_report = parse_diagnostic_report(test_helpers::load_puzzle_input(3)).unwrap();
+    assert_eq!(solve_part_1(&diagnostic_report), 1092896);
 }
 
 #[test]
 fn test_part_2() {
-    assert_eq!(solve_part_2(test_helpers::load_puzzle_input(3)), 4672151);
+    let diagnostic_report = parse_diagnostic_report(test_helpers::load_puzzle_input(3)).unwrap();
+    assert_eq!(solve_part_2(&diagnostic_report), Some(4672151));
 }
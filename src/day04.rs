@@ -1,15 +1,26 @@
-#[derive(Debug)]
+use crate::answer::Answer;
+use crate::error::{Error, ParseContext, ParseError};
+use crate::generate::Rng;
+use crate::solver::Solver;
+
+#[derive(Debug, Clone)]
 struct BingoSquare {
     number: u32,
     called: bool,
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 struct BingoBoard {
     //each inner vector is a row
     squares: Vec<Vec<BingoSquare>>,
 }
 
+#[derive(Debug)]
+pub struct BingoGame {
+    numbers_called: Vec<u32>,
+    boards: Vec<BingoBoard>,
+}
+
 impl BingoBoard {
     fn mark_number(&mut self, called_number: &u32) {
         for x in 0..5 {
@@ -59,78 +70,75 @@ impl BingoBoard {
     }
 }
 
-pub(crate) fn solve_part_1(puzzle_input: String) -> u32 {
+pub fn parse_bingo_game(puzzle_input: String) -> Result<BingoGame, ParseError> {
+    let context = ParseContext::new(4, &puzzle_input);
     let mut section_iterator = puzzle_input.split("\n\n");
 
     let numbers_called: Vec<u32> = section_iterator
         .next()
         .unwrap()
+        .trim_end()
         .split(",")
-        .map(|n| n.parse::<u32>().unwrap())
-        .collect();
+        .map(|n| context.parse::<u32>(n, "a bingo number"))
+        .collect::<Result<Vec<u32>, ParseError>>()?;
 
     let mut boards: Vec<BingoBoard> = Vec::new();
 
     for board_section in section_iterator {
         let new_squares = board_section
             .split("\n")
-            .filter(|line| line.len() != 0)
-            .map(|line| -> Vec<BingoSquare> {
-                line.split_whitespace()
-                    .map(|num| num.parse::<u32>().unwrap())
-                    .map(|num| BingoSquare {
-                        number: num,
-                        called: false,
+            .filter(|line| !line.is_empty())
+            .map(|line| -> Result<Vec<BingoSquare>, ParseError> {
+                let row = line
+                    .split_whitespace()
+                    .map(|num| context.parse::<u32>(num, "a bingo number"))
+                    .map(|num| {
+                        Ok(BingoSquare {
+                            number: num?,
+                            called: false,
+                        })
                     })
-                    .collect()
+                    .collect::<Result<Vec<BingoSquare>, ParseError>>()?;
+                if row.len() != 5 {
+                    return Err(context.error(line, "a row of 5 bingo numbers"));
+                }
+                Ok(row)
             })
-            .collect::<Vec<Vec<BingoSquare>>>();
+            .collect::<Result<Vec<Vec<BingoSquare>>, ParseError>>()?;
+        if new_squares.len() != 5 {
+            return Err(context.error(board_section.trim_end(), "a board with 5 rows"));
+        }
         boards.push(BingoBoard {
             squares: new_squares,
         });
     }
 
+    Ok(BingoGame {
+        numbers_called,
+        boards,
+    })
+}
+
+/// Returns `None` if no board ever wins.
+pub fn solve_part_1(bingo_game: &BingoGame) -> Option<u32> {
+    let numbers_called = &bingo_game.numbers_called;
+    let mut boards = bingo_game.boards.clone();
+
     for number_called in numbers_called.iter() {
         for board in &mut boards {
             board.mark_number(number_called);
             if board.is_winner() {
-                return board.sum_of_unmarked_squares() * number_called;
+                return Some(board.sum_of_unmarked_squares() * number_called);
             }
         }
     }
-    panic!("Failed to find winning board.");
+    None
 }
 
-pub(crate) fn solve_part_2(puzzle_input: String) -> u32 {
-    let mut section_iterator = puzzle_input.split("\n\n");
-
-    let numbers_called: Vec<u32> = section_iterator
-        .next()
-        .unwrap()
-        .split(",")
-        .map(|n| n.parse::<u32>().unwrap())
-        .collect();
-
-    let mut boards: Vec<BingoBoard> = Vec::new();
-
-    for board_section in section_iterator {
-        let new_squares = board_section
-            .split("\n")
-            .filter(|line| line.len() != 0)
-            .map(|line| -> Vec<BingoSquare> {
-                line.split_whitespace()
-                    .map(|num| num.parse::<u32>().unwrap())
-                    .map(|num| BingoSquare {
-                        number: num,
-                        called: false,
-                    })
-                    .collect()
-            })
-            .collect::<Vec<Vec<BingoSquare>>>();
-        boards.push(BingoBoard {
-            squares: new_squares,
-        });
-    }
+/// Returns `None` if some board never wins.
+pub fn solve_part_2(bingo_game: &BingoGame) -> Option<u32> {
+    let numbers_called = &bingo_game.numbers_called;
+    let mut boards = bingo_game.boards.clone();
 
     for number_called in numbers_called.iter() {
         for board in &mut boards {
@@ -145,17 +153,70 @@ pub(crate) fn solve_part_2(puzzle_input: String) -> u32 {
                 0
             })
             .sum();
-        println!("non-winning boards left: {}", non_winning_boards_left);
+        crate::log!(
+            Trace,
+            4,
+            "{} non-winning boards left after {} is called",
+            non_winning_boards_left,
+            number_called
+        );
         if non_winning_boards_left > 0 {
-            boards = boards
-                .into_iter()
-                .filter(|board| !board.is_winner())
-                .collect();
+            boards.retain(|board| !board.is_winner());
             continue;
         }
-        return boards.last().unwrap().sum_of_unmarked_squares() * number_called;
+        return Some(boards.last()?.sum_of_unmarked_squares() * number_called);
+    }
+    None
+}
+
+/// Generates a game with `size` boards (at least one), calling every
+/// number from 0 to 99 so that every board wins eventually.
+pub fn generate_input(rng: &mut Rng, size: usize) -> String {
+    let size = size.max(1);
+    let mut numbers = (0..100).collect::<Vec<u32>>();
+    rng.shuffle(&mut numbers);
+    let mut game = numbers
+        .iter()
+        .map(u32::to_string)
+        .collect::<Vec<String>>()
+        .join(",");
+    game.push('\n');
+    for _ in 0..size {
+        rng.shuffle(&mut numbers);
+        game.push('\n');
+        for row in numbers[..25].chunks(5) {
+            let row = row
+                .iter()
+                .map(|number| format!("{:>2}", number))
+                .collect::<Vec<String>>()
+                .join(" ");
+            game.push_str(&format!("{}\n", row));
+        }
+    }
+    game
+}
+
+pub struct Day04;
+
+impl Solver for Day04 {
+    const DAY: u8 = 4;
+    type Input = BingoGame;
+
+    fn parse(&self, puzzle_input: String) -> Result<BingoGame, ParseError> {
+        parse_bingo_game(puzzle_input)
+    }
+
+    fn solve_part_1(&self, bingo_game: &BingoGame) -> Result<Answer, Error> {
+        solve_part_1(bingo_game)
+            .map(Answer::from)
+            .ok_or_else(|| Error::unsolvable(4, 1, "no board ever wins"))
+    }
+
+    fn solve_part_2(&self, bingo_game: &BingoGame) -> Result<Answer, Error> {
+        solve_part_2(bingo_game)
+            .map(Answer::from)
+            .ok_or_else(|| Error::unsolvable(4, 2, "some board never wins"))
     }
-    panic!("Failed to find last winning board.");
 }
 
 #[cfg(test)]
@@ -163,10 +224,12 @@ use super::test_helpers;
 
 #[test]
 fn test_part_1() {
-    assert_eq!(solve_part_1(test_helpers::load_puzzle_input(4)), 55770);
+    let bingo_game = parse_bingo_game(test_helpers::load_puzzle_input(4)).unwrap();
+    assert_eq!(solve_part_1(&bingo_game), Some(55770));
 }
 
 #[test]
 fn test_part_2() {
-    assert_eq!(solve_part_2(test_helpers::load_puzzle_input(4)), 2980);
+    let bingo_game = parse_bingo_game(test_helpers::load_puzzle_input(4)).unwrap();
+    assert_eq!(solve_part_2(&bingo_game), Some(2980));
 }
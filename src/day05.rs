@@ -1,5 +1,11 @@
 use std::cmp;
 
+use crate::answer::Answer;
+use crate::error::{Error, ParseContext, ParseError};
+use crate::generate::Rng;
+use crate::grid::Grid;
+use crate::solver::Solver;
+
 #[derive(Debug)]
 struct Point {
     x: i32,
@@ -7,7 +13,7 @@ struct Point {
 }
 
 #[derive(Debug)]
-struct VentLine {
+pub struct VentLine {
     start_point: Point,
     end_point: Point,
 }
@@ -43,7 +49,7 @@ impl VentLine {
             };
             for x in x_range {
                 points_on_line.push(Point {
-                    x: x,
+                    x,
                     y: self.start_point.y,
                 });
             }
@@ -55,7 +61,7 @@ impl VentLine {
             for y in y_range {
                 points_on_line.push(Point {
                     x: self.start_point.x,
-                    y: y,
+                    y,
                 });
             }
         } else {
@@ -80,52 +86,69 @@ impl VentLine {
     }
 }
 
-fn get_vent_lines(puzzle_input: String) -> Vec<VentLine> {
+pub fn get_vent_lines(puzzle_input: String) -> Result<Vec<VentLine>, ParseError> {
+    let context = ParseContext::new(5, &puzzle_input);
+    let parse_ordinate = |n: &str| -> Result<i32, ParseError> {
+        match context.parse::<i32>(n, "a non-negative integer")? {
+            ordinate if ordinate < 0 => Err(context.error(n, "a non-negative integer")),
+            ordinate => Ok(ordinate),
+        }
+    };
+    let parse_point = |point_definition: &str| -> Result<Point, ParseError> {
+        let mut ordinate_iter = point_definition.split(",");
+        let x = context.next(&mut ordinate_iter, point_definition, "an x ordinate")?;
+        let y = context.next(&mut ordinate_iter, x, "\",\" followed by a y ordinate")?;
+        Ok(Point {
+            x: parse_ordinate(x)?,
+            y: parse_ordinate(y)?,
+        })
+    };
     puzzle_input
         .split("\n")
-        .filter(|line| line.len() > 0)
-        .map(|line_definition| -> VentLine {
-            // println!("Processing input line: {}", line_definition);
-            let mut vent_points = line_definition
-                .split(" -> ")
-                .map(|point_definition| -> Point {
-                    let mut ordinate_iter = point_definition
-                        .split(",")
-                        .map(|n| -> i32 { (n.parse::<i32>()).unwrap() });
-
-                    Point {
-                        x: ordinate_iter.next().unwrap(),
-                        y: ordinate_iter.next().unwrap(),
-                    }
-                });
+        .filter(|line| !line.is_empty())
+        .map(|line_definition| -> Result<VentLine, ParseError> {
+            let mut vent_points = line_definition.split(" -> ");
+            let start_point = context.next(&mut vent_points, line_definition, "a point")?;
+            let end_point = context.next(
+                &mut vent_points,
+                start_point,
+                "\" -> \" followed by a point",
+            )?;
 
-            VentLine {
-                start_point: vent_points.next().unwrap(),
-                end_point: vent_points.next().unwrap(),
+            let vent_line = VentLine {
+                start_point: parse_point(start_point)?,
+                end_point: parse_point(end_point)?,
+            };
+            let x_length = (vent_line.end_point.x - vent_line.start_point.x).abs();
+            let y_length = (vent_line.end_point.y - vent_line.start_point.y).abs();
+            if !vent_line.is_horizontal_or_vertical() && x_length != y_length {
+                return Err(context.error(
+                    line_definition,
+                    "a horizontal, vertical or 45 degree diagonal line",
+                ));
             }
+            Ok(vent_line)
         })
-        .collect::<Vec<VentLine>>()
+        .collect::<Result<Vec<VentLine>, ParseError>>()
 }
 
-fn make_ocean_floor(vent_lines: &Vec<&VentLine>) -> Vec<Vec<u32>> {
+fn make_ocean_floor(vent_lines: &[&VentLine]) -> Grid<u32> {
     let max_x: usize = vent_lines
         .iter()
         .map(|vent_line| cmp::max(vent_line.start_point.x, vent_line.end_point.x))
         .max()
-        .unwrap() as usize;
+        .unwrap_or(0) as usize;
     let max_y: usize = vent_lines
         .iter()
         .map(|vent_line| cmp::max(vent_line.start_point.y, vent_line.end_point.y))
         .max()
-        .unwrap() as usize;
+        .unwrap_or(0) as usize;
 
-    // println!("Got max_x: {}, max_y: {}", max_x, max_y);
-    vec![vec![0; max_x + 1]; max_y + 1]
+    crate::log!(Trace, 5, "ocean floor is {} by {}", max_x + 1, max_y + 1);
+    Grid::new(max_x + 1, max_y + 1, 0)
 }
 
-pub(crate) fn solve_part_1(puzzle_input: String) -> u32 {
-    let vent_lines = get_vent_lines(puzzle_input);
-
+pub fn solve_part_1(vent_lines: &[VentLine]) -> u32 {
     let horizontal_and_vertical_vent_lines = vent_lines
         .iter()
         .filter(|vent_line| vent_line.is_horizontal_or_vertical())
@@ -135,61 +158,89 @@ pub(crate) fn solve_part_1(puzzle_input: String) -> u32 {
 
     for vent_lines in &horizontal_and_vertical_vent_lines {
         for vent_line_point in vent_lines.get_points_on_line() {
-            ocean_floor[vent_line_point.y as usize][vent_line_point.x as usize] += 1;
-        }
-    }
-    let mut overlap_count: u32 = 0;
-    for ocean_floor_row in ocean_floor {
-        for vent_count in ocean_floor_row {
-            if vent_count > 1 {
-                overlap_count += 1;
-            }
+            ocean_floor[(vent_line_point.x as usize, vent_line_point.y as usize)] += 1;
         }
     }
+    ocean_floor
+        .iter()
+        .filter(|vent_count| **vent_count > 1)
+        .count() as u32
+}
 
-    overlap_count
+/// Draws the number of vent lines over each point, with `.` for none.
+fn render_ocean_floor(ocean_floor: &Grid<u32>) -> Grid<String> {
+    ocean_floor.map(|vent_count| match vent_count {
+        0 => ".".to_string(),
+        vent_count => vent_count.to_string(),
+    })
 }
 
-// fn render_ocean_floor(ocean_floor: &Vec<Vec<u32>>) {
-//     for ocean_floor_row in ocean_floor {
-//         let rendered_row = ocean_floor_row
-//             .iter()
-//             .map(|vent_count| vent_count.to_string())
-//             .map(|vent_count_string| {
-//                 if vent_count_string == "0" {
-//                     return ".".to_string();
-//                 }
-//                 vent_count_string
-//             })
-//             .collect::<Vec<String>>()
-//             .join("");
-//         println!("{}", rendered_row);
-//     }
-// }
-
-pub(crate) fn solve_part_2(puzzle_input: String) -> u32 {
-    let vent_lines = get_vent_lines(puzzle_input);
+pub fn solve_part_2(vent_lines: &[VentLine]) -> u32 {
     let vent_lines = vent_lines.iter().collect::<Vec<&VentLine>>();
 
     let mut ocean_floor = make_ocean_floor(&vent_lines);
 
     for vent_line in &vent_lines {
         for vent_line_point in vent_line.get_points_on_line() {
-            ocean_floor[vent_line_point.y as usize][vent_line_point.x as usize] += 1;
+            ocean_floor[(vent_line_point.x as usize, vent_line_point.y as usize)] += 1;
         }
-        // println!("Applied vent line {:?}", vent_line);
-        // render_ocean_floor(&ocean_floor);
+        crate::log!(Trace, 5, "applied vent line {:?}", vent_line);
     }
-    let mut overlap_count: u32 = 0;
-    for ocean_floor_row in &ocean_floor {
-        for vent_count in ocean_floor_row {
-            if vent_count > &1 {
-                overlap_count += 1;
-            }
-        }
+    crate::log!(
+        Trace,
+        5,
+        "ocean floor:\n{}",
+        render_ocean_floor(&ocean_floor)
+    );
+    ocean_floor
+        .iter()
+        .filter(|vent_count| **vent_count > 1)
+        .count() as u32
+}
+
+/// Generates `size` horizontal, vertical and diagonal vent lines
+/// between 0 and 989 on each axis.
+pub fn generate_input(rng: &mut Rng, size: usize) -> String {
+    let toward_middle = |ordinate: i64| match ordinate < 495 {
+        true => 1,
+        false => -1,
+    };
+    let mut vent_lines = String::new();
+    for _ in 0..size {
+        let (x1, y1) = (rng.range(0..=989), rng.range(0..=989));
+        let (x_step, y_step) = match rng.below(3) {
+            0 => (toward_middle(x1), 0),
+            1 => (0, toward_middle(y1)),
+            _ => (toward_middle(x1), toward_middle(y1)),
+        };
+        let length = rng.range(1..=494);
+        let (x2, y2) = (x1 + x_step * length, y1 + y_step * length);
+        let line = match rng.chance(0.5) {
+            true => format!("{},{} -> {},{}\n", x1, y1, x2, y2),
+            false => format!("{},{} -> {},{}\n", x2, y2, x1, y1),
+        };
+        vent_lines.push_str(&line);
+    }
+    vent_lines
+}
+
+pub struct Day05;
+
+impl Solver for Day05 {
+    const DAY: u8 = 5;
+    type Input = Vec<VentLine>;
+
+    fn parse(&self, puzzle_input: String) -> Result<Vec<VentLine>, ParseError> {
+        get_vent_lines(puzzle_input)
+    }
+
+    fn solve_part_1(&self, vent_lines: &Vec<VentLine>) -> Result<Answer, Error> {
+        Ok(solve_part_1(vent_lines).into())
+    }
+
+    fn solve_part_2(&self, vent_lines: &Vec<VentLine>) -> Result<Answer, Error> {
+        Ok(solve_part_2(vent_lines).into())
     }
-    // render_ocean_floor(&ocean_floor);
-    overlap_count
 }
 
 #[cfg(test)]
@@ -197,10 +248,32 @@ use super::test_helpers;
 
 #[test]
 fn test_part_1() {
-    assert_eq!(solve_part_1(test_helpers::load_puzzle_input(5)), 5442);
+    let vent_lines = get_vent_lines(test_helpers::load_puzzle_input(5)).unwrap();
+    assert_eq!(solve_part_1(&vent_lines), 5442);
 }
 
 #[test]
 fn test_part_2() {
-    assert_eq!(solve_part_2(test_helpers::load_puzzle_input(5)), 19571);
+    let vent_lines = get_vent_lines(test_helpers::load_puzzle_input(5)).unwrap();
+    assert_eq!(solve_part_2(&vent_lines), 19571);
+}
+
+#[test]
+fn test_get_vent_lines_reports_malformed_line() {
+    let error = get_vent_lines("0,9 -> 5,9\n8,0 -> 0,x8\n".to_string()).unwrap_err();
+    assert_eq!((error.line, error.column), (2, 10));
+    assert_eq!(error.text, "x8");
+
+    let error = get_vent_lines("0,9 -> 5,9\n8,0\n".to_string()).unwrap_err();
+    assert_eq!((error.line, error.column), (2, 4));
+    assert_eq!(error.expected, "\" -> \" followed by a point");
+
+    let error = get_vent_lines("0,9 -> 5,9\n0,0 -> 2,1\n".to_string()).unwrap_err();
+    assert_eq!((error.line, error.column), (2, 1));
+    assert_eq!(error.text, "0,0 -> 2,1");
+    assert_eq!(
+        error.expected,
+        "a horizontal, vertical or 45 degree diagonal line"
+    );
+    assert!(get_vent_lines("5,5 -> 8,2\n".to_string()).is_ok());
 }
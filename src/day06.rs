@@ -1,3 +1,11 @@
+use std::fmt;
+
+use crate::answer::Answer;
+use crate::error::{Error, ParseContext, ParseError};
+use crate::generate::Rng;
+use crate::simulation::Simulation;
+use crate::solver::Solver;
+
 fn simulate_lanternfish_day(lanternfish: &mut [u64; 9]) {
     let parent_lanternfish = lanternfish[0];
     for i in 0..8 {
@@ -7,48 +15,137 @@ fn simulate_lanternfish_day(lanternfish: &mut [u64; 9]) {
     lanternfish[8] = parent_lanternfish;
 }
 
-fn get_lanternfish(puzzle_input: String) -> [u64; 9] {
+/// How many lanternfish there are with each internal timer value, for
+/// watching the population grow day by day.
+pub struct LanternfishPopulation {
+    lanternfish: [u64; 9],
+}
+
+impl From<[u64; 9]> for LanternfishPopulation {
+    fn from(lanternfish: [u64; 9]) -> Self {
+        LanternfishPopulation { lanternfish }
+    }
+}
+
+impl fmt::Display for LanternfishPopulation {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        const BAR_WIDTH: u64 = 60;
+        let largest_count = self.lanternfish.iter().max().copied().unwrap_or(0).max(1);
+        for (timer, count) in self.lanternfish.iter().enumerate() {
+            let bar_length =
+                (u128::from(*count) * u128::from(BAR_WIDTH) / u128::from(largest_count)) as usize;
+            writeln!(f, "{} {:<60} {}", timer, "#".repeat(bar_length), count)?;
+        }
+        write!(f, "total {}", self.lanternfish.iter().sum::<u64>())
+    }
+}
+
+impl Simulation for LanternfishPopulation {
+    /// Finishes once the population no longer fits in a `u64`.
+    fn step(&mut self) -> bool {
+        let next_total = self
+            .lanternfish
+            .iter()
+            .try_fold(self.lanternfish[0], |total, count| {
+                total.checked_add(*count)
+            });
+        if next_total.is_none() {
+            return false;
+        }
+        simulate_lanternfish_day(&mut self.lanternfish);
+        true
+    }
+}
+
+pub fn get_lanternfish(puzzle_input: String) -> Result<[u64; 9], ParseError> {
+    let context = ParseContext::new(6, &puzzle_input);
     let mut lanternfish: [u64; 9] = [0; 9];
-    for lanternfish_due_day in puzzle_input
-        .trim()
-        .split(",")
-        .map(|lanterfish_due_string| lanterfish_due_string.parse::<usize>().unwrap())
-    {
-        lanternfish[lanternfish_due_day] += 1;
+    for lanterfish_due_string in puzzle_input.trim().split(",") {
+        let lanternfish_due_day =
+            context.parse::<usize>(lanterfish_due_string, "an internal timer from 0 to 8")?;
+        match lanternfish.get_mut(lanternfish_due_day) {
+            Some(count) => *count += 1,
+            None => {
+                return Err(context.error(lanterfish_due_string, "an internal timer from 0 to 8"))
+            }
+        }
     }
-    lanternfish
+    Ok(lanternfish)
 }
 
-pub(crate) fn solve_part_1(puzzle_input: String) -> u64 {
-    let mut lanternfish = get_lanternfish(puzzle_input);
-    for _day in 0..80 {
-        //println!("After {} day(s), lanternfish: {:?}", day, lanternfish);
+pub fn solve_part_1(lanternfish: &[u64; 9]) -> u64 {
+    let mut lanternfish = *lanternfish;
+    for day in 0..80 {
+        crate::log!(
+            Trace,
+            6,
+            "after {} day(s), lanternfish: {:?}",
+            day,
+            lanternfish
+        );
         simulate_lanternfish_day(&mut lanternfish);
     }
     lanternfish.iter().sum::<u64>()
 }
 
-pub(crate) fn solve_part_2(puzzle_input: String) -> u64 {
-    let mut lanternfish = get_lanternfish(puzzle_input);
-    for _day in 0..256 {
-        //println!("After {} day(s), lanternfish: {:?}", day, lanternfish);
+pub fn solve_part_2(lanternfish: &[u64; 9]) -> u64 {
+    let mut lanternfish = *lanternfish;
+    for day in 0..256 {
+        crate::log!(
+            Trace,
+            6,
+            "after {} day(s), lanternfish: {:?}",
+            day,
+            lanternfish
+        );
         simulate_lanternfish_day(&mut lanternfish);
     }
     lanternfish.iter().sum::<u64>()
 }
 
+/// Generates `size` lanternfish (at least one) with timers from 1 to 5.
+pub fn generate_input(rng: &mut Rng, size: usize) -> String {
+    let size = size.max(1);
+    let timers = (0..size)
+        .map(|_| rng.range(1..=5).to_string())
+        .collect::<Vec<String>>();
+    format!("{}\n", timers.join(","))
+}
+
+pub struct Day06;
+
+impl Solver for Day06 {
+    const DAY: u8 = 6;
+    type Input = [u64; 9];
+
+    fn parse(&self, puzzle_input: String) -> Result<[u64; 9], ParseError> {
+        get_lanternfish(puzzle_input)
+    }
+
+    fn solve_part_1(&self, lanternfish: &[u64; 9]) -> Result<Answer, Error> {
+        Ok(solve_part_1(lanternfish).into())
+    }
+
+    fn solve_part_2(&self, lanternfish: &[u64; 9]) -> Result<Answer, Error> {
+        Ok(solve_part_2(lanternfish).into())
+    }
+}
+
 #[cfg(test)]
 use super::test_helpers;
 
 #[test]
 fn test_part_1() {
-    assert_eq!(solve_part_1(test_helpers::load_puzzle_input(6)), 362666);
+    assert_eq!(
+        solve_part_1(&get_lanternfish(test_helpers::load_puzzle_input(6)).unwrap()),
+        362666
+    );
 }
 
 #[test]
 fn test_part_2() {
     assert_eq!(
-        solve_part_2(test_helpers::load_puzzle_input(6)),
+        solve_part_2(&get_lanternfish(test_helpers::load_puzzle_input(6)).unwrap()),
         1640526601595
     );
 }
@@ -1,11 +1,17 @@
-fn get_sorted_crab_positions(puzzle_input: String) -> Vec<i64> {
+use crate::answer::Answer;
+use crate::error::{Error, ParseContext, ParseError};
+use crate::generate::Rng;
+use crate::solver::Solver;
+
+pub fn get_sorted_crab_positions(puzzle_input: String) -> Result<Vec<i64>, ParseError> {
+    let context = ParseContext::new(7, &puzzle_input);
     let mut crab_initial_positions = puzzle_input
         .trim()
         .split(",")
-        .map(|n| n.parse::<i64>().unwrap())
-        .collect::<Vec<i64>>();
+        .map(|n| context.parse::<i64>(n, "a horizontal position"))
+        .collect::<Result<Vec<i64>, ParseError>>()?;
     crab_initial_positions.sort();
-    crab_initial_positions
+    Ok(crab_initial_positions)
 }
 
 fn calculate_crab_fuel_cost(initial_position: i64, target_position: i64) -> i64 {
@@ -13,8 +19,7 @@ fn calculate_crab_fuel_cost(initial_position: i64, target_position: i64) -> i64
     distance * (distance + 1) / 2
 }
 
-pub(crate) fn solve_part_1(puzzle_input: String) -> u64 {
-    let crab_initial_positions = get_sorted_crab_positions(puzzle_input);
+pub fn solve_part_1(crab_initial_positions: &[i64]) -> u64 {
     let target_position = crab_initial_positions[crab_initial_positions.len() / 2];
     crab_initial_positions
         .iter()
@@ -22,8 +27,7 @@ pub(crate) fn solve_part_1(puzzle_input: String) -> u64 {
         .sum::<i64>() as u64
 }
 
-pub(crate) fn solve_part_2(puzzle_input: String) -> u64 {
-    let crab_initial_positions = get_sorted_crab_positions(puzzle_input);
+pub fn solve_part_2(crab_initial_positions: &[i64]) -> u64 {
     let minimum_target_position = *crab_initial_positions.iter().min().unwrap();
     let maximum_target_position = *crab_initial_positions.iter().max().unwrap();
     // (target_position, total_fuel_cost)
@@ -37,10 +41,58 @@ pub(crate) fn solve_part_2(puzzle_input: String) -> u64 {
             .sum();
         target_options.push((target_position, total_fuel_cost));
     }
-    target_options.sort_by(|a, b| a.1.cmp(&b.1));
+    target_options.sort_by_key(|target_option| target_option.1);
     target_options[0].1 as u64
 }
 
+/// Solves part 1 by trying every position, without assuming that the
+/// median is best. For cross-checking [`solve_part_1`].
+pub fn reference_part_1(crab_initial_positions: &[i64]) -> u64 {
+    let minimum_target_position = *crab_initial_positions.iter().min().unwrap();
+    let maximum_target_position = *crab_initial_positions.iter().max().unwrap();
+    (minimum_target_position..=maximum_target_position)
+        .map(|target_position| {
+            crab_initial_positions
+                .iter()
+                .map(|position| (position - target_position).unsigned_abs())
+                .sum::<u64>()
+        })
+        .min()
+        .unwrap()
+}
+
+/// Generates `size` crab positions (at least one), mostly small with a few large
+/// outliers.
+pub fn generate_input(rng: &mut Rng, size: usize) -> String {
+    let size = size.max(1);
+    let positions = (0..size)
+        .map(|_| {
+            let spread = rng.range(0..=1900);
+            rng.range(0..=spread).to_string()
+        })
+        .collect::<Vec<String>>();
+    format!("{}\n", positions.join(","))
+}
+
+pub struct Day07;
+
+impl Solver for Day07 {
+    const DAY: u8 = 7;
+    type Input = Vec<i64>;
+
+    fn parse(&self, puzzle_input: String) -> Result<Vec<i64>, ParseError> {
+        get_sorted_crab_positions(puzzle_input)
+    }
+
+    fn solve_part_1(&self, crab_initial_positions: &Vec<i64>) -> Result<Answer, Error> {
+        Ok(solve_part_1(crab_initial_positions).into())
+    }
+
+    fn solve_part_2(&self, crab_initial_positions: &Vec<i64>) -> Result<Answer, Error> {
+        Ok(solve_part_2(crab_initial_positions).into())
+    }
+}
+
 #[cfg(test)]
 use super::test_helpers;
 
@@ -54,10 +106,14 @@ fn test_calculate_crab_fuel_cost() {
 
 #[test]
 fn test_part_1() {
-    assert_eq!(solve_part_1(test_helpers::load_puzzle_input(7)), 355764);
+    let crab_initial_positions =
+        get_sorted_crab_positions(test_helpers::load_puzzle_input(7)).unwrap();
+    assert_eq!(solve_part_1(&crab_initial_positions), 355764);
 }
 
 #[test]
 fn test_part_2() {
-    assert_eq!(solve_part_2(test_helpers::load_puzzle_input(7)), 99634572);
+    let crab_initial_positions =
+        get_sorted_crab_positions(test_helpers::load_puzzle_input(7)).unwrap();
+    assert_eq!(solve_part_2(&crab_initial_positions), 99634572);
 }
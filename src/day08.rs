@@ -1,7 +1,35 @@
 use std::collections::HashMap;
 use std::collections::HashSet;
 
-fn parse_puzzle_input(puzzle_input: &String) -> Vec<(Vec<HashSet<char>>, Vec<HashSet<char>>)> {
+use crate::answer::Answer;
+use crate::error::{Error, ParseContext, ParseError};
+use crate::generate::Rng;
+use crate::solver::Solver;
+
+/// All ten wire patterns seen on a display, followed by the four
+/// output patterns.
+pub type DisplayNote = (Vec<HashSet<char>>, Vec<HashSet<char>>);
+
+pub fn parse_puzzle_input(puzzle_input: &str) -> Result<Vec<DisplayNote>, ParseError> {
+    let context = ParseContext::new(8, puzzle_input);
+    let parse_patterns = |pattern_string: &str, count: usize, expected: &str| {
+        let patterns = pattern_string
+            .split_whitespace()
+            .map(|wire_pattern_string| {
+                match wire_pattern_string.find(|c| !('a'..='g').contains(&c)) {
+                    Some(position) => Err(context.error(
+                        &wire_pattern_string[position..=position],
+                        "a segment from a to g",
+                    )),
+                    None => Ok(string_to_set(wire_pattern_string)),
+                }
+            })
+            .collect::<Result<Vec<HashSet<char>>, ParseError>>()?;
+        if patterns.len() != count {
+            return Err(context.error(pattern_string, expected));
+        }
+        Ok(patterns)
+    };
     //Each element of the outer vector is a line from the input.
     //The first vector of HashSets inside the tuple contains all
     // possible output patterns.
@@ -12,17 +40,15 @@ fn parse_puzzle_input(puzzle_input: &String) -> Vec<(Vec<HashSet<char>>, Vec<Has
         .split("\n")
         .map(|line| {
             let mut line_parts = line.split(" | ");
-            let ten_pattern_string = line_parts.next().unwrap();
-            let four_pattern_string = line_parts.next().unwrap();
-            let all_patterns = ten_pattern_string
-                .split_whitespace()
-                .map(|wire_pattern_string| string_to_set(wire_pattern_string))
-                .collect();
-            let output_patterns = four_pattern_string
-                .split_whitespace()
-                .map(|wire_pattern_string| string_to_set(wire_pattern_string))
-                .collect();
-            (all_patterns, output_patterns)
+            let ten_pattern_string = context.next(&mut line_parts, line, "10 wire patterns")?;
+            let four_pattern_string = context.next(
+                &mut line_parts,
+                ten_pattern_string,
+                "\" | \" followed by 4 output patterns",
+            )?;
+            let all_patterns = parse_patterns(ten_pattern_string, 10, "10 wire patterns")?;
+            let output_patterns = parse_patterns(four_pattern_string, 4, "4 output patterns")?;
+            Ok((all_patterns, output_patterns))
         })
         .collect()
 }
@@ -35,11 +61,10 @@ fn string_to_set(character_string: &str) -> HashSet<char> {
     character_set
 }
 
-pub(crate) fn solve_part_1(puzzle_input: String) -> u64 {
-    let input_lines = parse_puzzle_input(&puzzle_input);
+pub fn solve_part_1(input_lines: &[DisplayNote]) -> u64 {
     let mut digit_count: u64 = 0;
     for input_line in input_lines {
-        for output_pattern in input_line.1 {
+        for output_pattern in &input_line.1 {
             match output_pattern.len() {
                 2 => digit_count += 1,
                 4 => digit_count += 1,
@@ -52,13 +77,14 @@ pub(crate) fn solve_part_1(puzzle_input: String) -> u64 {
     digit_count
 }
 
-pub(crate) fn solve_part_2(puzzle_input: String) -> u64 {
-    let input_lines = parse_puzzle_input(&puzzle_input);
+/// Returns `None` if the wire patterns on some display cannot be
+/// matched up with digits.
+pub fn solve_part_2(input_lines: &[DisplayNote]) -> Option<u64> {
     let mut output_sum: u64 = 0;
     for input_line in input_lines {
-        let all_patterns = input_line.0;
+        let all_patterns = &input_line.0;
         let mut encode_map = HashMap::<u64, &HashSet<char>>::new();
-        for pattern in &all_patterns {
+        for pattern in all_patterns {
             match pattern.len() {
                 2 => encode_map.insert(1, pattern),
                 4 => encode_map.insert(4, pattern),
@@ -67,10 +93,10 @@ pub(crate) fn solve_part_2(puzzle_input: String) -> u64 {
                 _ => None,
             };
         }
-        for pattern in &all_patterns {
+        for pattern in all_patterns {
             if pattern.len() == 6
                 && pattern
-                    .intersection(encode_map.get(&1).unwrap())
+                    .intersection(encode_map.get(&1)?)
                     .collect::<Vec<&char>>()
                     .len()
                     == 1
@@ -79,31 +105,23 @@ pub(crate) fn solve_part_2(puzzle_input: String) -> u64 {
                 break;
             }
         }
-        let right_top_segment_letter: char = *encode_map
-            .get(&8)
-            .unwrap()
-            .difference(encode_map.get(&6).unwrap())
-            .next()
-            .unwrap();
-        for pattern in &all_patterns {
+        let right_top_segment_letter: char =
+            *encode_map.get(&8)?.difference(encode_map.get(&6)?).next()?;
+        for pattern in all_patterns {
             if pattern.len() == 5 && !pattern.contains(&right_top_segment_letter) {
                 encode_map.insert(5, pattern);
                 break;
             }
         }
         let mut left_bottom_segment_letter: char = '?';
-        for missing_letter in encode_map
-            .get(&8)
-            .unwrap()
-            .difference(encode_map.get(&5).unwrap())
-        {
+        for missing_letter in encode_map.get(&8)?.difference(encode_map.get(&5)?) {
             if *missing_letter != right_top_segment_letter {
                 left_bottom_segment_letter = *missing_letter;
                 break;
             }
         }
-        for pattern in &all_patterns {
-            if pattern.len() != 5 || *encode_map.get(&5).unwrap() == pattern {
+        for pattern in all_patterns {
+            if pattern.len() != 5 || *encode_map.get(&5)? == pattern {
                 continue;
             }
             if pattern.contains(&left_bottom_segment_letter) {
@@ -112,8 +130,8 @@ pub(crate) fn solve_part_2(puzzle_input: String) -> u64 {
                 encode_map.insert(3, pattern);
             }
         }
-        for pattern in &all_patterns {
-            if pattern.len() != 6 || *encode_map.get(&6).unwrap() == pattern {
+        for pattern in all_patterns {
+            if pattern.len() != 6 || *encode_map.get(&6)? == pattern {
                 continue;
             }
             if pattern.contains(&left_bottom_segment_letter) {
@@ -128,9 +146,9 @@ pub(crate) fn solve_part_2(puzzle_input: String) -> u64 {
         //     decode_map.insert(pattern, digit);
         // }
 
-        let output_patterns = input_line.1;
+        let output_patterns = &input_line.1;
         let mut output_value: u64 = 0;
-        for output_pattern in &output_patterns {
+        for output_pattern in output_patterns {
             output_value *= 10;
             for (digit, pattern) in encode_map.iter() {
                 if output_pattern == *pattern {
@@ -141,7 +159,65 @@ pub(crate) fn solve_part_2(puzzle_input: String) -> u64 {
         }
         output_sum += output_value;
     }
-    output_sum
+    Some(output_sum)
+}
+
+/// The segments lit for each digit on a correctly wired display.
+const DIGIT_SEGMENTS: [&str; 10] = [
+    "abcefg", "cf", "acdeg", "acdfg", "bcdf", "abdfg", "abdefg", "acf", "abcdefg", "abcdfg",
+];
+
+/// Generates notes for `size` displays (at least one), each wired up at random.
+pub fn generate_input(rng: &mut Rng, size: usize) -> String {
+    let size = size.max(1);
+    let mut notes = String::new();
+    for _ in 0..size {
+        let mut wiring = ('a'..='g').collect::<Vec<char>>();
+        rng.shuffle(&mut wiring);
+        let scramble = |rng: &mut Rng, digit: usize| {
+            let mut wires = DIGIT_SEGMENTS[digit]
+                .chars()
+                .map(|segment| wiring[(segment as u8 - b'a') as usize])
+                .collect::<Vec<char>>();
+            rng.shuffle(&mut wires);
+            wires.into_iter().collect::<String>()
+        };
+        let mut digits = (0..10).collect::<Vec<usize>>();
+        rng.shuffle(&mut digits);
+        let patterns = digits
+            .iter()
+            .map(|digit| scramble(rng, *digit))
+            .collect::<Vec<String>>();
+        let output = (0..4)
+            .map(|_| {
+                let digit = rng.index(10);
+                scramble(rng, digit)
+            })
+            .collect::<Vec<String>>();
+        notes.push_str(&format!("{} | {}\n", patterns.join(" "), output.join(" ")));
+    }
+    notes
+}
+
+pub struct Day08;
+
+impl Solver for Day08 {
+    const DAY: u8 = 8;
+    type Input = Vec<DisplayNote>;
+
+    fn parse(&self, puzzle_input: String) -> Result<Vec<DisplayNote>, ParseError> {
+        parse_puzzle_input(&puzzle_input)
+    }
+
+    fn solve_part_1(&self, input_lines: &Vec<DisplayNote>) -> Result<Answer, Error> {
+        Ok(solve_part_1(input_lines).into())
+    }
+
+    fn solve_part_2(&self, input_lines: &Vec<DisplayNote>) -> Result<Answer, Error> {
+        solve_part_2(input_lines)
+            .map(Answer::from)
+            .ok_or_else(|| Error::unsolvable(8, 2, "some display's wire patterns are inconsistent"))
+    }
 }
 
 #[cfg(test)]
@@ -149,10 +225,12 @@ use super::test_helpers;
 
 #[test]
 fn test_part_1() {
-    assert_eq!(solve_part_1(test_helpers::load_puzzle_input(8)), 421);
+    let input_lines = parse_puzzle_input(&test_helpers::load_puzzle_input(8)).unwrap();
+    assert_eq!(solve_part_1(&input_lines), 421);
 }
 
 #[test]
 fn test_part_2() {
-    assert_eq!(solve_part_2(test_helpers::load_puzzle_input(8)), 986163);
+    let input_lines = parse_puzzle_input(&test_helpers::load_puzzle_input(8)).unwrap();
+    assert_eq!(solve_part_2(&input_lines), Some(986163));
 }
@@ -1,51 +1,29 @@
 use std::collections::HashSet;
 
-struct HeightMap {
-    // map_data[y][x]
-    map_data: Vec<Vec<u8>>,
+use crate::answer::Answer;
+use crate::error::{Error, ParseContext, ParseError};
+use crate::generate::Rng;
+use crate::grid::Grid;
+use crate::solver::Solver;
+
+pub struct HeightMap {
+    heights: Grid<u8>,
 }
 
 impl HeightMap {
-    fn get_max_x(&self) -> usize {
-        self.map_data[0].len() - 1
-    }
-    fn get_max_y(&self) -> usize {
-        self.map_data.len() - 1
-    }
-    fn get_neighbor_heights(&self, x: usize, y: usize) -> Vec<u8> {
-        let mut neighbor_heights: Vec<u8> = Vec::new();
-        for (neighbor_x, neighbor_y) in self.get_neighbor_coordinates(x, y) {
-            neighbor_heights.push(self.map_data[neighbor_y][neighbor_x]);
-        }
-        neighbor_heights
-    }
-    fn get_neighbor_coordinates(&self, x: usize, y: usize) -> Vec<(usize, usize)> {
-        let mut neighbor_coordinates: Vec<(usize, usize)> = Vec::new();
-        if x != 0 {
-            neighbor_coordinates.push((x - 1, y));
-        }
-        if x != self.get_max_x() {
-            neighbor_coordinates.push((x + 1, y));
-        }
-        if y != 0 {
-            neighbor_coordinates.push((x, y - 1));
-        }
-        if y != self.get_max_y() {
-            neighbor_coordinates.push((x, y + 1));
-        }
-        neighbor_coordinates
-    }
     fn is_low_point(&self, x: usize, y: usize) -> bool {
-        let my_height = self.map_data[y][x];
-        for neighbor_height in self.get_neighbor_heights(x, y).iter() {
-            if my_height >= *neighbor_height {
-                return false;
-            }
-        }
-        true
+        let my_height = self.heights[(x, y)];
+        self.heights
+            .neighbors_4(x, y)
+            .all(|neighbor| my_height < self.heights[neighbor])
     }
-    fn get_height(&self, x: usize, y: usize) -> u8 {
-        self.map_data[y][x]
+    /// The size of the basin containing `(x, y)`, or `None` if the
+    /// location is off the map or has height 9 and so is in no basin.
+    pub fn basin_size_at(&self, x: usize, y: usize) -> Option<usize> {
+        if *self.heights.get(x, y)? == 9 {
+            return None;
+        }
+        Some(self.find_basin_size(x, y))
     }
     fn find_basin_size(&self, x: usize, y: usize) -> usize {
         // x, y should be the coordinates of a low point
@@ -54,7 +32,7 @@ impl HeightMap {
         let mut new_neighbors: HashSet<(usize, usize)> = HashSet::new();
         new_neighbors.insert((x, y));
         loop {
-            if new_neighbors.len() == 0 {
+            if new_neighbors.is_empty() {
                 break;
             }
             for new_neighbor in new_neighbors.iter() {
@@ -62,17 +40,14 @@ impl HeightMap {
             }
             new_neighbors.clear();
             for basin_member in basin_members.iter() {
-                for neighbor in self
-                    .get_neighbor_coordinates(basin_member.0, basin_member.1)
-                    .iter()
-                {
-                    if self.get_height(neighbor.0, neighbor.1) == 9 {
+                for neighbor in self.heights.neighbors_4(basin_member.0, basin_member.1) {
+                    if self.heights[neighbor] == 9 {
                         continue;
                     }
-                    if basin_members.contains(neighbor) == true {
+                    if basin_members.contains(&neighbor) {
                         continue;
                     }
-                    new_neighbors.insert(*neighbor);
+                    new_neighbors.insert(neighbor);
                 }
             }
         }
@@ -80,46 +55,117 @@ impl HeightMap {
     }
 }
 
-fn parse_input(puzzle_input: String) -> HeightMap {
-    HeightMap {
-        map_data: puzzle_input
-            .trim()
-            .split("\n")
-            .map(|line| {
-                line.chars()
-                    .map(|n| n.to_digit(10).unwrap() as u8)
-                    .collect::<Vec<u8>>()
-            })
-            .collect::<Vec<Vec<u8>>>(),
-    }
+pub fn parse_input(puzzle_input: String) -> Result<HeightMap, ParseError> {
+    Ok(HeightMap {
+        heights: Grid::parse_digits(&ParseContext::new(9, &puzzle_input), &puzzle_input)?,
+    })
 }
 
-pub(crate) fn solve_part_1(puzzle_input: String) -> u64 {
-    let height_map = parse_input(puzzle_input);
+pub fn solve_part_1(height_map: &HeightMap) -> u64 {
     let mut risk_level_sum: u64 = 0;
-    for x in 0..=height_map.get_max_x() {
-        for y in 0..=height_map.get_max_y() {
-            if height_map.is_low_point(x, y) {
-                risk_level_sum += (height_map.map_data[y][x] + 1) as u64
-            }
+    for (x, y) in height_map.heights.positions() {
+        if height_map.is_low_point(x, y) {
+            risk_level_sum += (height_map.heights[(x, y)] + 1) as u64
         }
     }
     risk_level_sum
 }
 
-pub(crate) fn solve_part_2(puzzle_input: String) -> u64 {
-    let height_map = parse_input(puzzle_input);
+/// Returns `None` if there are fewer than three basins.
+pub fn solve_part_2(height_map: &HeightMap) -> Option<u64> {
     let mut basin_sizes: Vec<usize> = Vec::new();
-    for x in 0..=height_map.get_max_x() {
-        for y in 0..=height_map.get_max_y() {
-            if !height_map.is_low_point(x, y) {
-                continue;
-            }
-            basin_sizes.push(height_map.find_basin_size(x, y));
+    for (x, y) in height_map.heights.positions() {
+        if !height_map.is_low_point(x, y) {
+            continue;
         }
+        basin_sizes.push(height_map.find_basin_size(x, y));
     }
     basin_sizes.sort_by(|a, b| b.partial_cmp(a).unwrap());
-    basin_sizes[0] as u64 * basin_sizes[1] as u64 * basin_sizes[2] as u64
+    match basin_sizes[..] {
+        [first, second, third, ..] => Some(first as u64 * second as u64 * third as u64),
+        _ => None,
+    }
+}
+
+/// Generates a `size` by `size` height map (at least 5 by 5) made of
+/// small basins, each sloping up from a single low point to walls of
+/// 9s, like a real puzzle input.
+///
+/// Low points next to each other can merge their basins, so this tries
+/// up to 100 maps before settling for one with a low point in three of
+/// the corners, so that part 2 has an answer.
+pub fn generate_input(rng: &mut Rng, size: usize) -> String {
+    let size = size.max(5);
+    for _ in 0..100 {
+        let low_points = (0..(size * size / 60).max(3))
+            .map(|_| (rng.index(size), rng.index(size)))
+            .collect::<Vec<(usize, usize)>>();
+        let height_map = generate_height_map(rng, size, &low_points);
+        if solve_part_2(&parse_input(height_map.clone()).unwrap()).is_some() {
+            return height_map;
+        }
+    }
+    generate_height_map(rng, size, &[(0, 0), (size - 1, 0), (0, size - 1)])
+}
+
+/// Generates a `size` by `size` height map with a basin around each
+/// of `low_points`.
+fn generate_height_map(rng: &mut Rng, size: usize, low_points: &[(usize, usize)]) -> String {
+    // Each location belongs to the basin of the nearest low point.
+    let nearest_low_point = |x: usize, y: usize| {
+        low_points
+            .iter()
+            .enumerate()
+            .map(|(basin, (low_x, low_y))| (x.abs_diff(*low_x) + y.abs_diff(*low_y), basin))
+            .min()
+            .unwrap()
+    };
+    let basins = (0..size)
+        .map(|y| (0..size).map(|x| nearest_low_point(x, y)).collect())
+        .collect::<Vec<Vec<(usize, usize)>>>();
+    let mut height_map = String::new();
+    for y in 0..size {
+        for x in 0..size {
+            let (distance, basin) = basins[y][x];
+            let on_border = [(x + 1, y), (x, y + 1)]
+                .iter()
+                .any(|(neighbor_x, neighbor_y)| {
+                    basins
+                        .get(*neighbor_y)
+                        .and_then(|row| row.get(*neighbor_x))
+                        .is_some_and(|(_, neighbor_basin)| *neighbor_basin != basin)
+                });
+            let height = match (distance, on_border) {
+                (_, true) => 9,
+                (0, false) => 0,
+                (_, false) => (distance as u64 + rng.below(2)).min(8),
+            };
+            height_map.push_str(&height.to_string());
+        }
+        height_map.push('\n');
+    }
+    height_map
+}
+
+pub struct Day09;
+
+impl Solver for Day09 {
+    const DAY: u8 = 9;
+    type Input = HeightMap;
+
+    fn parse(&self, puzzle_input: String) -> Result<HeightMap, ParseError> {
+        parse_input(puzzle_input)
+    }
+
+    fn solve_part_1(&self, height_map: &HeightMap) -> Result<Answer, Error> {
+        Ok(solve_part_1(height_map).into())
+    }
+
+    fn solve_part_2(&self, height_map: &HeightMap) -> Result<Answer, Error> {
+        solve_part_2(height_map)
+            .map(Answer::from)
+            .ok_or_else(|| Error::unsolvable(9, 2, "there are fewer than three basins"))
+    }
 }
 
 #[cfg(test)]
@@ -127,10 +173,12 @@ use super::test_helpers;
 
 #[test]
 fn test_part_1() {
-    assert_eq!(solve_part_1(test_helpers::load_puzzle_input(9)), 560);
+    let height_map = parse_input(test_helpers::load_puzzle_input(9)).unwrap();
+    assert_eq!(solve_part_1(&height_map), 560);
 }
 
 #[test]
 fn test_part_2() {
-    assert_eq!(solve_part_2(test_helpers::load_puzzle_input(9)), 959136);
+    let height_map = parse_input(test_helpers::load_puzzle_input(9)).unwrap();
+    assert_eq!(solve_part_2(&height_map), Some(959136));
 }
@@ -1,3 +1,22 @@
+use crate::answer::Answer;
+use crate::error::{Error, ParseContext, ParseError};
+use crate::generate::Rng;
+use crate::solver::Solver;
+
+pub fn parse_navigation_subsystem(puzzle_input: String) -> Result<Vec<String>, ParseError> {
+    let context = ParseContext::new(10, &puzzle_input);
+    puzzle_input
+        .trim()
+        .split("\n")
+        .map(|line| match line.find(|c| !"()[]{}<>".contains(c)) {
+            Some(position) => {
+                Err(context.error(&line[position..position + 1], "one of ( ) [ ] { } < >"))
+            }
+            None => Ok(String::from(line)),
+        })
+        .collect()
+}
+
 fn find_first_illegal_character(navigation_line: &str) -> Option<char> {
     let mut stack = Vec::<char>::new();
     for navigation_char in navigation_line.chars() {
@@ -16,7 +35,7 @@ fn find_first_illegal_character(navigation_line: &str) -> Option<char> {
             '>' => '<',
             _ => panic!("Invalid navigation_char"),
         };
-        if expected_companion != stack.pop().unwrap() {
+        if stack.pop() != Some(expected_companion) {
             return Some(navigation_char);
         }
     }
@@ -24,10 +43,7 @@ fn find_first_illegal_character(navigation_line: &str) -> Option<char> {
 }
 
 fn is_line_corrupted(navigation_line: &str) -> bool {
-    match find_first_illegal_character(navigation_line) {
-        Some(_) => true,
-        None => false,
-    }
+    find_first_illegal_character(navigation_line).is_some()
 }
 
 fn calculate_character_points(illegal_char: char) -> u64 {
@@ -47,7 +63,6 @@ fn get_closing_characters(navigation_line: &str) -> Vec<char> {
             '(' | '[' | '{' | '<' => stack.push(navigation_char),
             ')' | ']' | '}' | '>' => {
                 stack.pop();
-                ()
             }
             _ => panic!("Invalid navigation_char"),
         }
@@ -81,25 +96,81 @@ fn score_closing_characters(closing_characters: Vec<char>) -> u64 {
     score
 }
 
-pub(crate) fn solve_part_1(puzzle_input: String) -> u64 {
-    puzzle_input
-        .trim()
-        .split("\n")
-        .filter_map(find_first_illegal_character)
+pub fn solve_part_1(navigation_subsystem: &[String]) -> u64 {
+    navigation_subsystem
+        .iter()
+        .filter_map(|line| find_first_illegal_character(line))
         .map(calculate_character_points)
         .sum()
 }
 
-pub(crate) fn solve_part_2(puzzle_input: String) -> u64 {
-    let mut line_scores = puzzle_input
-        .trim()
-        .split("\n")
+/// Returns `None` if every line is corrupted.
+pub fn solve_part_2(navigation_subsystem: &[String]) -> Option<u64> {
+    let mut line_scores = navigation_subsystem
+        .iter()
         .filter(|line| !is_line_corrupted(line))
-        .map(get_closing_characters)
+        .map(|line| get_closing_characters(line))
         .map(score_closing_characters)
         .collect::<Vec<u64>>();
     line_scores.sort();
-    line_scores[line_scores.len() / 2]
+    line_scores.get(line_scores.len() / 2).copied()
+}
+
+const CHUNK_DELIMITERS: [(char, char); 4] = [('(', ')'), ('[', ']'), ('{', '}'), ('<', '>')];
+
+/// Generates `size` lines, each either incomplete or corrupted. The
+/// first line is always incomplete so that part 2 has an answer.
+pub fn generate_input(rng: &mut Rng, size: usize) -> String {
+    let mut navigation_subsystem = String::new();
+    for line_number in 0..size {
+        let length = rng.index(90) + 20;
+        let mut line = String::new();
+        let mut expected_closings = Vec::new();
+        while line.len() < length || expected_closings.is_empty() {
+            match expected_closings.pop() {
+                Some(closing) if rng.chance(0.45) => line.push(closing),
+                unclosed => {
+                    expected_closings.extend(unclosed);
+                    let (opening, closing) = *rng.choose(&CHUNK_DELIMITERS);
+                    line.push(opening);
+                    expected_closings.push(closing);
+                }
+            }
+        }
+        if line_number > 0 && rng.chance(0.5) {
+            let expected_closing = expected_closings.last().copied();
+            let corrupt_closing = loop {
+                let (_, closing) = *rng.choose(&CHUNK_DELIMITERS);
+                if Some(closing) != expected_closing {
+                    break closing;
+                }
+            };
+            line.push(corrupt_closing);
+        }
+        navigation_subsystem.push_str(&format!("{}\n", line));
+    }
+    navigation_subsystem
+}
+
+pub struct Day10;
+
+impl Solver for Day10 {
+    const DAY: u8 = 10;
+    type Input = Vec<String>;
+
+    fn parse(&self, puzzle_input: String) -> Result<Vec<String>, ParseError> {
+        parse_navigation_subsystem(puzzle_input)
+    }
+
+    fn solve_part_1(&self, navigation_subsystem: &Vec<String>) -> Result<Answer, Error> {
+        Ok(solve_part_1(navigation_subsystem).into())
+    }
+
+    fn solve_part_2(&self, navigation_subsystem: &Vec<String>) -> Result<Answer, Error> {
+        solve_part_2(navigation_subsystem)
+            .map(Answer::from)
+            .ok_or_else(|| Error::unsolvable(10, 2, "every line is corrupted"))
+    }
 }
 
 #[cfg(test)]
@@ -107,13 +178,14 @@ use super::test_helpers;
 
 #[test]
 fn test_part_1() {
-    assert_eq!(solve_part_1(test_helpers::load_puzzle_input(10)), 462693);
+    let navigation_subsystem =
+        parse_navigation_subsystem(test_helpers::load_puzzle_input(10)).unwrap();
+    assert_eq!(solve_part_1(&navigation_subsystem), 462693);
 }
 
 #[test]
 fn test_part_2() {
-    assert_eq!(
-        solve_part_2(test_helpers::load_puzzle_input(10)),
-        3094671161
-    );
+    let navigation_subsystem =
+        parse_navigation_subsystem(test_helpers::load_puzzle_input(10)).unwrap();
+    assert_eq!(solve_part_2(&navigation_subsystem), Some(3094671161));
 }
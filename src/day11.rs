@@ -1,12 +1,19 @@
-use std::collections::HashMap;
 use std::fmt;
 
-#[derive(Debug)]
-struct OctopusMap {
-    hash_map: HashMap<(i8, i8), Octopus>,
+use crate::answer::Answer;
+use crate::cancel;
+use crate::error::{Error, ParseContext, ParseError};
+use crate::generate::Rng;
+use crate::grid::Grid;
+use crate::simulation::Simulation;
+use crate::solver::Solver;
+
+#[derive(Debug, Clone)]
+pub struct OctopusMap {
+    octopodes: Grid<Octopus>,
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 struct Octopus {
     energy_level: u8,
     has_flashed_this_step: bool,
@@ -48,159 +55,88 @@ impl fmt::Display for Octopus {
 
 impl fmt::Display for OctopusMap {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        for y in self.get_min_y()..=self.get_max_y() {
-            for x in self.get_min_x()..=self.get_max_x() {
-                match self.hash_map.get(&(x, y)) {
-                    None => write!(f, " ")?,
-                    Some(octopus) => write!(f, "{}", octopus)?,
-                }
-            }
-            write!(f, "\n")?;
-        }
-        write!(f, "")
+        write!(f, "{}", self.octopodes)
     }
 }
 
 impl OctopusMap {
-    fn create_octopus(&mut self, x: i8, y: i8, energy_level: u8) -> () {
-        self.hash_map.insert(
-            (x, y),
-            Octopus {
-                energy_level,
-                has_flashed_this_step: false,
-            },
-        );
-    }
     fn get_octopus_count(&self) -> usize {
-        self.hash_map.len()
-    }
-    fn get_min_x(&self) -> i8 {
-        self.hash_map
-            .keys()
-            .map(|coordinates| coordinates.0)
-            .min()
-            .unwrap()
-    }
-    fn get_max_x(&self) -> i8 {
-        self.hash_map
-            .keys()
-            .map(|coordinates| coordinates.0)
-            .max()
-            .unwrap()
-    }
-    fn get_min_y(&self) -> i8 {
-        self.hash_map
-            .keys()
-            .map(|coordinates| coordinates.1)
-            .min()
-            .unwrap()
-    }
-    fn get_max_y(&self) -> i8 {
-        self.hash_map
-            .keys()
-            .map(|coordinates| coordinates.1)
-            .max()
-            .unwrap()
+        self.octopodes.width() * self.octopodes.height()
     }
     fn mass_charge_octopodes(&mut self) {
-        for octopus in self.hash_map.values_mut() {
+        for octopus in self.octopodes.iter_mut() {
             (*octopus).increment_energy_level();
         }
     }
+    /// Simulates `steps` steps and returns the total number of flashes.
+    pub fn simulate_steps(&mut self, steps: u64) -> u64 {
+        (0..steps).map(|_| self.simulate_step()).sum()
+    }
     fn simulate_step(&mut self) -> u64 {
-        // println!("Beginning step simulation. Starting octopus map:\n{}", self);
         self.mass_charge_octopodes();
-        // println!("After mass charge:\n{}", self);
+        crate::log!(Trace, 11, "after mass charge:\n{}", self);
         let mut flash_count: u64 = 0;
         let mut an_octopus_flashed_this_iteration = true;
         while an_octopus_flashed_this_iteration {
             an_octopus_flashed_this_iteration = false;
-            let mut flashed_coordinates: Vec<(i8, i8)> = Vec::new();
-            for (coordinates, octopus) in &mut self.hash_map {
-                let octopus_successfully_flashed = octopus.attempt_flash();
+            let mut flashed_coordinates: Vec<(usize, usize)> = Vec::new();
+            for (x, y) in self.octopodes.positions() {
+                let octopus_successfully_flashed = self.octopodes[(x, y)].attempt_flash();
                 if !octopus_successfully_flashed {
                     continue;
                 }
                 flash_count += 1;
                 an_octopus_flashed_this_iteration = true;
-                flashed_coordinates.push((coordinates.0 - 1, coordinates.1 - 1));
-                flashed_coordinates.push((coordinates.0 - 1, coordinates.1));
-                flashed_coordinates.push((coordinates.0 - 1, coordinates.1 + 1));
-                flashed_coordinates.push((coordinates.0, coordinates.1 - 1));
-                flashed_coordinates.push((coordinates.0, coordinates.1));
-                flashed_coordinates.push((coordinates.0, coordinates.1 + 1));
-                flashed_coordinates.push((coordinates.0 + 1, coordinates.1 - 1));
-                flashed_coordinates.push((coordinates.0 + 1, coordinates.1));
-                flashed_coordinates.push((coordinates.0 + 1, coordinates.1 + 1));
+                flashed_coordinates.extend(self.octopodes.neighbors_8(x, y));
             }
-            // println!(
-            //     "The following locations received flashes: {:?}",
-            //     flashed_coordinates
-            // );
-            for flashed_location in flashed_coordinates.iter() {
-                match self.hash_map.get_mut(flashed_location) {
-                    None => {
-                        // println!(
-                        //     "No octopus found at {:?} to receive flash.",
-                        //     flashed_location
-                        // );
-                        continue;
-                    }
-                    Some(octopus_neighbor) => {
-                        // println!("Octopus found at {:?} to receive flash.", flashed_location);
-                        // println!(
-                        //     "Flashed octopus before incrementing energy level: {}",
-                        //     octopus_neighbor
-                        // );
-                        octopus_neighbor.increment_energy_level();
-                        // println!(
-                        //     "Flashed octopus after incrementing energy level: {}",
-                        //     octopus_neighbor
-                        // );
-                    }
-                }
+            for flashed_location in flashed_coordinates {
+                self.octopodes[flashed_location].increment_energy_level();
             }
-            // println!("After flash charges applied:\n{}", self);
+            crate::log!(Trace, 11, "after flash charges applied:\n{}", self);
         }
-        for octopus in self.hash_map.values_mut() {
+        for octopus in self.octopodes.iter_mut() {
             octopus.end_of_step_cleanup();
         }
         flash_count
     }
 }
 
-fn load_octopodes(puzzle_input: String) -> OctopusMap {
-    let mut my_octopus_map = OctopusMap {
-        hash_map: HashMap::<(i8, i8), Octopus>::new(),
-    };
-    for (y, octopus_line) in puzzle_input.trim().split("\n").enumerate() {
-        for (x, octopus_char) in octopus_line.chars().enumerate() {
-            my_octopus_map.create_octopus(
-                x as i8,
-                y as i8,
-                octopus_char.to_digit(10).unwrap() as u8,
-            );
-        }
+impl Simulation for OctopusMap {
+    fn step(&mut self) -> bool {
+        self.simulate_step();
+        true
     }
-    my_octopus_map
 }
 
-pub(crate) fn solve_part_1(puzzle_input: String) -> u64 {
-    let mut my_octopus_map = load_octopodes(puzzle_input);
-    // println!("Initial map:\n{}", my_octopus_map);
+pub fn load_octopodes(puzzle_input: String) -> Result<OctopusMap, ParseError> {
+    let context = ParseContext::new(11, &puzzle_input);
+    let energy_levels = Grid::parse_digits(&context, &puzzle_input)?;
+    Ok(OctopusMap {
+        octopodes: energy_levels.map(|energy_level| Octopus {
+            energy_level: *energy_level,
+            has_flashed_this_step: false,
+        }),
+    })
+}
+
+pub fn solve_part_1(octopus_map: &OctopusMap) -> u64 {
+    let mut my_octopus_map = octopus_map.clone();
+    crate::log!(Trace, 11, "initial map:\n{}", my_octopus_map);
     let mut total_flashes: u64 = 0;
-    for _step in 1..=100 {
+    for step in 1..=100 {
         total_flashes += my_octopus_map.simulate_step();
-        // println!("After executing step {}:\n{}", step, my_octopus_map);
+        crate::log!(Trace, 11, "after step {}:\n{}", step, my_octopus_map);
     }
     total_flashes
 }
 
-pub(crate) fn solve_part_2(puzzle_input: String) -> u64 {
-    let mut my_octopus_map = load_octopodes(puzzle_input);
+pub fn solve_part_2(octopus_map: &OctopusMap) -> u64 {
+    let mut my_octopus_map = octopus_map.clone();
     let population_size: u64 = my_octopus_map.get_octopus_count() as u64;
     let mut steps_performed: u64 = 0;
     loop {
+        // Some grids never synchronize.
+        cancel::checkpoint();
         steps_performed += 1;
         if my_octopus_map.simulate_step() == population_size {
             break;
@@ -209,15 +145,65 @@ pub(crate) fn solve_part_2(puzzle_input: String) -> u64 {
     steps_performed
 }
 
+/// Generates a `size` by `size` grid (at least 1 by 1) of random
+/// energy levels whose octopodes all flash together within 1000 steps,
+/// so that part 2 has an answer.
+///
+/// Many random grids never synchronize, and big ones rarely do, so
+/// this tries up to 100 grids before settling for one where every
+/// octopus starts with the same energy level.
+pub fn generate_input(rng: &mut Rng, size: usize) -> String {
+    let size = size.max(1);
+    for _ in 0..100 {
+        let mut octopodes = String::new();
+        for _ in 0..size {
+            for _ in 0..size {
+                octopodes.push_str(&rng.below(10).to_string());
+            }
+            octopodes.push('\n');
+        }
+        let mut octopus_map = match load_octopodes(octopodes.clone()) {
+            Ok(octopus_map) => octopus_map,
+            Err(_) => break,
+        };
+        if (0..1000).any(|_| octopus_map.simulate_step() == (size * size) as u64) {
+            return octopodes;
+        }
+    }
+    let energy_level = rng.below(10).to_string();
+    format!("{}\n", energy_level.repeat(size)).repeat(size)
+}
+
+pub struct Day11;
+
+impl Solver for Day11 {
+    const DAY: u8 = 11;
+    type Input = OctopusMap;
+
+    fn parse(&self, puzzle_input: String) -> Result<OctopusMap, ParseError> {
+        load_octopodes(puzzle_input)
+    }
+
+    fn solve_part_1(&self, octopus_map: &OctopusMap) -> Result<Answer, Error> {
+        Ok(solve_part_1(octopus_map).into())
+    }
+
+    fn solve_part_2(&self, octopus_map: &OctopusMap) -> Result<Answer, Error> {
+        Ok(solve_part_2(octopus_map).into())
+    }
+}
+
 #[cfg(test)]
 use super::test_helpers;
 
 #[test]
 fn test_part_1() {
-    assert_eq!(solve_part_1(test_helpers::load_puzzle_input(11)), 1705);
+    let octopus_map = load_octopodes(test_helpers::load_puzzle_input(11)).unwrap();
+    assert_eq!(solve_part_1(&octopus_map), 1705);
 }
 
 #[test]
 fn test_part_2() {
-    assert_eq!(solve_part_2(test_helpers::load_puzzle_input(11)), 265);
+    let octopus_map = load_octopodes(test_helpers::load_puzzle_input(11)).unwrap();
+    assert_eq!(solve_part_2(&octopus_map), 265);
 }
@@ -1,6 +1,11 @@
 use std::collections::HashMap;
 use std::collections::HashSet;
 
+use crate::answer::Answer;
+use crate::error::{Error, ParseContext, ParseError};
+use crate::generate::Rng;
+use crate::solver::Solver;
+
 #[derive(Debug, PartialEq)]
 enum CaveSize {
     Small,
@@ -13,6 +18,15 @@ struct RoomProperties {
     neighbors: Vec<usize>,
 }
 
+/// Every room in the cave system, addressed by offset, along with the
+/// offsets of the start and end rooms.
+#[derive(Debug)]
+pub struct CaveSystem {
+    all_room_properties: Vec<RoomProperties>,
+    start_offset: usize,
+    end_offset: usize,
+}
+
 fn is_string_lower_case(test_string: &str) -> bool {
     let mut lower_string = String::from(test_string);
     lower_string.make_ascii_lowercase();
@@ -23,12 +37,12 @@ fn count_paths_to_end(
     current_room_offset: &usize,
     start_room_offset: &usize,
     end_room_offset: &usize,
-    all_room_properties: &Vec<RoomProperties>,
-    visit_counts: &Vec<usize>,
+    all_room_properties: &[RoomProperties],
+    visit_counts: &[usize],
     still_have_extra_time: bool,
 ) -> u64 {
     let mut paths_to_end: u64 = 0;
-    let mut updated_visit_counts = visit_counts.clone();
+    let mut updated_visit_counts = visit_counts.to_vec();
     updated_visit_counts[*current_room_offset] += 1;
     for possible_next_room_offset in all_room_properties[*current_room_offset].neighbors.iter() {
         if possible_next_room_offset == end_room_offset {
@@ -63,15 +77,36 @@ fn count_paths_to_end(
     paths_to_end
 }
 
-fn parse_room_properties(puzzle_input: &str) -> (Vec<RoomProperties>, usize, usize) {
+pub fn parse_room_properties(puzzle_input: &str) -> Result<CaveSystem, ParseError> {
+    let context = ParseContext::new(12, puzzle_input);
+    let room_pairs = puzzle_input
+        .trim()
+        .split("\n")
+        .map(|line| {
+            let mut cave_iter = line.split("-");
+            let first_room = context.next(&mut cave_iter, line, "a room label")?;
+            let second_room =
+                context.next(&mut cave_iter, first_room, "\"-\" followed by a room label")?;
+            for room_label in [first_room, second_room] {
+                if room_label.is_empty() || !room_label.chars().all(|c| c.is_ascii_alphabetic()) {
+                    return Err(context.error(room_label, "a room label"));
+                }
+            }
+            if let Some(extra) = cave_iter.next() {
+                return Err(context.error(extra, "end of line"));
+            }
+            Ok((first_room, second_room))
+        })
+        .collect::<Result<Vec<(&str, &str)>, ParseError>>()?;
+
     let mut room_label_set: HashSet<&str> = HashSet::new();
-    for room_label in puzzle_input.trim().split(&['\n', '-'][..]) {
-        room_label_set.insert(room_label);
+    for room_pair in room_pairs.iter() {
+        room_label_set.insert(room_pair.0);
+        room_label_set.insert(room_pair.1);
     }
     let mut room_to_offset_map: HashMap<&str, usize> = HashMap::new();
     let mut all_room_properties: Vec<RoomProperties> = Vec::new();
-    let mut offset: usize = 0;
-    for room_label in room_label_set {
+    for (offset, room_label) in room_label_set.into_iter().enumerate() {
         room_to_offset_map.insert(room_label, offset);
         all_room_properties.push(RoomProperties {
             size: match is_string_lower_case(room_label) {
@@ -80,62 +115,152 @@ fn parse_room_properties(puzzle_input: &str) -> (Vec<RoomProperties>, usize, usi
             },
             neighbors: Vec::new(),
         });
-        offset += 1;
     }
 
-    for room_pair in puzzle_input.trim().split("\n").map(|line| {
-        let mut cave_iter = line.split("-");
-        (cave_iter.next().unwrap(), cave_iter.next().unwrap())
-    }) {
-        all_room_properties[*room_to_offset_map.get(room_pair.0).unwrap()]
+    for room_pair in room_pairs {
+        all_room_properties[room_to_offset_map[room_pair.0]]
             .neighbors
-            .push(*room_to_offset_map.get(room_pair.1).unwrap());
-        all_room_properties[*room_to_offset_map.get(room_pair.1).unwrap()]
+            .push(room_to_offset_map[room_pair.1]);
+        all_room_properties[room_to_offset_map[room_pair.1]]
             .neighbors
-            .push(*room_to_offset_map.get(room_pair.0).unwrap());
+            .push(room_to_offset_map[room_pair.0]);
     }
-    (
+    let end_of_input = puzzle_input.trim_end();
+    Ok(CaveSystem {
         all_room_properties,
-        *room_to_offset_map.get("start").unwrap(),
-        *room_to_offset_map.get("end").unwrap(),
-    )
+        start_offset: *room_to_offset_map
+            .get("start")
+            .ok_or_else(|| context.missing(end_of_input, "a passage to the start room"))?,
+        end_offset: *room_to_offset_map
+            .get("end")
+            .ok_or_else(|| context.missing(end_of_input, "a passage to the end room"))?,
+    })
 }
 
-pub(crate) fn solve_part_1(puzzle_input: String) -> u64 {
-    let (all_room_properties, start_offset, end_offset) = parse_room_properties(&puzzle_input);
-    let visit_counts = vec![0 as usize; all_room_properties.len()];
+pub fn solve_part_1(cave_system: &CaveSystem) -> u64 {
+    let visit_counts = vec![0_usize; cave_system.all_room_properties.len()];
     count_paths_to_end(
-        &start_offset,
-        &start_offset,
-        &end_offset,
-        &all_room_properties,
+        &cave_system.start_offset,
+        &cave_system.start_offset,
+        &cave_system.end_offset,
+        &cave_system.all_room_properties,
         &visit_counts,
         false,
     )
 }
 
-pub(crate) fn solve_part_2(puzzle_input: String) -> u64 {
-    let (all_room_properties, start_offset, end_offset) = parse_room_properties(&puzzle_input);
-    let visit_counts = vec![0 as usize; all_room_properties.len()];
+pub fn solve_part_2(cave_system: &CaveSystem) -> u64 {
+    let visit_counts = vec![0_usize; cave_system.all_room_properties.len()];
     count_paths_to_end(
-        &start_offset,
-        &start_offset,
-        &end_offset,
-        &all_room_properties,
+        &cave_system.start_offset,
+        &cave_system.start_offset,
+        &cave_system.end_offset,
+        &cave_system.all_room_properties,
         &visit_counts,
         true,
     )
 }
 
+/// Generates a cave system with `size` connections between `start`,
+/// `end` and some small and big caves. Big caves are never connected
+/// to each other, since there would be infinitely many paths.
+pub fn generate_input(rng: &mut Rng, size: usize) -> String {
+    let mut labels = HashSet::from([String::from("start"), String::from("end")]);
+    let mut new_label = |rng: &mut Rng, first_letter: u8| loop {
+        let label = [
+            rng.below(26) as u8 + first_letter,
+            rng.below(26) as u8 + first_letter,
+        ]
+        .iter()
+        .map(|letter| *letter as char)
+        .collect::<String>();
+        if labels.insert(label.clone()) {
+            break label;
+        }
+    };
+    let small_caves = (0..(size / 4).max(2))
+        .map(|_| new_label(rng, b'a'))
+        .collect::<Vec<String>>();
+    let big_caves = (0..(size / 8).max(1))
+        .map(|_| new_label(rng, b'A'))
+        .collect::<Vec<String>>();
+    let caves = [small_caves.clone(), big_caves].concat();
+
+    fn connect(connections: &mut Vec<(String, String)>, first: &str, second: &str) {
+        let is_big = |label: &str| label.chars().all(|c| c.is_ascii_uppercase());
+        let exists = connections
+            .iter()
+            .any(|(a, b)| (a == first && b == second) || (a == second && b == first));
+        if first != second && !(is_big(first) && is_big(second)) && !exists {
+            connections.push((first.to_string(), second.to_string()));
+        }
+    }
+    let mut connections = Vec::new();
+    // Make sure every cave is reachable, then add more connections at
+    // random.
+    for end in ["start", "end"] {
+        for _ in 0..rng.range(1..=2) {
+            connect(&mut connections, end, rng.choose(&caves).as_str());
+        }
+    }
+    for (index, cave) in caves.iter().enumerate().skip(1) {
+        let other = match index < small_caves.len() {
+            true => &caves[rng.index(index)],
+            false => rng.choose(&small_caves),
+        };
+        connect(&mut connections, cave, other);
+    }
+    for _ in 0..size * 10 {
+        if connections.len() >= size {
+            break;
+        }
+        connect(
+            &mut connections,
+            rng.choose(&caves).as_str(),
+            rng.choose(&caves).as_str(),
+        );
+    }
+
+    rng.shuffle(&mut connections);
+    connections
+        .into_iter()
+        .map(|(first, second)| match rng.chance(0.5) {
+            true => format!("{}-{}\n", first, second),
+            false => format!("{}-{}\n", second, first),
+        })
+        .collect()
+}
+
+pub struct Day12;
+
+impl Solver for Day12 {
+    const DAY: u8 = 12;
+    type Input = CaveSystem;
+
+    fn parse(&self, puzzle_input: String) -> Result<CaveSystem, ParseError> {
+        parse_room_properties(&puzzle_input)
+    }
+
+    fn solve_part_1(&self, cave_system: &CaveSystem) -> Result<Answer, Error> {
+        Ok(solve_part_1(cave_system).into())
+    }
+
+    fn solve_part_2(&self, cave_system: &CaveSystem) -> Result<Answer, Error> {
+        Ok(solve_part_2(cave_system).into())
+    }
+}
+
 #[cfg(test)]
 use super::test_helpers;
 
 #[test]
 fn test_part_1() {
-    assert_eq!(solve_part_1(test_helpers::load_puzzle_input(12)), 5212);
+    let cave_system = parse_room_properties(&test_helpers::load_puzzle_input(12)).unwrap();
+    assert_eq!(solve_part_1(&cave_system), 5212);
 }
 
 #[test]
 fn test_part_2() {
-    assert_eq!(solve_part_2(test_helpers::load_puzzle_input(12)), 134862);
+    let cave_system = parse_room_properties(&test_helpers::load_puzzle_input(12)).unwrap();
+    assert_eq!(solve_part_2(&cave_system), 134862);
 }
@@ -1,40 +1,63 @@
 use std::collections::HashSet;
+use std::fmt;
 
-fn parse_input(puzzle_input: String) -> (HashSet<(i64, i64)>, Vec<(String, i64)>) {
-    let mut puzzle_parts = puzzle_input.trim().split("\n\n");
-    let dot_coordinates_list = puzzle_parts.next().unwrap();
-    let fold_instructions_list = puzzle_parts.next().unwrap();
+use crate::answer::Answer;
+use crate::error::{Error, ParseContext, ParseError};
+use crate::generate::Rng;
+use crate::simulation::Simulation;
+use crate::solver::Solver;
 
-    let dot_coordinates_iter = dot_coordinates_list.split("\n").map(|line| {
-        let mut ordinates_iter = line.split(",").map(|n| n.parse::<i64>().unwrap());
-        (
-            ordinates_iter.next().unwrap(), //x
-            ordinates_iter.next().unwrap(), //y
-        )
-    });
+/// The dots on the transparent paper, followed by the fold
+/// instructions in order.
+pub type TransparentPaper = (HashSet<(i64, i64)>, Vec<(String, i64)>);
+
+pub fn parse_input(puzzle_input: String) -> Result<TransparentPaper, ParseError> {
+    let context = ParseContext::new(13, &puzzle_input);
+    let trimmed_input = puzzle_input.trim();
+    let mut puzzle_parts = trimmed_input.split("\n\n");
+    let dot_coordinates_list = context.next(&mut puzzle_parts, trimmed_input, "dot coordinates")?;
+    let fold_instructions_list = context.next(
+        &mut puzzle_parts,
+        dot_coordinates_list,
+        "a blank line followed by fold instructions",
+    )?;
 
     let mut dot_coordinates = HashSet::<(i64, i64)>::new();
-    for coordinates in dot_coordinates_iter {
-        dot_coordinates.insert(coordinates);
+    for line in dot_coordinates_list.split("\n") {
+        let mut ordinates_iter = line.split(",");
+        let x = context.next(&mut ordinates_iter, line, "an x coordinate")?;
+        let y = context.next(&mut ordinates_iter, x, "\",\" followed by a y coordinate")?;
+        dot_coordinates.insert((
+            context.parse::<u32>(x, "a non-negative x coordinate")? as i64,
+            context.parse::<u32>(y, "a non-negative y coordinate")? as i64,
+        ));
     }
 
     let fold_instructions = fold_instructions_list
         .split("\n")
         .map(|line| {
             let mut line_iter = line.split("=");
-            (
-                String::from(line_iter.next().unwrap()),
-                line_iter.next().unwrap().parse::<i64>().unwrap(),
-            )
+            let axis =
+                context.next(&mut line_iter, line, "\"fold along x\" or \"fold along y\"")?;
+            if axis != "fold along x" && axis != "fold along y" {
+                return Err(context.error(axis, "\"fold along x\" or \"fold along y\""));
+            }
+            let position =
+                context.next(&mut line_iter, axis, "\"=\" followed by a fold position")?;
+            Ok((
+                String::from(axis),
+                context.parse::<u32>(position, "a non-negative fold position")? as i64,
+            ))
         })
-        .collect::<Vec<(String, i64)>>();
-    (dot_coordinates, fold_instructions)
+        .collect::<Result<Vec<(String, i64)>, ParseError>>()?;
+    Ok((dot_coordinates, fold_instructions))
 }
 
+/// Returns `None` if a dot lies on the fold line.
 fn perform_fold(
     dot_coordinates: &HashSet<(i64, i64)>,
     fold_instruction: &(String, i64),
-) -> HashSet<(i64, i64)> {
+) -> Option<HashSet<(i64, i64)>> {
     let mut new_dot_coordinates = HashSet::<(i64, i64)>::new();
     if fold_instruction.0 == "fold along y" {
         for old_dot_coordinate_pair in dot_coordinates {
@@ -46,7 +69,7 @@ fn perform_fold(
                     2 * fold_instruction.1 - old_dot_coordinate_pair.1,
                 ));
             } else {
-                panic!("Invalid coordinate found");
+                return None;
             }
         }
     } else if fold_instruction.0 == "fold along x" {
@@ -59,60 +82,99 @@ fn perform_fold(
                     old_dot_coordinate_pair.1,
                 ));
             } else {
-                panic!("Invalid coordinate found");
+                return None;
             }
         }
     } else {
-        panic!("Invalid fold instruction");
+        unreachable!("Fold instructions are validated by parse_input");
     }
-    new_dot_coordinates
+    Some(new_dot_coordinates)
 }
 
-// fn render_dots(dots: &HashSet<(i64, i64)>) {
-//     let x_min = dots.iter().map(|tuple| tuple.0).min().unwrap();
-//     let x_max = dots.iter().map(|tuple| tuple.0).max().unwrap();
-//     let y_min = dots.iter().map(|tuple| tuple.1).min().unwrap();
-//     let y_max = dots.iter().map(|tuple| tuple.1).max().unwrap();
-//     for y in y_min..=y_max {
-//         for x in x_min..=x_max {
-//             if dots.contains(&(x, y)) {
-//                 print!("#");
-//             } else {
-//                 print!(".");
-//             }
-//         }
-//         print!("\n");
-//     }
-// }
-
-fn ocr_character(character_dots: &[bool]) -> char {
-    match character_dots {
-        [true, true, true, false, true, false, false, true, true, true, true, false, true, false, false, true, true, false, false, true, true, true, true, false] => {
-            'B'
-        }
-        [true, true, true, true, true, false, false, false, true, true, true, false, true, false, false, false, true, false, false, false, true, false, false, false] => {
-            'F'
-        }
-        [false, true, true, false, true, false, false, true, true, false, false, false, true, false, true, true, true, false, false, true, false, true, true, true] => {
-            'G'
-        }
-        [true, false, false, true, true, false, false, true, true, true, true, true, true, false, false, true, true, false, false, true, true, false, false, true] => {
-            'H'
+/// The transparent paper part way through folding, for watching the
+/// folds one at a time.
+pub struct FoldSequence {
+    dot_coordinates: HashSet<(i64, i64)>,
+    fold_instructions: Vec<(String, i64)>,
+    folds_performed: usize,
+}
+
+impl From<TransparentPaper> for FoldSequence {
+    fn from((dot_coordinates, fold_instructions): TransparentPaper) -> Self {
+        FoldSequence {
+            dot_coordinates,
+            fold_instructions,
+            folds_performed: 0,
         }
-        [true, false, false, false, true, false, false, false, true, false, false, false, true, false, false, false, true, false, false, false, true, true, true, true] => {
-            'L'
+    }
+}
+
+impl fmt::Display for FoldSequence {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let dots = &self.dot_coordinates;
+        let x_max = dots.iter().map(|tuple| tuple.0).max().unwrap_or(0);
+        let y_max = dots.iter().map(|tuple| tuple.1).max().unwrap_or(0);
+        for y in 0..=y_max {
+            for x in 0..=x_max {
+                match dots.contains(&(x, y)) {
+                    true => write!(f, "#")?,
+                    false => write!(f, ".")?,
+                }
+            }
+            writeln!(f)?;
         }
-        [true, true, true, false, true, false, false, true, true, false, false, true, true, true, true, false, true, false, true, false, true, false, false, true] => {
-            'R'
+        match self.fold_instructions.get(self.folds_performed) {
+            Some((axis, position)) => write!(f, "next: {}={}", axis, position),
+            None => write!(f, "all folds done"),
         }
-        [true, false, false, true, true, false, false, true, true, false, false, true, true, false, false, true, true, false, false, true, false, true, true, false] => {
-            'U'
+    }
+}
+
+impl Simulation for FoldSequence {
+    /// Finishes after the last fold, or before a fold with a dot on
+    /// the fold line.
+    fn step(&mut self) -> bool {
+        let folded_dot_coordinates = self
+            .fold_instructions
+            .get(self.folds_performed)
+            .and_then(|fold_instruction| perform_fold(&self.dot_coordinates, fold_instruction));
+        match folded_dot_coordinates {
+            Some(dot_coordinates) => {
+                self.dot_coordinates = dot_coordinates;
+                self.folds_performed += 1;
+                true
+            }
+            None => false,
         }
-        _ => panic!("Unsupported OCR character"),
     }
 }
 
-fn ocr_dots(dots: &HashSet<(i64, i64)>) -> String {
+/// The letters that can be read from the folded paper, as rows of 4
+/// dots.
+const LETTER_DOTS: [(char, [&str; 6]); 7] = [
+    ('B', ["###.", "#..#", "###.", "#..#", "#..#", "###."]),
+    ('F', ["####", "#...", "###.", "#...", "#...", "#..."]),
+    ('G', [".##.", "#..#", "#...", "#.##", "#..#", ".###"]),
+    ('H', ["#..#", "#..#", "####", "#..#", "#..#", "#..#"]),
+    ('L', ["#...", "#...", "#...", "#...", "#...", "####"]),
+    ('R', ["###.", "#..#", "#..#", "###.", "#.#.", "#..#"]),
+    ('U', ["#..#", "#..#", "#..#", "#..#", "#..#", ".##."]),
+];
+
+fn ocr_character(character_dots: &[bool]) -> Option<char> {
+    LETTER_DOTS
+        .iter()
+        .find(|(_, rows)| {
+            rows.concat()
+                .chars()
+                .map(|dot| dot == '#')
+                .eq(character_dots.iter().copied())
+        })
+        .map(|(letter, _)| *letter)
+}
+
+/// Returns `None` if the dots do not spell out eight known letters.
+fn ocr_dots(dots: &HashSet<(i64, i64)>) -> Option<String> {
     let mut ocr_result = String::with_capacity(8);
     for x_offset in [0, 5, 10, 15, 20, 25, 30, 35] {
         let mut character_pattern = Vec::<bool>::with_capacity(24);
@@ -121,37 +183,156 @@ fn ocr_dots(dots: &HashSet<(i64, i64)>) -> String {
                 character_pattern.push(dots.contains(&(x + x_offset, y)));
             }
         }
-        ocr_result.push(ocr_character(&character_pattern));
+        ocr_result.push(ocr_character(&character_pattern)?);
     }
-    ocr_result
+    Some(ocr_result)
 }
 
-pub(crate) fn solve_part_1(puzzle_input: String) -> u64 {
-    let (dot_coordinates, fold_instructions) = parse_input(puzzle_input);
-    perform_fold(&dot_coordinates, &fold_instructions[0]).len() as u64
+/// Returns `None` if a dot lies on the first fold line.
+pub fn solve_part_1((dot_coordinates, fold_instructions): &TransparentPaper) -> Option<u64> {
+    Some(perform_fold(dot_coordinates, &fold_instructions[0])?.len() as u64)
 }
 
-pub(crate) fn solve_part_2(puzzle_input: String) -> String {
-    let (mut dot_coordinates, fold_instructions) = parse_input(puzzle_input);
+/// Returns `None` if a dot lies on a fold line or the folded paper
+/// cannot be read.
+pub fn solve_part_2((dot_coordinates, fold_instructions): &TransparentPaper) -> Option<String> {
+    let mut dot_coordinates = dot_coordinates.clone();
     for fold_instruction in fold_instructions {
-        dot_coordinates = perform_fold(&dot_coordinates, &fold_instruction);
+        dot_coordinates = perform_fold(&dot_coordinates, fold_instruction)?;
     }
-    // render_dots(&dot_coordinates);
     ocr_dots(&dot_coordinates)
 }
 
+/// Generates at least `size` dots and the folds that take the paper
+/// down to eight random letters in 40 by 6, like a real puzzle input.
+/// No dot lies on a fold line.
+pub fn generate_input(rng: &mut Rng, size: usize) -> String {
+    let (mut width, mut height) = (40, 6);
+    let mut x_folds = Vec::new();
+    let mut y_folds = Vec::new();
+    for _ in 0..5 {
+        x_folds.push(width);
+        width = 2 * width + 1;
+    }
+    for _ in 0..7 {
+        y_folds.push(height);
+        height = 2 * height + 1;
+    }
+    let mut folds = Vec::new();
+    while !x_folds.is_empty() || !y_folds.is_empty() {
+        let fold = match (x_folds.is_empty(), y_folds.is_empty()) {
+            (false, true) => ("x", x_folds.pop().unwrap()),
+            (false, false) if rng.chance(0.5) => ("x", x_folds.pop().unwrap()),
+            _ => ("y", y_folds.pop().unwrap()),
+        };
+        folds.push(fold);
+    }
+
+    let mut letter_dots = Vec::new();
+    for x_offset in [0, 5, 10, 15, 20, 25, 30, 35] {
+        let (_, rows) = rng.choose(&LETTER_DOTS);
+        for (y, row) in rows.iter().enumerate() {
+            for (x, dot) in row.chars().enumerate() {
+                if dot == '#' {
+                    letter_dots.push((x as i64 + x_offset, y as i64));
+                }
+            }
+        }
+    }
+    // Every dot on the unfolded paper lands on a dot of a letter, and
+    // every dot of a letter has at least one dot landing on it.
+    let unfold = |rng: &mut Rng, (mut x, mut y): (i64, i64)| {
+        for (axis, position) in folds.iter().rev() {
+            let ordinate = match *axis {
+                "x" => &mut x,
+                _ => &mut y,
+            };
+            if rng.chance(0.5) {
+                *ordinate = 2 * position - *ordinate;
+            }
+        }
+        (x, y)
+    };
+    let mut dots = letter_dots
+        .iter()
+        .map(|dot| unfold(rng, *dot))
+        .collect::<Vec<(i64, i64)>>();
+    let mut dot_set = dots.iter().copied().collect::<HashSet<(i64, i64)>>();
+    for _ in 0..size * 10 {
+        if dots.len() >= size {
+            break;
+        }
+        let letter_dot = letter_dots[rng.index(letter_dots.len())];
+        let dot = unfold(rng, letter_dot);
+        if dot_set.insert(dot) {
+            dots.push(dot);
+        }
+    }
+    rng.shuffle(&mut dots);
+
+    let mut paper = String::new();
+    for (x, y) in dots {
+        paper.push_str(&format!("{},{}\n", x, y));
+    }
+    paper.push('\n');
+    for (axis, position) in folds {
+        paper.push_str(&format!("fold along {}={}\n", axis, position));
+    }
+    paper
+}
+
+pub struct Day13;
+
+impl Solver for Day13 {
+    const DAY: u8 = 13;
+    type Input = TransparentPaper;
+
+    fn parse(&self, puzzle_input: String) -> Result<TransparentPaper, ParseError> {
+        parse_input(puzzle_input)
+    }
+
+    fn solve_part_1(&self, transparent_paper: &TransparentPaper) -> Result<Answer, Error> {
+        solve_part_1(transparent_paper)
+            .map(Answer::from)
+            .ok_or_else(|| Error::unsolvable(13, 1, "a dot lies on the fold line"))
+    }
+
+    fn solve_part_2(&self, transparent_paper: &TransparentPaper) -> Result<Answer, Error> {
+        solve_part_2(transparent_paper)
+            .map(Answer::from)
+            .ok_or_else(|| {
+                Error::unsolvable(13, 2, "the folded paper does not spell out a known code")
+            })
+    }
+}
+
 #[cfg(test)]
 use super::test_helpers;
 
 #[test]
 fn test_part_1() {
-    assert_eq!(solve_part_1(test_helpers::load_puzzle_input(13)), 810);
+    let transparent_paper = parse_input(test_helpers::load_puzzle_input(13)).unwrap();
+    assert_eq!(solve_part_1(&transparent_paper), Some(810));
 }
 
 #[test]
 fn test_part_2() {
+    let transparent_paper = parse_input(test_helpers::load_puzzle_input(13)).unwrap();
+    assert_eq!(
+        solve_part_2(&transparent_paper),
+        Some(String::from("HLBUBGFR"))
+    );
+}
+
+#[test]
+fn test_fold_sequence() {
+    let transparent_paper = parse_input(String::from("0,0\n4,1\n\nfold along x=2\n")).unwrap();
+    let mut fold_sequence = FoldSequence::from(transparent_paper);
     assert_eq!(
-        solve_part_2(test_helpers::load_puzzle_input(13)),
-        "HLBUBGFR"
+        fold_sequence.to_string(),
+        "#....\n....#\nnext: fold along x=2"
     );
+    assert!(fold_sequence.step());
+    assert_eq!(fold_sequence.to_string(), "#\n#\nall folds done");
+    assert!(!fold_sequence.step());
 }
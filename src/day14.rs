@@ -1,16 +1,24 @@
 use std::collections::HashMap;
 
+use crate::answer::Answer;
+use crate::error::{Error, ParseContext, ParseError};
+use crate::generate::Rng;
+use crate::solver::Solver;
+
 /// Represents a pair insertion rule.
 ///
 /// `"CH -> B"` is represented as `PairInsertionRule { first: 'C',
 /// second: 'H', insert: 'B' }`
 #[derive(Debug, PartialEq)]
-struct PairInsertionRule {
+pub struct PairInsertionRule {
     first: char,
     second: char,
     insert: char,
 }
 
+/// The polymer template followed by the pair insertion rules.
+pub type PolymerInstructions = (Vec<char>, Vec<PairInsertionRule>);
+
 /// Parses the puzzle input.
 ///
 /// Returns a tuple. The first value, `Vec<char>`, contains the
@@ -18,29 +26,50 @@ struct PairInsertionRule {
 /// puzzle input. The second value, `Vec<PairInsertionRule>` contains
 /// [`PairInsertionRule`]s representing each pair insertion rule on
 /// subsequent lines of the puzzle input.
-fn parse_input(puzzle_input: String) -> (Vec<char>, Vec<PairInsertionRule>) {
-    let mut puzzle_part_iter = puzzle_input.trim().split("\n\n");
-    let polymer_template = puzzle_part_iter
-        .next()
-        .unwrap()
-        .chars()
-        .collect::<Vec<char>>();
-    let pair_insertion_rules = puzzle_part_iter
-        .next()
-        .unwrap()
+pub fn parse_input(puzzle_input: String) -> Result<PolymerInstructions, ParseError> {
+    let context = ParseContext::new(14, &puzzle_input);
+    let trimmed_input = puzzle_input.trim();
+    let mut puzzle_part_iter = trimmed_input.split("\n\n");
+    let template_line = context.next(&mut puzzle_part_iter, trimmed_input, "a polymer template")?;
+    if template_line.chars().count() < 2 || template_line.contains(char::is_whitespace) {
+        return Err(context.error(template_line, "a polymer template of at least two elements"));
+    }
+    let polymer_template = template_line.chars().collect::<Vec<char>>();
+    let pair_insertion_rules = context
+        .next(
+            &mut puzzle_part_iter,
+            template_line,
+            "a blank line followed by pair insertion rules",
+        )?
         .split("\n")
         .map(|line| {
             let mut line_parts_iter = line.split(" -> ");
-            let mut pair_char_iter = line_parts_iter.next().unwrap().chars();
-            let insert = line_parts_iter.next().unwrap().chars().next().unwrap();
-            PairInsertionRule {
-                first: pair_char_iter.next().unwrap(),
-                second: pair_char_iter.next().unwrap(),
-                insert,
+            let pair = context.next(&mut line_parts_iter, line, "a pair of elements")?;
+            let insert = context.next(
+                &mut line_parts_iter,
+                pair,
+                "\" -> \" followed by an element",
+            )?;
+            let mut pair_char_iter = pair.chars();
+            let mut insert_char_iter = insert.chars();
+            match (
+                pair_char_iter.next(),
+                pair_char_iter.next(),
+                pair_char_iter.next(),
+                insert_char_iter.next(),
+                insert_char_iter.next(),
+            ) {
+                (Some(first), Some(second), None, Some(insert), None) => Ok(PairInsertionRule {
+                    first,
+                    second,
+                    insert,
+                }),
+                (Some(_), Some(_), None, _, _) => Err(context.error(insert, "a single element")),
+                _ => Err(context.error(pair, "a pair of elements")),
             }
         })
-        .collect::<Vec<PairInsertionRule>>();
-    (polymer_template, pair_insertion_rules)
+        .collect::<Result<Vec<PairInsertionRule>, ParseError>>()?;
+    Ok((polymer_template, pair_insertion_rules))
 }
 
 /// Determines the resultant element frequency when pair insertion
@@ -56,18 +85,18 @@ fn parse_input(puzzle_input: String) -> (Vec<char>, Vec<PairInsertionRule>) {
 /// `pair_insertion_rules` are the rules provided in the puzzle input.
 ///
 /// `cache` is used for memoization.
+///
+/// Returns `None` if a pair that needs expanding has no pair insertion
+/// rule.
 fn pair_to_element_frequency(
     first_element: char,
     second_element: char,
     steps: u32,
     pair_insertion_rules: &Vec<PairInsertionRule>,
     cache: &mut HashMap<(char, char, u32), HashMap<char, u64>>,
-) -> HashMap<char, u64> {
-    if cache.contains_key(&(first_element, second_element, steps)) {
-        return cache
-            .get(&(first_element, second_element, steps))
-            .unwrap()
-            .clone();
+) -> Option<HashMap<char, u64>> {
+    if let Some(element_frequency) = cache.get(&(first_element, second_element, steps)) {
+        return Some(element_frequency.clone());
     }
     let mut element_frequency = HashMap::<char, u64>::new();
     if steps == 0 {
@@ -81,32 +110,31 @@ fn pair_to_element_frequency(
             (first_element, second_element, steps),
             element_frequency.clone(),
         );
-        return element_frequency;
+        return Some(element_frequency);
     }
     let middle_element = pair_insertion_rules
         .iter()
         .filter(|rule| rule.first == first_element && rule.second == second_element)
         .map(|rule| rule.insert)
-        .next()
-        .unwrap();
+        .next()?;
     let first_frequencies = pair_to_element_frequency(
         first_element,
         middle_element,
         steps - 1,
         pair_insertion_rules,
         cache,
-    );
+    )?;
     let second_frequencies = pair_to_element_frequency(
         middle_element,
         second_element,
         steps - 1,
         pair_insertion_rules,
         cache,
-    );
+    )?;
     let result =
         reduce_adjacent_frequencies(&first_frequencies, &second_frequencies, middle_element);
     cache.insert((first_element, second_element, steps), result.clone());
-    result
+    Some(result)
 }
 
 /// Reduces two hashes containing element frequencies into one.
@@ -130,17 +158,17 @@ fn reduce_adjacent_frequencies(
     reduced_frequencies
 }
 
-pub(crate) fn solve_part_1(puzzle_input: String) -> String {
-    let (polymer_template, pair_insertion_rules) = parse_input(puzzle_input);
-
+/// Returns `None` if a pair that needs expanding has no pair insertion
+/// rule.
+pub fn solve_part_1((polymer_template, pair_insertion_rules): &PolymerInstructions) -> Option<u64> {
     let mut cache: HashMap<(char, char, u32), HashMap<char, u64>> = HashMap::new();
     let mut element_frequencies: HashMap<char, u64> = pair_to_element_frequency(
         polymer_template[0],
         polymer_template[1],
         10,
-        &pair_insertion_rules,
+        pair_insertion_rules,
         &mut cache,
-    );
+    )?;
 
     let mut polymer_index: usize = 1;
 
@@ -149,9 +177,9 @@ pub(crate) fn solve_part_1(puzzle_input: String) -> String {
             polymer_template[polymer_index],
             polymer_template[polymer_index + 1],
             10,
-            &pair_insertion_rules,
+            pair_insertion_rules,
             &mut cache,
-        );
+        )?;
         element_frequencies = reduce_adjacent_frequencies(
             &element_frequencies,
             &next_element_frequencies,
@@ -160,21 +188,20 @@ pub(crate) fn solve_part_1(puzzle_input: String) -> String {
         polymer_index += 1;
     }
 
-    (element_frequencies.values().max().unwrap() - element_frequencies.values().min().unwrap())
-        .to_string()
+    Some(element_frequencies.values().max()? - element_frequencies.values().min()?)
 }
 
-pub(crate) fn solve_part_2(puzzle_input: String) -> String {
-    let (polymer_template, pair_insertion_rules) = parse_input(puzzle_input);
-
+/// Returns `None` if a pair that needs expanding has no pair insertion
+/// rule.
+pub fn solve_part_2((polymer_template, pair_insertion_rules): &PolymerInstructions) -> Option<u64> {
     let mut cache: HashMap<(char, char, u32), HashMap<char, u64>> = HashMap::new();
     let mut element_frequencies: HashMap<char, u64> = pair_to_element_frequency(
         polymer_template[0],
         polymer_template[1],
         40,
-        &pair_insertion_rules,
+        pair_insertion_rules,
         &mut cache,
-    );
+    )?;
 
     let mut polymer_index: usize = 1;
 
@@ -183,9 +210,9 @@ pub(crate) fn solve_part_2(puzzle_input: String) -> String {
             polymer_template[polymer_index],
             polymer_template[polymer_index + 1],
             40,
-            &pair_insertion_rules,
+            pair_insertion_rules,
             &mut cache,
-        );
+        )?;
         element_frequencies = reduce_adjacent_frequencies(
             &element_frequencies,
             &next_element_frequencies,
@@ -194,8 +221,55 @@ pub(crate) fn solve_part_2(puzzle_input: String) -> String {
         polymer_index += 1;
     }
 
-    (element_frequencies.values().max().unwrap() - element_frequencies.values().min().unwrap())
-        .to_string()
+    Some(element_frequencies.values().max()? - element_frequencies.values().min()?)
+}
+
+/// Generates a template of length `size` (at least 2) made from 10 elements, with a
+/// rule for every pair of them.
+pub fn generate_input(rng: &mut Rng, size: usize) -> String {
+    let size = size.max(2);
+    let mut elements = ('A'..='Z').collect::<Vec<char>>();
+    rng.shuffle(&mut elements);
+    elements.truncate(10);
+    let template = (0..size)
+        .map(|_| *rng.choose(&elements))
+        .collect::<String>();
+    let mut rules = Vec::new();
+    for first in elements.iter() {
+        for second in elements.iter() {
+            rules.push(format!(
+                "{}{} -> {}\n",
+                first,
+                second,
+                rng.choose(&elements)
+            ));
+        }
+    }
+    rng.shuffle(&mut rules);
+    format!("{}\n\n{}", template, rules.concat())
+}
+
+pub struct Day14;
+
+impl Solver for Day14 {
+    const DAY: u8 = 14;
+    type Input = PolymerInstructions;
+
+    fn parse(&self, puzzle_input: String) -> Result<PolymerInstructions, ParseError> {
+        parse_input(puzzle_input)
+    }
+
+    fn solve_part_1(&self, polymer_instructions: &PolymerInstructions) -> Result<Answer, Error> {
+        solve_part_1(polymer_instructions)
+            .map(Answer::from)
+            .ok_or_else(|| Error::unsolvable(14, 1, "a pair has no pair insertion rule"))
+    }
+
+    fn solve_part_2(&self, polymer_instructions: &PolymerInstructions) -> Result<Answer, Error> {
+        solve_part_2(polymer_instructions)
+            .map(Answer::from)
+            .ok_or_else(|| Error::unsolvable(14, 2, "a pair has no pair insertion rule"))
+    }
 }
 
 #[cfg(test)]
@@ -203,21 +277,20 @@ use super::test_helpers;
 
 #[test]
 fn test_part_1() {
-    assert_eq!(solve_part_1(test_helpers::load_puzzle_input(14)), "2657");
+    let polymer_instructions = parse_input(test_helpers::load_puzzle_input(14)).unwrap();
+    assert_eq!(solve_part_1(&polymer_instructions), Some(2657));
 }
 
 #[test]
 fn test_part_2() {
-    assert_eq!(
-        solve_part_2(test_helpers::load_puzzle_input(14)),
-        "2911561572630"
-    );
+    let polymer_instructions = parse_input(test_helpers::load_puzzle_input(14)).unwrap();
+    assert_eq!(solve_part_2(&polymer_instructions), Some(2911561572630));
 }
 
 #[test]
 fn test_parse_input() {
     let (polymer_template, pair_insertion_rules) =
-        parse_input("ABCD\n\nAB -> C\nBC -> D\n".to_string());
+        parse_input("ABCD\n\nAB -> C\nBC -> D\n".to_string()).unwrap();
     assert_eq!(polymer_template, vec!['A', 'B', 'C', 'D']);
     assert_eq!(
         pair_insertion_rules,
@@ -243,7 +316,7 @@ fn test_pair_to_element_frequency() {
     result.insert('N', 2);
     assert_eq!(
         pair_to_element_frequency('N', 'N', 0, &Vec::<PairInsertionRule>::new(), &mut cache),
-        result
+        Some(result.clone())
     );
     cache.clear();
     result.clear();
@@ -262,7 +335,7 @@ fn test_pair_to_element_frequency() {
             }],
             &mut cache
         ),
-        result
+        Some(result)
     );
 }
 
@@ -1,185 +1,419 @@
-use std::collections::HashMap;
-use std::convert::TryInto;
+use std::collections::HashSet;
 
-struct CavernMap {
-    risk_levels: HashMap<(i64, i64), i64>,
-    min_cost_to_origin: HashMap<(i64, i64), i64>,
-    bottom_right: (i64, i64),
-    locations_touched_by_last_propagation: Vec<(i64, i64)>,
+use termion::{color, style};
+
+use crate::answer::Answer;
+use crate::error::{Error, ParseContext, ParseError};
+use crate::generate::Rng;
+use crate::grid::{Grid, Neighborhood};
+use crate::pathfinding::{self, Path};
+use crate::solver::Solver;
+
+/// Risk levels of every location in the cavern.
+#[derive(Clone)]
+pub struct CavernMap {
+    risk_levels: Grid<u8>,
 }
 
 impl CavernMap {
-    fn from(text_map: String) -> Self {
-        let mut new_cavern_map = CavernMap {
-            risk_levels: HashMap::new(),
-            min_cost_to_origin: HashMap::new(),
-            bottom_right: (0, 0),
-            locations_touched_by_last_propagation: vec![(0, 0)],
-        };
-        for (y, line) in text_map.trim().split("\n").enumerate() {
-            for (x, risk_level) in line.chars().enumerate() {
-                new_cavern_map.risk_levels.insert(
-                    (x.try_into().unwrap(), y.try_into().unwrap()),
-                    risk_level.to_digit(10).unwrap().into(),
-                );
-                if x != 0 || y != 0 {
-                    new_cavern_map
-                        .min_cost_to_origin
-                        .insert((x.try_into().unwrap(), y.try_into().unwrap()), i64::MAX);
-                }
-            }
+    pub fn from(text_map: String) -> Result<Self, ParseError> {
+        Ok(CavernMap {
+            risk_levels: Grid::parse_digits(&ParseContext::new(15, &text_map), &text_map)?,
+        })
+    }
+
+    pub fn get_bottom_right(&self) -> (usize, usize) {
+        (self.risk_levels.width() - 1, self.risk_levels.height() - 1)
+    }
+
+    pub fn get_risk_level(&self, (x, y): (usize, usize)) -> Option<u8> {
+        self.risk_levels.get(x, y).copied()
+    }
+
+    /// The cavern as seen with `options`. Risk levels of the tiles are
+    /// worked out when they are needed rather than stored.
+    pub fn with_options(&self, options: CavernOptions) -> Cavern<'_> {
+        assert!(options.tile_factor > 0, "The tile factor must be positive");
+        assert!(
+            options.wrap_modulus > 0,
+            "The wrap modulus must be positive"
+        );
+        Cavern {
+            cavern_map: self,
+            options,
         }
-        new_cavern_map.min_cost_to_origin.insert((0, 0), 0);
-        let max_x = new_cavern_map
-            .risk_levels
-            .keys()
-            .map(|(x, _y)| x)
-            .max()
-            .unwrap();
-        let max_y = new_cavern_map
-            .risk_levels
-            .keys()
-            .map(|(_x, y)| y)
-            .max()
-            .unwrap();
-        new_cavern_map.bottom_right = (*max_x, *max_y);
+    }
+}
+
+/// How to tile a cavern map into a bigger cavern and move around it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CavernOptions {
+    /// How many copies of the map there are across and down.
+    pub tile_factor: usize,
+    /// Each tile to the right or down has risk levels one higher than
+    /// the last, wrapping around from `wrap_modulus` back to 1.
+    pub wrap_modulus: u8,
+    pub neighborhood: Neighborhood,
+}
 
-        new_cavern_map
+impl CavernOptions {
+    pub const PART_1: CavernOptions = CavernOptions {
+        tile_factor: 1,
+        wrap_modulus: 9,
+        neighborhood: Neighborhood::Four,
+    };
+    pub const PART_2: CavernOptions = CavernOptions {
+        tile_factor: 5,
+        ..CavernOptions::PART_1
+    };
+}
+
+/// A cavern map tiled according to some [`CavernOptions`].
+pub struct Cavern<'a> {
+    cavern_map: &'a CavernMap,
+    options: CavernOptions,
+}
+
+impl Cavern<'_> {
+    pub fn width(&self) -> usize {
+        self.cavern_map.risk_levels.width() * self.options.tile_factor
     }
 
-    fn get_neighbors(&self, (x, y): &(i64, i64)) -> Vec<(i64, i64)> {
-        let possible_neighbors = [(x - 1, *y), (x + 1, *y), (*x, y - 1), (*x, y + 1)];
-        // possible_neighbors
-        //     .iter()
-        //     .filter(|possible_neighbor| self.risk_levels.contains_key(possible_neighbor))
-        //     .map(|neighbor| *neighbor)
-        //     .collect::<Vec<(i64, i64)>>()
-        possible_neighbors
+    pub fn height(&self) -> usize {
+        self.cavern_map.risk_levels.height() * self.options.tile_factor
+    }
+
+    pub fn get_bottom_right(&self) -> (usize, usize) {
+        (self.width() - 1, self.height() - 1)
+    }
+
+    pub fn get_risk_level(&self, (x, y): (usize, usize)) -> Option<u8> {
+        if x >= self.width() || y >= self.height() {
+            return None;
+        }
+        let risk_levels = &self.cavern_map.risk_levels;
+        let (tile_x, tile_y) = (x / risk_levels.width(), y / risk_levels.height());
+        let risk_level = risk_levels[(x % risk_levels.width(), y % risk_levels.height())];
+        Some(self.wrap(risk_level, tile_x + tile_y))
+    }
+
+    /// The risk level of a location with `risk_level` on the map, in a
+    /// tile `tile_offset` tiles right and down from the top left one.
+    /// This wraps around even in the top left tile.
+    fn wrap(&self, risk_level: u8, tile_offset: usize) -> u8 {
+        let wrapped = (risk_level as isize - 1 + tile_offset as isize)
+            % self.options.wrap_modulus as isize
+            + 1;
+        wrapped as u8
+    }
+
+    /// The lowest risk level of any location in the cavern, after
+    /// wrapping.
+    fn lowest_risk_level(&self) -> u8 {
+        let risk_levels = self.cavern_map.risk_levels.iter().collect::<HashSet<_>>();
+        // Tile offsets repeat every `wrap_modulus`.
+        let max_tile_offset =
+            (2 * (self.options.tile_factor - 1)).min(self.options.wrap_modulus as usize - 1);
+        risk_levels
             .iter()
-            .filter(|(x, y)| {
-                *x >= 0 && *x <= self.bottom_right.0 && *y >= 0 && *y <= self.bottom_right.1
+            .flat_map(|risk_level| {
+                (0..=max_tile_offset).map(move |tile_offset| self.wrap(**risk_level, tile_offset))
             })
-            .map(|neighbor| *neighbor)
-            .collect::<Vec<(i64, i64)>>()
-    }
-
-    fn get_bottom_right(&self) -> (i64, i64) {
-        self.bottom_right
-    }
-
-    // fn get_risk_level(&self, (x, y): &(i64, i64)) -> i64 {
-    //     *self.risk_levels.get(&(*x, *y)).unwrap()
-    // }
-
-    // fn max_cost(&self) -> i64 {
-    //     *self.min_cost_to_origin.values().max().unwrap()
-    // }
-
-    fn total_cost(&self) -> i64 {
-        self.min_cost_to_origin.values().sum::<i64>()
-    }
-
-    fn propagate_costs(&mut self) {
-        // let mut new_min_cost_to_origin: HashMap<(i64, i64), i64> = HashMap::new();
-        let mut new_locations_touched: Vec<(i64, i64)> = Vec::new();
-        // update following logic to only update neighbors of
-        // locations touched by last propagation:
-        for previously_touched_location in &self.locations_touched_by_last_propagation {
-            let cost = *self
-                .min_cost_to_origin
-                .get(&previously_touched_location)
-                .unwrap();
-            for neighbor_location in self.get_neighbors(&previously_touched_location) {
-                let neighbor_risk_level = self.risk_levels.get(&neighbor_location).unwrap();
-                let neighbor_minimum_cost =
-                    self.min_cost_to_origin.get_mut(&neighbor_location).unwrap();
-                if cost + neighbor_risk_level < *neighbor_minimum_cost {
-                    *neighbor_minimum_cost = cost + neighbor_risk_level;
-                    new_locations_touched.push(neighbor_location);
-                }
+            .min()
+            .unwrap_or(0)
+    }
+
+    /// A path from the top left to the bottom right with the lowest
+    /// total risk, or `None` if the bottom right cannot be reached. The
+    /// risk level of the top left is not counted.
+    pub fn lowest_risk_path(&self) -> Option<Path<(usize, usize)>> {
+        let path = pathfinding::shortest_path_in_rectangle(
+            (self.width(), self.height()),
+            self.options.neighborhood,
+            (0, 0),
+            self.get_bottom_right(),
+            |location| self.get_risk_level(location).map(u64::from),
+            self.lowest_risk_level().into(),
+        );
+        match &path {
+            Some(path) => crate::log!(
+                Debug,
+                15,
+                "lowest total risk to {:?} is {}, along a path through {} locations",
+                self.get_bottom_right(),
+                path.cost,
+                path.nodes.len()
+            ),
+            None => crate::log!(Debug, 15, "{:?} cannot be reached", self.get_bottom_right()),
+        }
+        path
+    }
+
+    /// Draws the risk levels as digits, with those on `path` in bold
+    /// red and the rest dimmed.
+    pub fn render_terminal(&self, path: &[(usize, usize)]) -> String {
+        let on_path = path.iter().collect::<HashSet<_>>();
+        let mut rendered = String::new();
+        for y in 0..self.height() {
+            for x in 0..self.width() {
+                let risk_level = self.get_risk_level((x, y)).unwrap();
+                rendered.push_str(&match on_path.contains(&(x, y)) {
+                    true => format!(
+                        "{}{}{}{}",
+                        style::Bold,
+                        color::Fg(color::Red),
+                        risk_level,
+                        style::Reset
+                    ),
+                    false => format!("{}{}{}", style::Faint, risk_level, style::Reset),
+                });
             }
+            rendered.push('\n');
         }
-        self.locations_touched_by_last_propagation = new_locations_touched;
-    }
-
-    fn enlarge_cavern(&mut self) {
-        // for part 2
-        let mut enlarged_risk_levels: HashMap<(i64, i64), i64> = HashMap::new();
-        for (location, risk_level) in self.risk_levels.iter() {
-            for x_offset in 0..=4 {
-                for y_offset in 0..=4 {
-                    let new_location = (
-                        location.0 + x_offset * (self.bottom_right.0 + 1),
-                        location.1 + y_offset * (self.bottom_right.1 + 1),
-                    );
-                    let new_risk_level = (risk_level - 1 + x_offset + y_offset) % 9 + 1;
-                    enlarged_risk_levels.insert(new_location, new_risk_level);
-                }
+        rendered
+    }
+
+    /// Draws the cavern as a binary PPM image with `cell_size` pixels
+    /// to a side for each location, shaded from light for risk level 1
+    /// to dark for 9, with `path` in red.
+    pub fn render_ppm(&self, path: &[(usize, usize)], cell_size: usize) -> Vec<u8> {
+        let on_path = path.iter().collect::<HashSet<_>>();
+        let width = self.width() * cell_size;
+        let height = self.height() * cell_size;
+        let mut image = format!("P6\n{} {}\n255\n", width, height).into_bytes();
+        for y in 0..height {
+            for x in 0..width {
+                let location = (x / cell_size, y / cell_size);
+                image.extend_from_slice(&match on_path.contains(&location) {
+                    true => PATH_COLOUR,
+                    false => risk_colour(self.get_risk_level(location).unwrap()),
+                });
             }
         }
-        self.risk_levels = enlarged_risk_levels;
-        let mut enlarged_min_cost_to_origin: HashMap<(i64, i64), i64> = HashMap::new();
-        for location in self.risk_levels.keys() {
-            enlarged_min_cost_to_origin.insert(*location, i64::MAX);
+        image
+    }
+
+    /// Draws the cavern as an SVG image with `cell_size` pixels to a
+    /// side for each location, shaded as for [`Self::render_ppm`], with
+    /// `path` as a red line through the middle of its locations.
+    pub fn render_svg(&self, path: &[(usize, usize)], cell_size: usize) -> String {
+        let width = self.width() * cell_size;
+        let height = self.height() * cell_size;
+        let mut svg = format!(
+            "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{}\" height=\"{}\" shape-rendering=\"crispEdges\">\n",
+            width, height
+        );
+        for (x, y) in (0..self.height()).flat_map(|y| (0..self.width()).map(move |x| (x, y))) {
+            let [red, green, blue] = risk_colour(self.get_risk_level((x, y)).unwrap());
+            svg.push_str(&format!(
+                "<rect x=\"{}\" y=\"{}\" width=\"{}\" height=\"{}\" fill=\"#{:02x}{:02x}{:02x}\"/>\n",
+                x * cell_size,
+                y * cell_size,
+                cell_size,
+                cell_size,
+                red,
+                green,
+                blue
+            ));
         }
-        enlarged_min_cost_to_origin.insert((0, 0), 0);
-        self.min_cost_to_origin = enlarged_min_cost_to_origin;
-        let max_x = self.risk_levels.keys().map(|(x, _y)| x).max().unwrap();
-        let max_y = self.risk_levels.keys().map(|(_x, y)| y).max().unwrap();
-        self.bottom_right = (*max_x, *max_y);
-        println!("Bottom right is now {:?}", self.bottom_right);
+        let points = path
+            .iter()
+            .map(|(x, y)| {
+                format!(
+                    "{},{}",
+                    x * cell_size + cell_size / 2,
+                    y * cell_size + cell_size / 2
+                )
+            })
+            .collect::<Vec<String>>()
+            .join(" ");
+        let [red, green, blue] = PATH_COLOUR;
+        svg.push_str(&format!(
+            "<polyline points=\"{}\" fill=\"none\" stroke=\"#{:02x}{:02x}{:02x}\" stroke-width=\"{}\" stroke-linejoin=\"round\"/>\n</svg>\n",
+            points,
+            red,
+            green,
+            blue,
+            (cell_size as f64 / 3.0).max(1.0)
+        ));
+        svg
     }
 }
 
-pub(crate) fn solve_part_1(puzzle_input: String) -> String {
-    let mut cavern_map = CavernMap::from(puzzle_input);
-    let minimum_passes = cavern_map.bottom_right.0 + cavern_map.bottom_right.1;
-    for _ in 1..=minimum_passes {
-        println!("Propagating costs.");
-        cavern_map.propagate_costs();
-    }
+const PATH_COLOUR: [u8; 3] = [220, 30, 30];
+
+/// A shade of grey from light for risk level 1 to dark for 9.
+fn risk_colour(risk_level: u8) -> [u8; 3] {
+    let shade = 255 - risk_level.min(9) * 25;
+    [shade, shade, shade]
+}
+
+pub fn solve_part_1(cavern_map: &CavernMap) -> u64 {
+    lowest_total_risk(cavern_map, CavernOptions::PART_1).unwrap()
+}
 
-    // println!(
-    //     "No minimum costs are i64::MAX. Largest is now {}",
-    //     cavern_map.max_cost()
-    // );
+pub fn solve_part_2(cavern_map: &CavernMap) -> u64 {
+    lowest_total_risk(cavern_map, CavernOptions::PART_2).unwrap()
+}
+
+/// The lowest total risk of getting from the top left to the bottom
+/// right of the cavern seen with `options`, or `None` if the bottom
+/// right cannot be reached.
+pub fn lowest_total_risk(cavern_map: &CavernMap, options: CavernOptions) -> Option<u64> {
+    Some(cavern_map.with_options(options).lowest_risk_path()?.cost)
+}
 
-    let mut last_total_cost = i64::MAX;
-    while last_total_cost > cavern_map.total_cost() {
-        last_total_cost = cavern_map.total_cost();
-        cavern_map.propagate_costs();
-        println!("Total costs are now {}", cavern_map.total_cost());
+/// Generates a `size` by `size` map (at least 1 by 1) of risk levels
+/// from 1 to 9.
+pub fn generate_input(rng: &mut Rng, size: usize) -> String {
+    let size = size.max(1);
+    let mut cavern_map = String::new();
+    for _ in 0..size {
+        for _ in 0..size {
+            cavern_map.push_str(&rng.range(1..=9).to_string());
+        }
+        cavern_map.push('\n');
     }
     cavern_map
-        .min_cost_to_origin
-        .get(&cavern_map.get_bottom_right())
-        .unwrap()
-        .to_string()
-    //    "".to_string()
-}
-
-pub(crate) fn solve_part_2(puzzle_input: String) -> String {
-    let mut cavern_map = CavernMap::from(puzzle_input);
-    cavern_map.enlarge_cavern();
-    let minimum_passes = cavern_map.bottom_right.0 + cavern_map.bottom_right.1;
-    for _ in 1..=minimum_passes {
-        println!("Propagating costs.");
-        cavern_map.propagate_costs();
-    }
-    // println!(
-    //     "No minimum costs are i64::MAX. Largest is now {}",
-    //     cavern_map.max_cost()
-    // );
-    let mut last_total_cost = i64::MAX;
-    while last_total_cost > cavern_map.total_cost() {
-        last_total_cost = cavern_map.total_cost();
-        cavern_map.propagate_costs();
-        println!("Total costs are now {}", cavern_map.total_cost());
+}
+
+pub struct Day15;
+
+impl Solver for Day15 {
+    const DAY: u8 = 15;
+    type Input = CavernMap;
+
+    fn parse(&self, puzzle_input: String) -> Result<CavernMap, ParseError> {
+        CavernMap::from(puzzle_input)
+    }
+
+    fn solve_part_1(&self, cavern_map: &CavernMap) -> Result<Answer, Error> {
+        Ok(solve_part_1(cavern_map).into())
+    }
+
+    fn solve_part_2(&self, cavern_map: &CavernMap) -> Result<Answer, Error> {
+        Ok(solve_part_2(cavern_map).into())
+    }
+}
+
+#[cfg(test)]
+use super::test_helpers;
+
+#[test]
+fn test_part_1_sample() {
+    let cavern_map =
+        CavernMap::from(test_helpers::load_puzzle_input_variant(15, Some("sample"))).unwrap();
+    assert_eq!(solve_part_1(&cavern_map), 40);
+}
+
+#[test]
+fn test_part_2_sample() {
+    let cavern_map =
+        CavernMap::from(test_helpers::load_puzzle_input_variant(15, Some("sample"))).unwrap();
+    assert_eq!(solve_part_2(&cavern_map), 315);
+}
+
+#[test]
+fn test_part_1() {
+    let cavern_map = CavernMap::from(test_helpers::load_puzzle_input(15)).unwrap();
+    assert_eq!(solve_part_1(&cavern_map), 581);
+}
+
+#[test]
+fn test_part_2() {
+    let cavern_map = CavernMap::from(test_helpers::load_puzzle_input(15)).unwrap();
+    assert_eq!(solve_part_2(&cavern_map), 2916);
+}
+
+#[test]
+fn test_lowest_risk_path() {
+    let cavern_map =
+        CavernMap::from(test_helpers::load_puzzle_input_variant(15, Some("tiny"))).unwrap();
+    let cavern = cavern_map.with_options(CavernOptions::PART_1);
+    let path = cavern.lowest_risk_path().unwrap();
+    assert_eq!(
+        path.nodes,
+        vec![
+            (0, 0),
+            (1, 0),
+            (1, 1),
+            (1, 2),
+            (2, 2),
+            (3, 2),
+            (3, 3),
+            (3, 4),
+            (4, 4),
+            (5, 4),
+            (5, 5)
+        ]
+    );
+    assert_eq!(path.cost, 10);
+    let ppm = cavern.render_ppm(&path.nodes, 2);
+    assert!(ppm.starts_with(b"P6\n12 12\n255\n"));
+    assert_eq!(ppm.len(), "P6\n12 12\n255\n".len() + 12 * 12 * 3);
+    let svg = cavern.render_svg(&path.nodes, 10);
+    assert_eq!(svg.matches("<rect").count(), 36);
+    assert!(svg.contains("points=\"5,5 15,5 15,15 "));
+}
+
+#[test]
+fn test_cavern_options() {
+    let cavern_map =
+        CavernMap::from(test_helpers::load_puzzle_input_variant(15, Some("tiny"))).unwrap();
+    let diagonal = CavernOptions {
+        neighborhood: Neighborhood::Eight,
+        ..CavernOptions::PART_1
+    };
+    assert_eq!(lowest_total_risk(&cavern_map, diagonal), Some(5));
+    let knight = CavernOptions {
+        neighborhood: Neighborhood::Knight,
+        ..CavernOptions::PART_1
+    };
+    assert_eq!(lowest_total_risk(&cavern_map, knight), Some(14));
+
+    let cavern = cavern_map.with_options(CavernOptions {
+        tile_factor: 3,
+        wrap_modulus: 4,
+        neighborhood: Neighborhood::Four,
+    });
+    assert_eq!(cavern.get_bottom_right(), (17, 17));
+    assert_eq!(cavern.get_risk_level((0, 0)), Some(1));
+    assert_eq!(cavern.get_risk_level((6, 0)), Some(2));
+    assert_eq!(cavern.get_risk_level((12, 12)), Some(1));
+    assert_eq!(cavern.get_risk_level((18, 0)), None);
+}
+
+#[test]
+fn test_lowest_risk_path_with_wrapping_is_cheapest() {
+    let mut rng = Rng::new(0);
+    for _ in 0..20 {
+        let text_map = (0..8)
+            .map(|_| {
+                (0..8)
+                    .map(|_| rng.range(5..=9).to_string())
+                    .collect::<String>()
+                    + "\n"
+            })
+            .collect::<String>();
+        let cavern_map = CavernMap::from(text_map).unwrap();
+        for tile_factor in [1, 2] {
+            let cavern = cavern_map.with_options(CavernOptions {
+                tile_factor,
+                wrap_modulus: 5,
+                neighborhood: Neighborhood::Four,
+            });
+            let cheapest = pathfinding::dijkstra(
+                (0, 0),
+                |&location| {
+                    Neighborhood::Four
+                        .neighbors(location, cavern.width(), cavern.height())
+                        .map(|neighbor| (neighbor, cavern.get_risk_level(neighbor).unwrap().into()))
+                        .collect::<Vec<_>>()
+                },
+                |&location| location == cavern.get_bottom_right(),
+            )
+            .unwrap();
+            assert_eq!(cavern.lowest_risk_path().unwrap().cost, cheapest.cost);
+        }
     }
-    cavern_map
-        .min_cost_to_origin
-        .get(&cavern_map.get_bottom_right())
-        .unwrap()
-        .to_string()
-    //    "".to_string()
 }
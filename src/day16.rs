@@ -1,76 +1,149 @@
-#[derive(Debug)]
-struct Packet {
-    version: u8,
-    type_id: u8,
-    literal_value: Option<u64>,
-    length_type_id: Option<u8>,
-    sub_packets: Option<Vec<Packet>>,
-    length_in_bits: usize,
-}
-
-fn parse_packet(bits: &[u8]) -> Packet {
-    let version: u8 = bits_to_u8(&bits[0..=2]);
-    let type_id: u8 = bits_to_u8(&bits[3..=5]);
+use crate::answer::Answer;
+use crate::error::{Error, ParseContext, ParseError};
+use crate::generate::Rng;
+use crate::solver::Solver;
+
+/// A packet decoded from a BITS transmission.
+///
+/// `literal_value` is only present for literal value packets (type ID
+/// 4). `length_type_id` and `sub_packets` are only present for operator
+/// packets.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Packet {
+    pub version: u8,
+    pub type_id: u8,
+    pub literal_value: Option<u64>,
+    pub length_type_id: Option<u8>,
+    pub sub_packets: Option<Vec<Packet>>,
+    pub length_in_bits: usize,
+}
+
+impl Packet {
+    /// A literal value packet (type ID 4) holding `literal_value`,
+    /// written in as few groups of bits as possible.
+    pub fn literal(version: u8, literal_value: u64) -> Packet {
+        Packet {
+            version,
+            type_id: 4,
+            literal_value: Some(literal_value),
+            length_type_id: None,
+            sub_packets: None,
+            length_in_bits: 3 + 3 + 5 * literal_group_count(literal_value),
+        }
+    }
+
+    /// An operator packet. With length type ID 0 the length of the
+    /// sub-packets is given in bits, and with length type ID 1 the
+    /// number of sub-packets is given instead.
+    pub fn operator(
+        version: u8,
+        type_id: u8,
+        length_type_id: u8,
+        sub_packets: Vec<Packet>,
+    ) -> Packet {
+        let length_in_bits = sub_packets.iter().map(|p| p.length_in_bits).sum::<usize>()
+            + match length_type_id {
+                0 => 3 + 3 + 1 + 15,
+                _ => 3 + 3 + 1 + 11,
+            };
+        Packet {
+            version,
+            type_id,
+            literal_value: None,
+            length_type_id: Some(length_type_id),
+            sub_packets: Some(sub_packets),
+            length_in_bits,
+        }
+    }
+}
+
+/// Describes `packet` and its sub-packets, one per line, with each
+/// sub-packet indented under its parent.
+pub fn format_packet_tree(packet: &Packet) -> String {
+    let mut tree = String::new();
+    let mut stack = vec![(packet, 0)];
+    while let Some((packet, depth)) = stack.pop() {
+        let description = match packet.literal_value {
+            Some(literal_value) => format!("literal {}", literal_value),
+            None => String::from(match packet.type_id {
+                0 => "sum",
+                1 => "product",
+                2 => "minimum",
+                3 => "maximum",
+                5 => "greater than",
+                6 => "less than",
+                _ => "equal to",
+            }),
+        };
+        tree.push_str(&format!(
+            "{}v{} {} ({} bits)\n",
+            "  ".repeat(depth),
+            packet.version,
+            description,
+            packet.length_in_bits
+        ));
+        for sub_packet in packet.sub_packets.iter().flatten().rev() {
+            stack.push((sub_packet, depth + 1));
+        }
+    }
+    tree
+}
+
+/// Returns `None` if `bits` ends before the packet does.
+fn parse_packet(bits: &[u8]) -> Option<Packet> {
+    let version: u8 = bits_to_u8(bits.get(0..=2)?);
+    let type_id: u8 = bits_to_u8(bits.get(3..=5)?);
     let mut length_in_bits = 0;
     let mut literal_value = None;
     if type_id == 4 {
-        let literal_value_result = parse_literal_value(&bits[6..]);
+        let literal_value_result = parse_literal_value(&bits[6..])?;
         literal_value = Some(literal_value_result.0);
         length_in_bits = literal_value_result.1 + 3 + 3; // version and type ID are each 3 bits
     }
     let length_type_id = match type_id {
         4 => None,
-        _ => Some(bits[6]),
+        _ => Some(*bits.get(6)?),
     };
-    let sub_packets = match (type_id, length_type_id) {
-        (4, _) => None,
-        (_, Some(0)) => {
-            let subpackets_length_in_bits = bits_to_usize(&bits[7..22]);
+    let sub_packets = match length_type_id {
+        None => None,
+        Some(0) => {
+            let subpackets_length_in_bits = bits_to_usize(bits.get(7..22)?);
             let mut parsed_packets: Vec<Packet> = Vec::new();
             let mut running_length_total: usize = 0;
             while running_length_total < subpackets_length_in_bits {
-                let new_packet = parse_packet(&bits[(22 + running_length_total)..]);
+                let new_packet = parse_packet(bits.get((22 + running_length_total)..)?)?;
                 running_length_total += new_packet.length_in_bits;
                 parsed_packets.push(new_packet);
             }
             Some(parsed_packets)
         }
-        (_, Some(1)) => {
-            let number_of_subpackets: usize = bits_to_usize(&bits[7..18]);
+        Some(_) => {
+            let number_of_subpackets: usize = bits_to_usize(bits.get(7..18)?);
             let mut parsed_packets: Vec<Packet> = Vec::new();
             let mut running_length_total: usize = 0;
             while parsed_packets.len() < number_of_subpackets {
-                let new_packet = parse_packet(&bits[(18 + running_length_total)..]);
+                let new_packet = parse_packet(bits.get((18 + running_length_total)..)?)?;
                 running_length_total += new_packet.length_in_bits;
                 parsed_packets.push(new_packet);
             }
             Some(parsed_packets)
         }
-        (_, _) => {
-            panic!("Invalid combination of packet type ID and length type ID");
-        }
     };
-    if type_id != 4 {
-        length_in_bits = sub_packets
-            .as_ref()
-            .unwrap()
-            .iter()
-            .map(|p| p.length_in_bits)
-            .sum::<usize>()
+    if let Some(sub_packets) = &sub_packets {
+        length_in_bits = sub_packets.iter().map(|p| p.length_in_bits).sum::<usize>()
             + match length_type_id {
                 Some(0) => 3 + 3 + 1 + 15,
-                Some(1) => 3 + 3 + 1 + 11,
-                _ => panic!("Invalid length type ID"),
+                _ => 3 + 3 + 1 + 11,
             };
     }
-    Packet {
+    Some(Packet {
         version,
         type_id,
         literal_value,
         length_type_id,
         sub_packets,
         length_in_bits,
-    }
+    })
 }
 
 /// Parses a literal value as found in packet type ID 4.
@@ -78,107 +151,180 @@ fn parse_packet(bits: &[u8]) -> Packet {
 /// Returns (literal_value, length_in_bits) where literal_value is the
 /// value encoded by the bit string, and length_in_bits is the total
 /// number of bits used to represent the literal value including the
-/// "header" bits in every fifth position.
-fn parse_literal_value(bits: &[u8]) -> (u64, usize) {
+/// "header" bits in every fifth position. Returns `None` if `bits`
+/// ends before the last group does.
+fn parse_literal_value(bits: &[u8]) -> Option<(u64, usize)> {
     let mut literal_value: u64 = 0;
     let mut length_in_bits: usize = 0;
     for group in 0..=bits.len() / 5 {
         let group_start = group * 5;
         literal_value <<= 4;
-        literal_value += bits_to_u64(&bits[group_start + 1..=group_start + 4]);
+        literal_value += bits_to_u64(bits.get(group_start + 1..=group_start + 4)?);
         length_in_bits += 5;
         if bits[group_start] == 0 {
             break;
         }
     }
-    (literal_value, length_in_bits)
-}
-
-fn parse_puzzle_string_to_bits(puzzle_string: String) -> Vec<u8> {
-    puzzle_string
-        .trim()
-        .chars()
-        .map(|c| {
-            let nybble = c.to_digit(16).unwrap() as u8;
-            [
-                (nybble & 8) >> 3,
-                (nybble & 4) >> 2,
-                (nybble & 2) >> 1,
-                nybble & 1,
-            ]
-        })
-        .flatten()
-        .collect::<Vec<u8>>()
+    Some((literal_value, length_in_bits))
 }
 
-pub(crate) fn solve_part_1(puzzle_string: String) -> String {
-    let bits = parse_puzzle_string_to_bits(puzzle_string);
-    // println!("{:?}", bits);
-    let packet = parse_packet(&bits);
-    // println!("Packet: {:?}", packet);
-    let mut packets: Vec<Packet> = Vec::new();
-    packets.push(packet);
-    sum_packet_versions(&packets).to_string()
+/// The fewest groups of four bits that can hold `literal_value`.
+fn literal_group_count(literal_value: u64) -> usize {
+    (64 - literal_value.leading_zeros() as usize)
+        .max(1)
+        .div_ceil(4)
 }
 
-pub(crate) fn solve_part_2(puzzle_string: String) -> String {
-    let bits = parse_puzzle_string_to_bits(puzzle_string);
-    let packet = parse_packet(&bits);
-    evaluate_packet(&packet).to_string()
+/// Encodes `packet` as the bits of a BITS transmission, the inverse of
+/// parsing it. A literal value is written in as many groups as its
+/// `length_in_bits` says it was parsed from, so leading zero groups
+/// survive a round trip.
+///
+/// Returns `None` if a field does not fit in its bits, e.g. a version
+/// above 7 or too many sub-packets for the length type, or a packet
+/// has a literal value or sub-packets that its type ID does not allow.
+pub fn encode_packet(packet: &Packet) -> Option<Vec<u8>> {
+    let mut bits = Vec::new();
+    push_packet(&mut bits, packet)?;
+    Some(bits)
 }
 
-fn evaluate_packet(packet: &Packet) -> u64 {
-    if packet.type_id == 4 {
-        return packet.literal_value.unwrap();
+fn push_packet(bits: &mut Vec<u8>, packet: &Packet) -> Option<()> {
+    if packet.version > 7 || packet.type_id > 7 {
+        return None;
     }
-    let mut sub_packet_iterator = packet.sub_packets.as_ref().unwrap().iter();
-    match packet.type_id {
-        0 => sub_packet_iterator
-            .map(|sub_packet| evaluate_packet(sub_packet))
-            .sum(),
-        1 => sub_packet_iterator
-            .map(|sub_packet| evaluate_packet(sub_packet))
-            .product(),
-        2 => sub_packet_iterator
-            .map(|sub_packet| evaluate_packet(sub_packet))
-            .min()
-            .unwrap(),
-        3 => sub_packet_iterator
-            .map(|sub_packet| evaluate_packet(sub_packet))
-            .max()
-            .unwrap(),
-        5 => {
-            let first = sub_packet_iterator.next().unwrap();
-            let second = sub_packet_iterator.next().unwrap();
-            if evaluate_packet(first) > evaluate_packet(second) {
-                1
-            } else {
-                0
+    push_bits(bits, packet.version.into(), 3);
+    push_bits(bits, packet.type_id.into(), 3);
+    match (packet.type_id, packet.literal_value, &packet.sub_packets) {
+        (4, Some(literal_value), None) => {
+            let group_count = literal_group_count(literal_value)
+                .max(packet.length_in_bits.saturating_sub(3 + 3) / 5);
+            if group_count > 16 {
+                return None;
             }
-        }
-        6 => {
-            let first = sub_packet_iterator.next().unwrap();
-            let second = sub_packet_iterator.next().unwrap();
-            if evaluate_packet(first) < evaluate_packet(second) {
-                1
-            } else {
-                0
+            for group in (0..group_count).rev() {
+                push_bits(bits, (group > 0) as u64, 1);
+                push_bits(bits, literal_value.checked_shr(group as u32 * 4)?, 4);
             }
         }
-        7 => {
-            let first = sub_packet_iterator.next().unwrap();
-            let second = sub_packet_iterator.next().unwrap();
-            if evaluate_packet(first) == evaluate_packet(second) {
-                1
-            } else {
-                0
+        (4, _, _) | (_, Some(_), _) | (_, None, None) => return None,
+        (_, None, Some(sub_packets)) => {
+            let mut sub_packet_bits = Vec::new();
+            for sub_packet in sub_packets {
+                push_packet(&mut sub_packet_bits, sub_packet)?;
             }
+            match packet.length_type_id {
+                Some(0) if sub_packet_bits.len() < 1 << 15 => {
+                    push_bits(bits, 0, 1);
+                    push_bits(bits, sub_packet_bits.len() as u64, 15);
+                }
+                Some(1) if sub_packets.len() < 1 << 11 => {
+                    push_bits(bits, 1, 1);
+                    push_bits(bits, sub_packets.len() as u64, 11);
+                }
+                _ => return None,
+            }
+            bits.extend(sub_packet_bits);
         }
-        _ => panic!("Invalid packet type ID"),
+    }
+    Some(())
+}
+
+/// Encodes `packet` as a hexadecimal BITS transmission, padded with
+/// zero bits to a whole number of bytes like the puzzle's
+/// transmissions. Returns `None` if [`encode_packet`] does.
+pub fn encode_transmission(packet: &Packet) -> Option<String> {
+    let mut bits = encode_packet(packet)?;
+    while bits.len() % 8 != 0 {
+        bits.push(0);
+    }
+    Some(
+        bits.chunks(4)
+            .map(|nibble| format!("{:X}", bits_to_u8(nibble)))
+            .collect(),
+    )
+}
+
+fn parse_puzzle_string_to_bits(
+    context: &ParseContext,
+    transmission: &str,
+) -> Result<Vec<u8>, ParseError> {
+    let mut bits = Vec::with_capacity(transmission.len() * 4);
+    for (i, c) in transmission.char_indices() {
+        let nybble = match c.to_digit(16) {
+            Some(nybble) => nybble as u8,
+            None => {
+                return Err(context.error(&transmission[i..i + c.len_utf8()], "a hexadecimal digit"))
+            }
+        };
+        bits.extend([
+            (nybble & 8) >> 3,
+            (nybble & 4) >> 2,
+            (nybble & 2) >> 1,
+            nybble & 1,
+        ]);
+    }
+    Ok(bits)
+}
+
+pub fn parse_transmission(puzzle_string: String) -> Result<Packet, ParseError> {
+    let context = ParseContext::new(16, &puzzle_string);
+    let transmission = puzzle_string.trim();
+    let bits = parse_puzzle_string_to_bits(&context, transmission)?;
+    parse_packet(&bits).ok_or_else(|| context.error(transmission, "a complete BITS transmission"))
+}
+
+pub fn solve_part_1(packet: &Packet) -> u64 {
+    crate::log!(Trace, 16, "packet: {:?}", packet);
+    sum_packet_versions(std::slice::from_ref(packet))
+}
+
+/// Returns `None` if some operator packet has the wrong number of
+/// sub-packets.
+pub fn solve_part_2(packet: &Packet) -> Option<u64> {
+    evaluate_packet(packet)
+}
+
+fn evaluate_packet(packet: &Packet) -> Option<u64> {
+    if packet.type_id == 4 {
+        return packet.literal_value;
+    }
+    let mut sub_packet_iterator = packet.sub_packets.as_ref()?.iter();
+    let comparison = |first: &Packet, second: &Packet, compare: fn(&u64, &u64) -> bool| {
+        Some(compare(&evaluate_packet(first)?, &evaluate_packet(second)?) as u64)
+    };
+    match packet.type_id {
+        0 => sub_packet_iterator.map(evaluate_packet).sum(),
+        1 => sub_packet_iterator.map(evaluate_packet).product(),
+        2 => sub_packet_iterator
+            .map(evaluate_packet)
+            .collect::<Option<Vec<u64>>>()?
+            .into_iter()
+            .min(),
+        3 => sub_packet_iterator
+            .map(evaluate_packet)
+            .collect::<Option<Vec<u64>>>()?
+            .into_iter()
+            .max(),
+        5 => comparison(
+            sub_packet_iterator.next()?,
+            sub_packet_iterator.next()?,
+            u64::gt,
+        ),
+        6 => comparison(
+            sub_packet_iterator.next()?,
+            sub_packet_iterator.next()?,
+            u64::lt,
+        ),
+        _ => comparison(
+            sub_packet_iterator.next()?,
+            sub_packet_iterator.next()?,
+            u64::eq,
+        ),
     }
 }
 
-fn sum_packet_versions(packets: &Vec<Packet>) -> u64 {
+fn sum_packet_versions(packets: &[Packet]) -> u64 {
     packets
         .iter()
         .map(|packet| match &packet.sub_packets {
@@ -221,18 +367,178 @@ fn bits_to_usize(bits: &[u8]) -> usize {
     result
 }
 
+/// Generates a transmission of `size` packets nested at random, whose
+/// value fits in a `u32`.
+pub fn generate_input(rng: &mut Rng, size: usize) -> String {
+    let packet = generate_packet(rng, size.max(1), u32::MAX as u64);
+    format!(
+        "{}\n",
+        encode_transmission(&packet).expect("Generated packets can be encoded")
+    )
+}
+
+/// A packet made of `packet_count` packets in all, whose value is at
+/// most `max_value`.
+fn generate_packet(rng: &mut Rng, packet_count: usize, max_value: u64) -> Packet {
+    let version = rng.below(8) as u8;
+    if packet_count == 1 {
+        return Packet::literal(version, rng.below(max_value + 1));
+    }
+    let sub_packet_count = packet_count - 1;
+    // Comparisons need exactly two sub-packets.
+    let type_id = loop {
+        let type_id = rng.below(8) as u8;
+        if type_id != 4 && (type_id < 5 || sub_packet_count >= 2) {
+            break type_id;
+        }
+    };
+    let child_count = match type_id {
+        5..=7 => 2,
+        _ => rng.index(sub_packet_count.min(5)) + 1,
+    };
+    let mut child_sizes = vec![1; child_count];
+    for _ in child_count..sub_packet_count {
+        child_sizes[rng.index(child_count)] += 1;
+    }
+    let child_max_value = match type_id {
+        0 => max_value / child_count as u64,
+        1 => {
+            let mut root = (max_value as f64).powf(1.0 / child_count as f64) as u64;
+            while root
+                .checked_pow(child_count as u32)
+                .is_none_or(|power| power > max_value)
+            {
+                root -= 1;
+            }
+            root
+        }
+        _ => max_value,
+    };
+    let sub_packets = child_sizes
+        .into_iter()
+        .map(|child_size| generate_packet(rng, child_size, child_max_value))
+        .collect::<Vec<Packet>>();
+    let sub_packet_bits = sub_packets.iter().map(|p| p.length_in_bits).sum::<usize>();
+    let length_type_id = match sub_packet_bits < 1 << 15 && rng.chance(0.5) {
+        true => 0,
+        false => 1,
+    };
+    Packet::operator(version, type_id, length_type_id, sub_packets)
+}
+
+/// Appends the lowest `width` bits of `value` to `bits`, most
+/// significant first.
+fn push_bits(bits: &mut Vec<u8>, value: u64, width: usize) {
+    for bit in (0..width).rev() {
+        bits.push((value >> bit & 1) as u8);
+    }
+}
+
+pub struct Day16;
+
+impl Solver for Day16 {
+    const DAY: u8 = 16;
+    type Input = Packet;
+
+    fn parse(&self, puzzle_string: String) -> Result<Packet, ParseError> {
+        parse_transmission(puzzle_string)
+    }
+
+    fn solve_part_1(&self, packet: &Packet) -> Result<Answer, Error> {
+        Ok(solve_part_1(packet).into())
+    }
+
+    fn solve_part_2(&self, packet: &Packet) -> Result<Answer, Error> {
+        solve_part_2(packet).map(Answer::from).ok_or_else(|| {
+            Error::unsolvable(
+                16,
+                2,
+                "an operator packet has the wrong number of sub-packets",
+            )
+        })
+    }
+}
+
 #[cfg(test)]
 use super::test_helpers;
 
 #[test]
 fn test_part_1() {
-    assert_eq!(solve_part_1(test_helpers::load_puzzle_input(16)), "860");
+    let packet = parse_transmission(test_helpers::load_puzzle_input(16)).unwrap();
+    assert_eq!(solve_part_1(&packet), 860);
 }
 
 #[test]
 fn test_part_2() {
+    let packet = parse_transmission(test_helpers::load_puzzle_input(16)).unwrap();
+    assert_eq!(solve_part_2(&packet), Some(470949537659));
+}
+
+#[test]
+fn test_part_2_rejects_invalid_sub_packets() {
+    // A "greater than" packet needs two sub-packets.
+    let invalid = Packet::operator(0, 5, 1, vec![Packet::literal(0, 1)]);
+    for type_id in [2, 3] {
+        let packet = Packet::operator(0, type_id, 1, vec![Packet::literal(0, 7), invalid.clone()]);
+        assert_eq!(solve_part_2(&packet), None);
+    }
+}
+
+#[cfg(test)]
+const EXAMPLE_TRANSMISSIONS: [&str; 15] = [
+    "D2FE28",
+    "38006F45291200",
+    "EE00D40C823060",
+    "8A004A801A8002F478",
+    "620080001611562C8802118E34",
+    "C0015000016115A2E0802F182340",
+    "A0016C880162017C3686B18A3D4780",
+    "C200B40A82",
+    "04005AC33890",
+    "880086C3E88112",
+    "CE00C43D881120",
+    "D8005AC2A8F0",
+    "F600BC2D8F",
+    "9C005AC2F8F0",
+    "9C0141080250320F1802104A08",
+];
+
+#[test]
+fn test_encode_round_trip() {
+    let puzzle_input = test_helpers::load_puzzle_input(16);
+    for transmission in puzzle_input.lines().chain(EXAMPLE_TRANSMISSIONS) {
+        let packet = parse_transmission(transmission.to_string()).unwrap();
+        assert_eq!(
+            encode_transmission(&packet).as_deref(),
+            Some(transmission.trim())
+        );
+    }
+}
+
+#[test]
+fn test_encode_built_packets() {
+    let packet = Packet::operator(
+        7,
+        3,
+        1,
+        vec![
+            Packet::literal(2, 1),
+            Packet::literal(4, 2),
+            Packet::literal(1, 3),
+        ],
+    );
+    assert_eq!(encode_transmission(&packet).unwrap(), "EE00D40C823060");
+    let packet = Packet::operator(
+        1,
+        6,
+        0,
+        vec![Packet::literal(6, 10), Packet::literal(2, 20)],
+    );
+    assert_eq!(encode_transmission(&packet).unwrap(), "38006F45291200");
     assert_eq!(
-        solve_part_2(test_helpers::load_puzzle_input(16)),
-        "470949537659"
+        parse_transmission("38006F45291200".to_string()).unwrap(),
+        packet
     );
+    assert_eq!(encode_packet(&Packet::literal(8, 1)), None);
+    assert_eq!(encode_packet(&Packet::operator(0, 0, 2, vec![])), None);
 }
@@ -1,12 +1,59 @@
-pub(crate) fn solve_part_1(puzzle_input: String) -> String {
-    let (_, _, y_target_min, _) = parse_puzzle_input(puzzle_input);
-    let initial_y_velocity = (y_target_min + 1) * -1;
-    (initial_y_velocity * (initial_y_velocity + 1) / 2).to_string()
+use crate::answer::Answer;
+use crate::error::{Error, ParseContext, ParseError};
+use crate::generate::Rng;
+use crate::solver::Solver;
+
+/// The target area as `(x_target_min, x_target_max, y_target_min,
+/// y_target_max)`.
+pub type TargetArea = (i64, i64, i64, i64);
+
+/// Assumes the probe's highest launch lands on the bottom row of the
+/// target area, which holds when some x velocity leaves the probe
+/// stopped inside it. Otherwise falls back to checking every y velocity
+/// against the steps each x velocity spends over the target area.
+pub fn solve_part_1(target_area: &TargetArea) -> i64 {
+    let x_step_windows = get_x_step_windows(target_area);
+    if x_step_windows
+        .iter()
+        .any(|(_, last_step)| last_step.is_none())
+    {
+        let initial_y_velocity = -(target_area.2 + 1);
+        return initial_y_velocity * (initial_y_velocity + 1) / 2;
+    }
+    get_y_step_windows(target_area)
+        .iter()
+        .filter(|(_, y_step_window)| {
+            x_step_windows
+                .iter()
+                .any(|x_step_window| step_windows_overlap(*x_step_window, *y_step_window))
+        })
+        .map(|(initial_y_velocity, _)| {
+            // Probes launched level or downwards never rise.
+            let initial_y_velocity = initial_y_velocity.max(&0);
+            initial_y_velocity * (initial_y_velocity + 1) / 2
+        })
+        .max()
+        .unwrap_or(0)
 }
 
-pub(crate) fn solve_part_2(puzzle_input: String) -> String {
-    let (x_target_min, x_target_max, y_target_min, y_target_max) = parse_puzzle_input(puzzle_input);
+pub fn solve_part_2(target_area: &TargetArea) -> u64 {
+    let x_step_windows = get_x_step_windows(target_area);
+    let y_step_windows = get_y_step_windows(target_area);
+    let mut valid_initial_velocity_combinations: u64 = 0;
+    for x_step_window in x_step_windows.iter() {
+        for (_, y_step_window) in y_step_windows.iter() {
+            if step_windows_overlap(*x_step_window, *y_step_window) {
+                valid_initial_velocity_combinations += 1;
+            }
+        }
+    }
+    valid_initial_velocity_combinations
+}
 
+/// The first and last steps at which each x velocity that reaches the
+/// target area's x range is within it. The last step is `None` for
+/// velocities that leave the probe stopped there.
+fn get_x_step_windows(&(x_target_min, x_target_max, _, _): &TargetArea) -> Vec<(u64, Option<u64>)> {
     let x_velocity_min: i64 =
         ((((x_target_min as f64) * 8.0 + 1.0).sqrt() - 1.0) / 2.0).ceil() as i64;
     // Expression is equivalent to v*(v+1)/2 = x_target_max solved for
@@ -15,16 +62,13 @@ pub(crate) fn solve_part_2(puzzle_input: String) -> String {
     let x_velocity_stop_in_range_max =
         ((((x_target_max as f64) * 8.0 + 1.0).sqrt() - 1.0) / 2.0).floor() as i64;
     let x_velocity_max = x_target_max;
-
-    let y_velocity_min = y_target_min;
-    let y_velocity_max = (y_target_min + 1) * -1;
-    println!(
-        "{} {} {} {} {}",
+    crate::log!(
+        Debug,
+        17,
+        "x velocity from {} to {} ({} to stop in range)",
         x_velocity_min,
-        x_velocity_stop_in_range_max,
         x_velocity_max,
-        y_velocity_min,
-        y_velocity_max
+        x_velocity_stop_in_range_max
     );
     let mut x_step_windows: Vec<(u64, Option<u64>)> = Vec::new();
     for x_velocity in x_velocity_min..=x_velocity_stop_in_range_max {
@@ -61,7 +105,22 @@ pub(crate) fn solve_part_2(puzzle_input: String) -> String {
             ));
         }
     }
-    let mut y_step_windows: Vec<(u64, u64)> = Vec::new();
+    x_step_windows
+}
+
+/// The first and last steps at which the probe is within the target
+/// area's y range, for each y velocity that gets it there.
+fn get_y_step_windows(&(_, _, y_target_min, y_target_max): &TargetArea) -> Vec<(i64, (u64, u64))> {
+    let y_velocity_min = y_target_min;
+    let y_velocity_max = -(y_target_min + 1);
+    crate::log!(
+        Debug,
+        17,
+        "y velocity from {} to {}",
+        y_velocity_min,
+        y_velocity_max
+    );
+    let mut y_step_windows: Vec<(i64, (u64, u64))> = Vec::new();
     for initial_y_velocity in y_velocity_min..=y_velocity_max {
         let mut y_position = 0;
         let mut current_y_velocity = initial_y_velocity;
@@ -80,45 +139,147 @@ pub(crate) fn solve_part_2(puzzle_input: String) -> String {
         let first_step_under_minimum = step;
         if first_step_equal_or_under_maximum != first_step_under_minimum {
             y_step_windows.push((
-                first_step_equal_or_under_maximum,
-                first_step_under_minimum - 1,
+                initial_y_velocity,
+                (
+                    first_step_equal_or_under_maximum,
+                    first_step_under_minimum - 1,
+                ),
             ));
         }
     }
-    let mut valid_initial_velocity_combinations: u64 = 0;
-    for x_step_window in x_step_windows.iter() {
-        for y_step_window in y_step_windows.iter() {
-            let first_valid_x_step = x_step_window.0;
-            let first_valid_y_step = y_step_window.0;
-            let last_valid_y_step = y_step_window.1;
-            match x_step_window.1 {
-                None => {
-                    if first_valid_x_step <= last_valid_y_step {
-                        valid_initial_velocity_combinations += 1;
-                    }
-                }
-                Some(last_valid_x_step) => {
-                    if first_valid_x_step <= last_valid_y_step
-                        && last_valid_x_step >= first_valid_y_step
-                    {
-                        valid_initial_velocity_combinations += 1;
-                    }
+    y_step_windows
+}
+
+/// Whether the probe is within the target area's x range and its y
+/// range at the same step.
+fn step_windows_overlap(
+    (first_valid_x_step, last_valid_x_step): (u64, Option<u64>),
+    (first_valid_y_step, last_valid_y_step): (u64, u64),
+) -> bool {
+    first_valid_x_step <= last_valid_y_step
+        && last_valid_x_step.is_none_or(|last_valid_x_step| last_valid_x_step >= first_valid_y_step)
+}
+
+/// Parses the target area, which must lie to the right of and below
+/// the launch point.
+pub fn parse_puzzle_input(puzzle_input: String) -> Result<TargetArea, ParseError> {
+    let context = ParseContext::new(17, &puzzle_input);
+    let line = puzzle_input.trim();
+    let ranges_text = line
+        .strip_prefix("target area: x=")
+        .ok_or_else(|| context.error(line, "\"target area: x=\" followed by a range"))?;
+    let mut ranges = ranges_text.split(", y=");
+    let x_range_text = context.next(&mut ranges, ranges_text, "an x range")?;
+    let y_range_text = context.next(&mut ranges, x_range_text, "\", y=\" followed by a range")?;
+    let parse_range = |range_text: &str, expected: &str| -> Result<(i64, i64), ParseError> {
+        let mut range = range_text.split("..");
+        let min_text = context.next(&mut range, range_text, expected)?;
+        let max_text = context.next(&mut range, min_text, "\"..\" followed by an integer")?;
+        let min = context.parse::<i64>(min_text, "an integer")?;
+        let max = context.parse::<i64>(max_text, "an integer")?;
+        if min > max {
+            return Err(context.error(range_text, "a range from low to high"));
+        }
+        Ok((min, max))
+    };
+    let (x_target_min, x_target_max) = parse_range(x_range_text, "an x range")?;
+    if x_target_min <= 0 {
+        return Err(context.error(x_range_text, "a range of positive x positions"));
+    }
+    let (y_target_min, y_target_max) = parse_range(y_range_text, "a y range")?;
+    if y_target_max >= 0 {
+        return Err(context.error(y_range_text, "a range of negative y positions"));
+    }
+    Ok((x_target_min, x_target_max, y_target_min, y_target_max))
+}
+
+/// Launches the probe at every velocity that could reach the target
+/// area and returns the highest point of each launch that hits it.
+fn simulate_launches(
+    &(x_target_min, x_target_max, y_target_min, y_target_max): &TargetArea,
+) -> Vec<i64> {
+    let mut highest_points = Vec::new();
+    for initial_x_velocity in 1..=x_target_max {
+        for initial_y_velocity in y_target_min..=-y_target_min {
+            let (mut x, mut y) = (0, 0);
+            let (mut x_velocity, mut y_velocity) = (initial_x_velocity, initial_y_velocity);
+            let mut highest_point = 0;
+            while x <= x_target_max && y >= y_target_min {
+                if x >= x_target_min && y <= y_target_max {
+                    highest_points.push(highest_point);
+                    break;
                 }
+                x += x_velocity;
+                y += y_velocity;
+                highest_point = highest_point.max(y);
+                x_velocity -= x_velocity.signum();
+                y_velocity -= 1;
             }
         }
     }
-    valid_initial_velocity_combinations.to_string()
+    highest_points
+}
+
+/// Solves part 1 by simulating every launch, without assuming that some
+/// x velocity leaves the probe stopped in the target area. For
+/// cross-checking [`solve_part_1`]. Returns `None` if nothing hits.
+pub fn reference_part_1(target_area: &TargetArea) -> Option<i64> {
+    simulate_launches(target_area).into_iter().max()
+}
+
+/// Solves part 2 by simulating every launch. For cross-checking
+/// [`solve_part_2`].
+pub fn reference_part_2(target_area: &TargetArea) -> u64 {
+    simulate_launches(target_area).len() as u64
+}
+
+/// Generates a target area roughly `size` away from the launch point.
+/// Usually some x velocity leaves the probe stopped inside it, as in
+/// every real puzzle input. About one in three target areas instead lie
+/// between the positions where two x velocities stop, so that solving
+/// them cannot rely on the probe stopping.
+pub fn generate_input(rng: &mut Rng, size: usize) -> String {
+    let size = size.max(10) as i64;
+    let stopping_velocity =
+        rng.range((size as f64).sqrt() as i64..=((2 * size) as f64).sqrt() as i64);
+    let stopping_position = stopping_velocity * (stopping_velocity + 1) / 2;
+    let (x_target_min, x_target_max) = match rng.below(3) {
+        // The next velocity stops at stopping_position + stopping_velocity + 1.
+        0 => {
+            let x_target_min = stopping_position + 1 + rng.range(0..=stopping_velocity / 2);
+            let x_target_max = rng.range(x_target_min..=stopping_position + stopping_velocity);
+            (x_target_min, x_target_max)
+        }
+        _ => (
+            (stopping_position - rng.range(0..=size / 4)).max(1),
+            stopping_position + rng.range(0..=size / 4),
+        ),
+    };
+    let y_target_max = -rng.range(size / 2..=size);
+    let y_target_min = y_target_max - rng.range(size / 5..=size / 2);
+    format!(
+        "target area: x={}..{}, y={}..{}\n",
+        x_target_min, x_target_max, y_target_min, y_target_max
+    )
 }
 
-fn parse_puzzle_input(puzzle_input: String) -> (i64, i64, i64, i64) {
-    let mut ranges = puzzle_input.trim()[15..].split(", y=");
-    let mut x_range = ranges.next().unwrap().split("..");
-    let x_target_min = x_range.next().unwrap().parse::<i64>().unwrap();
-    let x_target_max = x_range.next().unwrap().parse::<i64>().unwrap();
-    let mut y_range = ranges.next().unwrap().split("..");
-    let y_target_min = y_range.next().unwrap().parse::<i64>().unwrap();
-    let y_target_max = y_range.next().unwrap().parse::<i64>().unwrap();
-    (x_target_min, x_target_max, y_target_min, y_target_max)
+pub struct Day17;
+
+impl Solver for Day17 {
+    const DAY: u8 = 17;
+    type Input = TargetArea;
+
+    fn parse(&self, puzzle_input: String) -> Result<TargetArea, ParseError> {
+        parse_puzzle_input(puzzle_input)
+    }
+
+    fn solve_part_1(&self, target_area: &TargetArea) -> Result<Answer, Error> {
+        Ok(solve_part_1(target_area).into())
+    }
+
+    fn solve_part_2(&self, target_area: &TargetArea) -> Result<Answer, Error> {
+        Ok(solve_part_2(target_area).into())
+    }
 }
 
 #[cfg(test)]
@@ -126,10 +287,28 @@ use super::test_helpers;
 
 #[test]
 fn test_part_1() {
-    assert_eq!(solve_part_1(test_helpers::load_puzzle_input(17)), "11781");
+    let target_area = parse_puzzle_input(test_helpers::load_puzzle_input(17)).unwrap();
+    assert_eq!(solve_part_1(&target_area), 11781);
 }
 
 #[test]
 fn test_part_2() {
-    assert_eq!(solve_part_2(test_helpers::load_puzzle_input(17)), "4531");
+    let target_area = parse_puzzle_input(test_helpers::load_puzzle_input(17)).unwrap();
+    assert_eq!(solve_part_2(&target_area), 4531);
+}
+
+#[test]
+fn test_part_1_without_stopping_x_velocity() {
+    let puzzle_input = crate::generate::generate(17, 7, None).unwrap();
+    let target_area = parse_puzzle_input(puzzle_input).unwrap();
+    assert!(get_x_step_windows(&target_area)
+        .iter()
+        .all(|(_, last_step)| last_step.is_some()));
+    assert_eq!(
+        Some(solve_part_1(&target_area)),
+        reference_part_1(&target_area)
+    );
+    let target_area = (8, 9, -12, -8);
+    assert_eq!(solve_part_1(&target_area), 0);
+    assert_eq!(solve_part_2(&target_area), reference_part_2(&target_area));
 }
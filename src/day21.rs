@@ -1,6 +1,18 @@
-pub(crate) fn solve_part_1(puzzle_input: String) -> String {
-    let (player_1_start, player_2_start) = get_starting_positions(puzzle_input);
-    // println!("{} {}", player_1_start, player_2_start);
+use std::collections::HashMap;
+
+use crate::answer::Answer;
+use crate::error::{Error, ParseContext, ParseError};
+use crate::generate::Rng;
+use crate::solver::Solver;
+
+pub fn solve_part_1(&(player_1_start, player_2_start): &(u32, u32)) -> u32 {
+    crate::log!(
+        Trace,
+        21,
+        "starting positions: {} and {}",
+        player_1_start,
+        player_2_start
+    );
 
     // Player 1 landed squares will have period 5: +6, +4, +2, +0, +8 (= +20)
     // Player 2 landed squares will have period 10: +5, +3, +1, +9, +7, +5, +3, +1, +9, +7 (= +50)
@@ -55,30 +67,48 @@ pub(crate) fn solve_part_1(puzzle_input: String) -> String {
             loser_score * die_roll_count
         }
     }
-    .to_string()
 }
 
-pub(crate) fn solve_part_2(puzzle_input: String) -> String {
-    let (player_1_start, player_2_start) = get_starting_positions(puzzle_input);
+pub fn solve_part_2(&(player_1_start, player_2_start): &(u32, u32)) -> u64 {
     // let mut memoization_cache: HashMap<(u32, u32, u32, u32), (u64, u64)> = HashMap::new();
     let mut memoization_cache: Vec<Option<(u64, u64)>> = vec![None; 2_usize.pow(20)];
     let (player_1_win_count, player_2_win_count) =
         calculate_win_counts(player_1_start, 0, player_2_start, 0, &mut memoization_cache);
-    //println!("{} {}", player_1_win_count, player_2_win_count);
+    crate::log!(
+        Debug,
+        21,
+        "player 1 wins in {} universes, player 2 in {}",
+        player_1_win_count,
+        player_2_win_count
+    );
     match player_1_win_count > player_2_win_count {
         true => player_1_win_count,
         false => player_2_win_count,
     }
-    .to_string()
 }
 
-fn get_starting_positions(puzzle_input: String) -> (u32, u32) {
-    let mut start_iter = puzzle_input.trim().split("\n").map(|line| {
-        let mut line_iter = line.split("starting position: ");
-        line_iter.next();
-        line_iter.next().unwrap().parse::<u32>().unwrap()
-    });
-    (start_iter.next().unwrap(), start_iter.next().unwrap())
+pub fn get_starting_positions(puzzle_input: String) -> Result<(u32, u32), ParseError> {
+    let context = ParseContext::new(21, &puzzle_input);
+    let trimmed_input = puzzle_input.trim();
+    let mut line_iter = trimmed_input.split("\n");
+    let mut parse_starting_position = |player: u32, previous_text: &str| {
+        let line = context.next(
+            &mut line_iter,
+            previous_text,
+            &format!("a line for player {}", player),
+        )?;
+        let prefix = format!("Player {} starting position: ", player);
+        let position_text = line
+            .strip_prefix(prefix.as_str())
+            .ok_or_else(|| context.error(line, &format!("{:?} followed by a position", prefix)))?;
+        match context.parse::<u32>(position_text, "a position from 1 to 10")? {
+            position @ 1..=10 => Ok((position, line)),
+            _ => Err(context.error(position_text, "a position from 1 to 10")),
+        }
+    };
+    let (player_1_start, player_1_line) = parse_starting_position(1, trimmed_input)?;
+    let (player_2_start, _) = parse_starting_position(2, player_1_line)?;
+    Ok((player_1_start, player_2_start))
 }
 
 fn get_score_after_round(player_score_loop: &[u32], round: u32) -> u32 {
@@ -92,12 +122,12 @@ fn get_score_after_round(player_score_loop: &[u32], round: u32) -> u32 {
 }
 
 fn first_round_with_score_at_least_1000(player_score_loop: &[u32]) -> u32 {
-    let mut round = 1000 / player_score_loop.iter().sum::<u32>() * player_score_loop.len() as u32;
-    for _ in 0..player_score_loop.len() {
+    let first_round_to_check =
+        1000 / player_score_loop.iter().sum::<u32>() * player_score_loop.len() as u32;
+    for round in first_round_to_check..first_round_to_check + player_score_loop.len() as u32 {
         if get_score_after_round(player_score_loop, round) >= 1000 {
             return round;
         }
-        round += 1;
     }
     panic!("Should have found a round inside the for loop");
 }
@@ -119,7 +149,15 @@ fn calculate_win_counts(
         None => (),
         Some(result) => return result,
     }
-    // println!("Calculating win counts.\nPlayer 1 position: {}\nPlayer 1 current score: {}\nPlayer 2 position: {}\nPlayer 2 current score: {}\n", player_1_position, player_1_current_score, player_2_position, player_2_current_score);
+    crate::log!(
+        Trace,
+        21,
+        "calculating win counts: player 1 at {} with {} points, player 2 at {} with {} points",
+        player_1_position,
+        player_1_current_score,
+        player_2_position,
+        player_2_current_score
+    );
     let dirac_die_result_sets = [
         [1, 1, 1],
         [1, 1, 2],
@@ -165,10 +203,6 @@ fn calculate_win_counts(
         let new_player_1_position = new_player_1_position;
         let new_player_1_score = new_player_1_score;
         if new_player_1_score >= 21 {
-            // println!(
-            //     "Player 1 won with Dirac die results set {:?}\n",
-            //     player_1_dirac_die_result_set
-            // );
             player_1_win_count += 1;
             continue;
         }
@@ -187,15 +221,10 @@ fn calculate_win_counts(
             let new_player_2_position = new_player_2_position;
             let new_player_2_score = new_player_2_score;
             if new_player_2_score >= 21 {
-                // println!(
-                //     "Player 2 won with Dirac die results set {:?}\n",
-                //     player_2_dirac_die_result_set
-                // );
                 player_2_win_count += 1;
                 continue;
             }
             // This set of die results does not let player 2 win this round either.
-            // println!("Neither player won this round.\nPlayer 1 Dirac die result set: {:?}, Player 2 Dirac die result set: {:?}\n", player_1_dirac_die_result_set, player_2_dirac_die_result_set);
             let (next_round_player_1_win_count, next_round_player_2_win_count) =
                 calculate_win_counts(
                     new_player_1_position,
@@ -212,18 +241,102 @@ fn calculate_win_counts(
     (player_1_win_count, player_2_win_count)
 }
 
+/// Solves part 1 by playing the game roll by roll. For cross-checking
+/// [`solve_part_1`].
+pub fn reference_part_1(&(player_1_start, player_2_start): &(u32, u32)) -> u32 {
+    let mut positions = [player_1_start, player_2_start];
+    let mut scores = [0, 0];
+    let mut last_roll = 0;
+    let mut roll_count = 0;
+    for player in [0, 1].iter().cycle() {
+        let mut moves = 0;
+        for _ in 0..3 {
+            last_roll = last_roll % 100 + 1;
+            moves += last_roll;
+        }
+        roll_count += 3;
+        positions[*player] = (positions[*player] + moves - 1) % 10 + 1;
+        scores[*player] += positions[*player];
+        if scores[*player] >= 1000 {
+            return scores[1 - player] * roll_count;
+        }
+    }
+    unreachable!()
+}
+
+/// Solves part 2 by counting the universes in every game state turn by
+/// turn, rather than recursing with a cache. For cross-checking
+/// [`solve_part_2`].
+pub fn reference_part_2(&(player_1_start, player_2_start): &(u32, u32)) -> u64 {
+    // The sums of three rolls of the Dirac die, and how many ways each
+    // can come up.
+    const ROLL_SUMS: [(u32, u64); 7] = [(3, 1), (4, 3), (5, 6), (6, 7), (7, 6), (8, 3), (9, 1)];
+    // (positions, scores) of both players -> number of universes
+    let mut universes = HashMap::from([(([player_1_start, player_2_start], [0, 0]), 1_u64)]);
+    let mut wins = [0_u64, 0];
+    for player in [0, 1].iter().cycle() {
+        if universes.is_empty() {
+            break;
+        }
+        let mut next_universes = HashMap::new();
+        for ((positions, scores), count) in universes {
+            for (roll_sum, ways) in ROLL_SUMS {
+                let (mut positions, mut scores) = (positions, scores);
+                positions[*player] = (positions[*player] + roll_sum - 1) % 10 + 1;
+                scores[*player] += positions[*player];
+                match scores[*player] >= 21 {
+                    true => wins[*player] += count * ways,
+                    false => {
+                        *next_universes.entry((positions, scores)).or_insert(0) += count * ways
+                    }
+                }
+            }
+        }
+        universes = next_universes;
+    }
+    wins[0].max(wins[1])
+}
+
+/// Generates random starting positions. There is nothing to scale, so
+/// `size` is ignored.
+pub fn generate_input(rng: &mut Rng, _size: usize) -> String {
+    format!(
+        "Player 1 starting position: {}\nPlayer 2 starting position: {}\n",
+        rng.range(1..=10),
+        rng.range(1..=10)
+    )
+}
+
+pub struct Day21;
+
+impl Solver for Day21 {
+    const DAY: u8 = 21;
+    type Input = (u32, u32);
+
+    fn parse(&self, puzzle_input: String) -> Result<(u32, u32), ParseError> {
+        get_starting_positions(puzzle_input)
+    }
+
+    fn solve_part_1(&self, starting_positions: &(u32, u32)) -> Result<Answer, Error> {
+        Ok(solve_part_1(starting_positions).into())
+    }
+
+    fn solve_part_2(&self, starting_positions: &(u32, u32)) -> Result<Answer, Error> {
+        Ok(solve_part_2(starting_positions).into())
+    }
+}
+
 #[cfg(test)]
 use super::test_helpers;
 
 #[test]
 fn test_part_1() {
-    assert_eq!(solve_part_1(test_helpers::load_puzzle_input(21)), "711480");
+    let starting_positions = get_starting_positions(test_helpers::load_puzzle_input(21)).unwrap();
+    assert_eq!(solve_part_1(&starting_positions), 711480);
 }
 
 #[test]
 fn test_part_2() {
-    assert_eq!(
-        solve_part_2(test_helpers::load_puzzle_input(21)),
-        "265845890886828"
-    );
+    let starting_positions = get_starting_positions(test_helpers::load_puzzle_input(21)).unwrap();
+    assert_eq!(solve_part_2(&starting_positions), 265845890886828);
 }
@@ -1,13 +1,19 @@
 use std::collections::HashSet;
 use std::ops::Range;
 
-pub(crate) fn solve_part_1(puzzle_input: String) -> String {
-    let reboot_steps = parse_reboot_steps(&puzzle_input);
-    // for reboot_step in reboot_steps {
-    //     println!("{:?}", reboot_step);
-    // }
+use crate::answer::Answer;
+use crate::cancel;
+use crate::error::{Error, ParseContext, ParseError};
+use crate::generate::Rng;
+use crate::solver::Solver;
+
+pub fn solve_part_1(reboot_steps: &[RebootStep]) -> usize {
+    for reboot_step in reboot_steps {
+        crate::log!(Trace, 22, "reboot step: {:?}", reboot_step);
+    }
     let mut on_cubes: HashSet<(i32, i32, i32)> = HashSet::new();
     for reboot_step in reboot_steps.iter() {
+        cancel::checkpoint();
         if (reboot_step.x_range.start < -50 && reboot_step.x_range.end <= -50)
             || (reboot_step.y_range.start < -50 && reboot_step.y_range.end <= -50)
             || (reboot_step.z_range.start < -50 && reboot_step.z_range.end <= -50)
@@ -18,15 +24,15 @@ pub(crate) fn solve_part_1(puzzle_input: String) -> String {
             continue;
         }
         for x in reboot_step.x_range.start..reboot_step.x_range.end {
-            if x < -50 || x > 50 {
+            if !(-50..=50).contains(&x) {
                 continue;
             }
             for y in reboot_step.y_range.start..reboot_step.y_range.end {
-                if y < -50 || y > 50 {
+                if !(-50..=50).contains(&y) {
                     continue;
                 }
                 for z in reboot_step.z_range.start..reboot_step.z_range.end {
-                    if z < -50 || z > 50 {
+                    if !(-50..=50).contains(&z) {
                         continue;
                     }
                     if reboot_step.turn_on {
@@ -38,54 +44,42 @@ pub(crate) fn solve_part_1(puzzle_input: String) -> String {
             }
         }
     }
-    on_cubes.len().to_string()
+    on_cubes.len()
 }
 
-pub(crate) fn solve_part_2(puzzle_input: String) -> String {
-    let mut reboot_steps = parse_reboot_steps(&puzzle_input);
-    // // steps that overlap the origin in the x coordinate
-    // for reboot_step in reboot_steps {
-    //     if reboot_step.x_range.start < 0 && reboot_step.x_range.end > 0 {
-    //         println!("{:?}", reboot_step);
-    //     }
-    // }
-
+pub fn solve_part_2(reboot_steps: &[RebootStep]) -> u64 {
+    let mut reboot_steps = reboot_steps.to_vec();
     // steps that are overlapped by subsequent steps
-    // let mut last_sum_of_all_on_cubes = sum_of_all_on_cubes(&reboot_steps);
-    // println!("Got reboot steps: {:?}", reboot_steps);
     loop {
+        crate::log!(
+            Debug,
+            22,
+            "looking for overlaps among {} reboot steps",
+            reboot_steps.len()
+        );
         let mut new_reboot_steps: Vec<RebootStep> = Vec::new();
         let mut found_any_overlaps = false;
         for (i, reboot_step) in reboot_steps.iter().enumerate() {
-            // println!(
-            //     "Comparing reboot step at index {} against subsequent steps",
-            //     i
-            // );
-            // println!("\nCurrent step is 'on' type: {}", reboot_step.turn_on);
-            // println!("Examining step: {:#?}", reboot_step);
+            cancel::checkpoint();
             let mut found_overlap = false;
             for subsequent_step in reboot_steps[(i + 1)..].iter() {
-                // println!(
-                //     "Considering subsequent step at offset {}: {:?}",
-                //     j + i + 1,
-                //     subsequent_step
-                // );
-                if reboot_step_cuboids_overlap(&reboot_step, &subsequent_step) {
+                if reboot_step_cuboids_overlap(reboot_step, subsequent_step) {
                     found_overlap = true;
                     found_any_overlaps = true;
-                    let mut difference = reboot_step_difference(&reboot_step, subsequent_step);
-                    // match difference.len() {
-                    //     0 | 3 => (),
-                    //     _ => println!("Got a potentially remarkable number of reboot steps."),
-                    // }
-                    // println!("It overlaps with subsequent step: {:#?}", subsequent_step);
-                    // println!("Reboot steps representing cubes present in this step but not the identified subsequent step: {:#?}\n", difference);
+                    let mut difference = reboot_step_difference(reboot_step, subsequent_step);
+                    crate::log!(
+                        Trace,
+                        22,
+                        "step {:?} overlaps {:?}, leaving {:?}",
+                        reboot_step,
+                        subsequent_step,
+                        difference
+                    );
                     new_reboot_steps.append(&mut difference);
                     break;
                 }
             }
             if !found_overlap {
-                // println!("No subsequent overlapping steps were found.\n");
                 new_reboot_steps.push(reboot_step.clone())
             }
         }
@@ -94,22 +88,19 @@ pub(crate) fn solve_part_2(puzzle_input: String) -> String {
             break;
         }
     }
-    sum_of_all_on_cubes(&reboot_steps).to_string()
+    sum_of_all_on_cubes(&reboot_steps)
 }
 
 fn reboot_step_cuboids_overlap(a: &RebootStep, b: &RebootStep) -> bool {
-    // println!("Determining whether cuboids overlap: {:?}, {:?}", a, b);
     ranges_overlap(&a.x_range, &b.x_range)
         && ranges_overlap(&a.y_range, &b.y_range)
         && ranges_overlap(&a.z_range, &b.z_range)
 }
 
 fn ranges_overlap(a: &Range<i32>, b: &Range<i32>) -> bool {
-    let result = (a.start >= b.start && a.start < b.end)
+    (a.start >= b.start && a.start < b.end)
         || (a.end > b.start && a.end <= b.end)
-        || (a.start <= b.start && a.end >= b.end);
-    // println!("Ranges {:?} and {:?} overlap: {}", a, b, result);
-    result
+        || (a.start <= b.start && a.end >= b.end)
 }
 
 /// Produces a vector of up to three reboot steps containing the cubes
@@ -211,7 +202,7 @@ fn reboot_step_difference(first: &RebootStep, second: &RebootStep) -> Vec<Reboot
 /// Returns a count of all cubes that are turned on in all reboot
 /// steps. Does not take into account cubes that may have been turned
 /// on twice, or cubes that may have been turned off and back on.
-fn sum_of_all_on_cubes(reboot_steps: &Vec<RebootStep>) -> u64 {
+fn sum_of_all_on_cubes(reboot_steps: &[RebootStep]) -> u64 {
     let mut result: u64 = 0;
     for reboot_step in reboot_steps.iter() {
         if !reboot_step.turn_on {
@@ -224,51 +215,176 @@ fn sum_of_all_on_cubes(reboot_steps: &Vec<RebootStep>) -> u64 {
     result
 }
 
+/// A single reboot step. Ranges are half open, unlike the closed
+/// ranges in the puzzle input.
 #[derive(Debug, Clone)]
-struct RebootStep {
-    turn_on: bool, // true when the step is "on", false otherwise
-    x_range: Range<i32>,
-    y_range: Range<i32>,
-    z_range: Range<i32>,
+pub struct RebootStep {
+    pub turn_on: bool, // true when the step is "on", false otherwise
+    pub x_range: Range<i32>,
+    pub y_range: Range<i32>,
+    pub z_range: Range<i32>,
 }
 
-fn parse_reboot_steps(puzzle_input: &str) -> Vec<RebootStep> {
+pub fn parse_reboot_steps(puzzle_input: &str) -> Result<Vec<RebootStep>, ParseError> {
+    let context = ParseContext::new(22, puzzle_input);
+    let parse_range = |range_text: &str, axis: &str| -> Result<Range<i32>, ParseError> {
+        let prefix = format!("{}=", axis);
+        let endpoints_text = range_text.strip_prefix(prefix.as_str()).ok_or_else(|| {
+            context.error(range_text, &format!("{:?} followed by a range", prefix))
+        })?;
+        let mut endpoint_iter = endpoints_text.split("..");
+        let first_text = context.next(&mut endpoint_iter, endpoints_text, "an integer")?;
+        let second_text = context.next(
+            &mut endpoint_iter,
+            first_text,
+            "\"..\" followed by an integer",
+        )?;
+        let first_endpoint = context.parse::<i32>(first_text, "an integer")?;
+        let second_endpoint = context.parse::<i32>(second_text, "an integer")?;
+        Ok(Range::<i32> {
+            start: match first_endpoint < second_endpoint {
+                true => first_endpoint,
+                false => second_endpoint,
+            },
+            end: match first_endpoint < second_endpoint {
+                true => second_endpoint + 1,
+                false => first_endpoint + 1,
+            }, // +1 because problem ranges are closed but Rust
+               // ranges are half open.
+        })
+    };
     puzzle_input
         .trim()
         .split("\n")
         .map(|line| {
             let mut line_iter = line.split(" ");
-            let turn_on = match line_iter.next().unwrap() {
+            let operation = context.next(&mut line_iter, line, "\"on\" or \"off\"")?;
+            let turn_on = match operation {
                 "on" => true,
                 "off" => false,
-                _ => panic!("Invalid operation on cuboid region"),
+                _ => return Err(context.error(operation, "\"on\" or \"off\"")),
             };
-            let mut range_iter = line_iter.next().unwrap().split(",").map(|range_text| {
-                let mut endpoint_iter = range_text
-                    .trim_matches(&['x', 'y', 'z', '='] as &[_])
-                    .split("..");
-                let first_endpoint = endpoint_iter.next().unwrap().parse::<i32>().unwrap();
-                let second_endpoint = endpoint_iter.next().unwrap().parse::<i32>().unwrap();
-                Range::<i32> {
-                    start: match first_endpoint < second_endpoint {
-                        true => first_endpoint,
-                        false => second_endpoint,
-                    },
-                    end: match first_endpoint < second_endpoint {
-                        true => second_endpoint + 1,
-                        false => first_endpoint + 1,
-                    }, // +1 because problem ranges are closed but Rust
-                       // ranges are half open.
-                }
-            });
-            RebootStep {
+            let cuboid_text =
+                context.next(&mut line_iter, operation, "\" \" followed by a cuboid")?;
+            let mut range_iter = cuboid_text.split(",");
+            let x_text = context.next(&mut range_iter, cuboid_text, "an x range")?;
+            let y_text = context.next(&mut range_iter, x_text, "\",y=\" followed by a range")?;
+            let z_text = context.next(&mut range_iter, y_text, "\",z=\" followed by a range")?;
+            Ok(RebootStep {
                 turn_on,
-                x_range: range_iter.next().unwrap(),
-                y_range: range_iter.next().unwrap(),
-                z_range: range_iter.next().unwrap(),
-            }
+                x_range: parse_range(x_text, "x")?,
+                y_range: parse_range(y_text, "y")?,
+                z_range: parse_range(z_text, "z")?,
+            })
         })
-        .collect::<Vec<RebootStep>>()
+        .collect::<Result<Vec<RebootStep>, ParseError>>()
+}
+
+/// Solves part 2 by splitting space into a grid at every cuboid edge
+/// and switching whole grid cells on and off, without working out how
+/// cuboids overlap. For cross-checking [`solve_part_2`] on small
+/// inputs: the grid has up to (2 × steps)³ cells.
+pub fn reference_part_2(reboot_steps: &[RebootStep]) -> u64 {
+    let edges = |range: fn(&RebootStep) -> &Range<i32>| {
+        let mut edges = reboot_steps
+            .iter()
+            .flat_map(|step| [range(step).start, range(step).end])
+            .collect::<Vec<i32>>();
+        edges.sort_unstable();
+        edges.dedup();
+        edges
+    };
+    let x_edges = edges(|step| &step.x_range);
+    let y_edges = edges(|step| &step.y_range);
+    let z_edges = edges(|step| &step.z_range);
+    let cells = |edges: &[i32], range: &Range<i32>| {
+        edges.binary_search(&range.start).unwrap()..edges.binary_search(&range.end).unwrap()
+    };
+    let mut on = vec![vec![vec![false; z_edges.len()]; y_edges.len()]; x_edges.len()];
+    for step in reboot_steps {
+        for x in cells(&x_edges, &step.x_range) {
+            for y in cells(&y_edges, &step.y_range) {
+                for z in cells(&z_edges, &step.z_range) {
+                    on[x][y][z] = step.turn_on;
+                }
+            }
+        }
+    }
+    let mut volume = 0;
+    for (x, plane) in on.iter().enumerate() {
+        for (y, row) in plane.iter().enumerate() {
+            for (z, cell) in row.iter().enumerate() {
+                if *cell {
+                    volume += (x_edges[x + 1] - x_edges[x]) as u64
+                        * (y_edges[y + 1] - y_edges[y]) as u64
+                        * (z_edges[z + 1] - z_edges[z]) as u64;
+                }
+            }
+        }
+    }
+    volume
+}
+
+/// Generates `size` reboot steps (at least one). Like a real puzzle input, the first
+/// few are small cuboids in the initialization procedure region and
+/// the rest are large cuboids outside it.
+pub fn generate_input(rng: &mut Rng, size: usize) -> String {
+    let size = size.max(1);
+    let initialization_steps = (size / 21).max(1);
+    let mut reboot_steps = String::new();
+    for step in 0..size {
+        let mut ranges = [(0, 0); 3];
+        if step < initialization_steps {
+            for range in ranges.iter_mut() {
+                let length = rng.range(10..=50);
+                let start = rng.range(-50..=50 - length);
+                *range = (start, start + length);
+            }
+        } else {
+            for range in ranges.iter_mut() {
+                let length = rng.range(10_000..=50_000);
+                let start = rng.range(-100_000..=100_000 - length);
+                *range = (start, start + length);
+            }
+            // Keep clear of the initialization procedure region along
+            // one axis.
+            let axis = rng.index(3);
+            let length = rng.range(10_000..=50_000);
+            let start = rng.range(51..=100_000 - length);
+            ranges[axis] = match rng.chance(0.5) {
+                true => (start, start + length),
+                false => (-start - length, -start),
+            };
+        }
+        let operation = match step == 0 || rng.chance(0.7) {
+            true => "on",
+            false => "off",
+        };
+        reboot_steps.push_str(&format!(
+            "{} x={}..{},y={}..{},z={}..{}\n",
+            operation, ranges[0].0, ranges[0].1, ranges[1].0, ranges[1].1, ranges[2].0, ranges[2].1
+        ));
+    }
+    reboot_steps
+}
+
+pub struct Day22;
+
+impl Solver for Day22 {
+    const DAY: u8 = 22;
+    type Input = Vec<RebootStep>;
+
+    fn parse(&self, puzzle_input: String) -> Result<Vec<RebootStep>, ParseError> {
+        parse_reboot_steps(&puzzle_input)
+    }
+
+    fn solve_part_1(&self, reboot_steps: &Vec<RebootStep>) -> Result<Answer, Error> {
+        Ok(solve_part_1(reboot_steps).into())
+    }
+
+    fn solve_part_2(&self, reboot_steps: &Vec<RebootStep>) -> Result<Answer, Error> {
+        Ok(solve_part_2(reboot_steps).into())
+    }
 }
 
 #[cfg(test)]
@@ -276,13 +392,23 @@ use super::test_helpers;
 
 #[test]
 fn test_part_1() {
-    assert_eq!(solve_part_1(test_helpers::load_puzzle_input(22)), "658691");
+    let reboot_steps = parse_reboot_steps(&test_helpers::load_puzzle_input(22)).unwrap();
+    assert_eq!(solve_part_1(&reboot_steps), 658691);
 }
 
 #[test]
 fn test_part_2() {
-    assert_eq!(
-        solve_part_2(test_helpers::load_puzzle_input(22)),
-        "1228699515783640"
-    );
+    let reboot_steps = parse_reboot_steps(&test_helpers::load_puzzle_input(22)).unwrap();
+    assert_eq!(solve_part_2(&reboot_steps), 1228699515783640);
+}
+
+#[test]
+fn test_parse_reboot_steps_reports_malformed_line() {
+    let error =
+        parse_reboot_steps("on x=1..2,y=3..4,z=5..6\ntoggle x=1..2,y=3..4,z=5..6\n").unwrap_err();
+    assert_eq!((error.line, error.column), (2, 1));
+    assert_eq!(error.text, "toggle");
+    let error = parse_reboot_steps("off x=1..2,y=3..4\n").unwrap_err();
+    assert_eq!((error.line, error.column), (1, 18));
+    assert_eq!(error.expected, "\",z=\" followed by a range");
 }
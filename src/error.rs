@@ -0,0 +1,200 @@
+use std::error;
+use std::fmt;
+use std::str::FromStr;
+
+/// Describes malformed puzzle input.
+///
+/// `line` and `column` are 1-based. `line_text` is the whole line the
+/// problem was found on, and `text` is the offending part of it (which
+/// is empty when something is missing from the end of the line).
+#[derive(Debug, Clone, PartialEq)]
+pub struct ParseError {
+    pub day: u8,
+    pub line: usize,
+    pub column: usize,
+    pub text: String,
+    pub line_text: String,
+    pub expected: String,
+}
+
+impl ParseError {
+    /// Renders the offending line with a caret under the offending
+    /// text, in the style of a compiler diagnostic.
+    pub fn snippet(&self) -> String {
+        let gutter = " ".repeat(self.line.to_string().len());
+        let underline = "^".repeat(self.text.chars().count().max(1));
+        format!(
+            "{} |\n{} | {}\n{} | {}{}",
+            gutter,
+            self.line,
+            self.line_text,
+            gutter,
+            " ".repeat(self.column - 1),
+            underline
+        )
+    }
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "day {} input, line {}, column {}: expected {}",
+            self.day, self.line, self.column, self.expected
+        )?;
+        match self.text.is_empty() {
+            true => write!(f, ", found end of line"),
+            false => write!(f, ", found {:?}", self.text),
+        }
+    }
+}
+
+impl error::Error for ParseError {}
+
+/// Anything that can go wrong while solving a puzzle.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Error {
+    /// The puzzle input is malformed.
+    Parse(ParseError),
+    /// The puzzle input is well formed but has no answer.
+    Unsolvable { day: u8, part: u8, reason: String },
+    /// There is no solver for this day and part.
+    NotImplemented { day: u8, part: u8 },
+}
+
+impl Error {
+    pub(crate) fn unsolvable(day: u8, part: u8, reason: &str) -> Self {
+        Error::Unsolvable {
+            day,
+            part,
+            reason: reason.to_string(),
+        }
+    }
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Error::Parse(parse_error) => write!(f, "{}", parse_error),
+            Error::Unsolvable { day, part, reason } => {
+                write!(f, "day {} part {} has no solution: {}", day, part, reason)
+            }
+            Error::NotImplemented { day, part } => {
+                write!(
+                    f,
+                    "the solution for day {} part {} is not implemented",
+                    day, part
+                )
+            }
+        }
+    }
+}
+
+impl error::Error for Error {
+    fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+        match self {
+            Error::Parse(parse_error) => Some(parse_error),
+            _ => None,
+        }
+    }
+}
+
+impl From<ParseError> for Error {
+    fn from(parse_error: ParseError) -> Self {
+        Error::Parse(parse_error)
+    }
+}
+
+/// Builds [`ParseError`]s for one day's puzzle input.
+///
+/// Every `&str` handed to these methods must be a slice of the puzzle
+/// input, which is always the case for the results of `split`, `trim`
+/// and friends. Its line and column are worked out from where it sits
+/// in the puzzle input.
+pub(crate) struct ParseContext<'a> {
+    day: u8,
+    puzzle_input: &'a str,
+}
+
+impl<'a> ParseContext<'a> {
+    pub(crate) fn new(day: u8, puzzle_input: &'a str) -> Self {
+        ParseContext { day, puzzle_input }
+    }
+
+    /// Reports that `text` is not the `expected` thing.
+    pub(crate) fn error(&self, text: &str, expected: &str) -> ParseError {
+        let offset = (text.as_ptr() as usize).wrapping_sub(self.puzzle_input.as_ptr() as usize);
+        assert!(
+            offset <= self.puzzle_input.len(),
+            "Offending text must be a slice of the puzzle input"
+        );
+        let preceding = &self.puzzle_input[..offset];
+        let line_start = preceding.rfind('\n').map_or(0, |i| i + 1);
+        let line_end = self.puzzle_input[offset..]
+            .find('\n')
+            .map_or(self.puzzle_input.len(), |i| offset + i);
+        ParseError {
+            day: self.day,
+            line: preceding.matches('\n').count() + 1,
+            column: preceding[line_start..].chars().count() + 1,
+            text: text.to_string(),
+            line_text: self.puzzle_input[line_start..line_end].to_string(),
+            expected: expected.to_string(),
+        }
+    }
+
+    /// Reports that something `expected` is missing from the end of
+    /// `text`.
+    pub(crate) fn missing(&self, text: &str, expected: &str) -> ParseError {
+        self.error(&text[text.len()..], expected)
+    }
+
+    /// Unwraps the next item from a `split` of `text`, reporting it as
+    /// missing if there is none.
+    pub(crate) fn next<'b>(
+        &self,
+        items: &mut impl Iterator<Item = &'b str>,
+        text: &str,
+        expected: &str,
+    ) -> Result<&'b str, ParseError> {
+        items.next().ok_or_else(|| self.missing(text, expected))
+    }
+
+    /// Parses `text` with [`str::parse`], reporting it as not being the
+    /// `expected` thing on failure.
+    pub(crate) fn parse<T: FromStr>(&self, text: &str, expected: &str) -> Result<T, ParseError> {
+        text.parse::<T>().map_err(|_| self.error(text, expected))
+    }
+}
+
+#[test]
+fn test_parse_context_error_location() {
+    let puzzle_input = "1,2\n3,x4\n";
+    let context = ParseContext::new(5, puzzle_input);
+    let error = context.error(&puzzle_input[6..8], "an integer");
+    assert_eq!(error.line, 2);
+    assert_eq!(error.column, 3);
+    assert_eq!(error.text, "x4");
+    assert_eq!(error.line_text, "3,x4");
+    assert_eq!(
+        error.to_string(),
+        "day 5 input, line 2, column 3: expected an integer, found \"x4\""
+    );
+    assert_eq!(error.snippet(), "  |\n2 | 3,x4\n  |   ^^");
+}
+
+#[test]
+fn test_parse_context_missing() {
+    let puzzle_input = "on x=1..2";
+    let context = ParseContext::new(22, puzzle_input);
+    let mut fields = puzzle_input.split(',');
+    let x_range = context.next(&mut fields, puzzle_input, "x range").unwrap();
+    let error = context
+        .next(&mut fields, x_range, "\",y=\" followed by a range")
+        .unwrap_err();
+    assert_eq!((error.line, error.column), (1, 10));
+    assert_eq!(
+        error.to_string(),
+        "day 22 input, line 1, column 10: expected \",y=\" followed by a range, found end of line"
+    );
+}
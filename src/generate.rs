@@ -0,0 +1,236 @@
+use std::ops::RangeInclusive;
+
+use crate::{day01, day02, day03, day04, day05, day06, day07, day08, day09, day10};
+use crate::{day11, day12, day13, day14, day15, day16, day17, day21, day22};
+
+/// A small, seedable pseudo-random number generator (SplitMix64), so
+/// that generated inputs are the same for a given seed on every
+/// platform and Rust version.
+#[derive(Debug, Clone)]
+pub struct Rng {
+    state: u64,
+}
+
+impl Rng {
+    pub fn new(seed: u64) -> Self {
+        Rng { state: seed }
+    }
+
+    pub fn next_u64(&mut self) -> u64 {
+        self.state = self.state.wrapping_add(0x9e37_79b9_7f4a_7c15);
+        let mut z = self.state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+        z ^ (z >> 31)
+    }
+
+    /// A number from 0 up to but not including `bound`, which must not
+    /// be zero.
+    pub fn below(&mut self, bound: u64) -> u64 {
+        ((self.next_u64() as u128 * bound as u128) >> 64) as u64
+    }
+
+    /// An index into something of length `len`, which must not be zero.
+    pub fn index(&mut self, len: usize) -> usize {
+        self.below(len as u64) as usize
+    }
+
+    pub fn range(&mut self, range: RangeInclusive<i64>) -> i64 {
+        let span = (*range.end() as i128 - *range.start() as i128 + 1) as u64;
+        range.start().wrapping_add(self.below(span) as i64)
+    }
+
+    /// True with the given probability.
+    pub fn chance(&mut self, probability: f64) -> bool {
+        ((self.next_u64() >> 11) as f64 / (1_u64 << 53) as f64) < probability
+    }
+
+    pub fn choose<'a, T>(&mut self, items: &'a [T]) -> &'a T {
+        &items[self.index(items.len())]
+    }
+
+    pub fn shuffle<T>(&mut self, items: &mut [T]) {
+        for i in (1..items.len()).rev() {
+            items.swap(i, self.index(i + 1));
+        }
+    }
+}
+
+/// Writes a random puzzle input for one day.
+pub struct Generator {
+    pub day: u8,
+    /// What `size` controls, e.g. `"number of vent lines"`.
+    pub size_meaning: &'static str,
+    /// The size of a real puzzle input.
+    pub default_size: usize,
+    pub generate: fn(&mut Rng, usize) -> String,
+}
+
+/// A generator for every implemented day, in order.
+pub static GENERATORS: &[Generator] = &[
+    Generator {
+        day: 1,
+        size_meaning: "number of depth measurements",
+        default_size: 2000,
+        generate: day01::generate_input,
+    },
+    Generator {
+        day: 2,
+        size_meaning: "number of commands",
+        default_size: 1000,
+        generate: day02::generate_input,
+    },
+    Generator {
+        day: 3,
+        size_meaning: "number of binary numbers",
+        default_size: 1000,
+        generate: day03::generate_input,
+    },
+    Generator {
+        day: 4,
+        size_meaning: "number of boards",
+        default_size: 100,
+        generate: day04::generate_input,
+    },
+    Generator {
+        day: 5,
+        size_meaning: "number of vent lines",
+        default_size: 500,
+        generate: day05::generate_input,
+    },
+    Generator {
+        day: 6,
+        size_meaning: "number of lanternfish",
+        default_size: 300,
+        generate: day06::generate_input,
+    },
+    Generator {
+        day: 7,
+        size_meaning: "number of crabs",
+        default_size: 1000,
+        generate: day07::generate_input,
+    },
+    Generator {
+        day: 8,
+        size_meaning: "number of displays",
+        default_size: 200,
+        generate: day08::generate_input,
+    },
+    Generator {
+        day: 9,
+        size_meaning: "width and height of the height map",
+        default_size: 100,
+        generate: day09::generate_input,
+    },
+    Generator {
+        day: 10,
+        size_meaning: "number of lines",
+        default_size: 90,
+        generate: day10::generate_input,
+    },
+    Generator {
+        day: 11,
+        size_meaning: "width and height of the octopus grid",
+        default_size: 10,
+        generate: day11::generate_input,
+    },
+    Generator {
+        day: 12,
+        size_meaning: "number of connections between caves",
+        default_size: 24,
+        generate: day12::generate_input,
+    },
+    Generator {
+        day: 13,
+        size_meaning: "number of dots",
+        default_size: 900,
+        generate: day13::generate_input,
+    },
+    Generator {
+        day: 14,
+        size_meaning: "length of the polymer template",
+        default_size: 20,
+        generate: day14::generate_input,
+    },
+    Generator {
+        day: 15,
+        size_meaning: "width and height of the cavern",
+        default_size: 100,
+        generate: day15::generate_input,
+    },
+    Generator {
+        day: 16,
+        size_meaning: "number of packets",
+        default_size: 60,
+        generate: day16::generate_input,
+    },
+    Generator {
+        day: 17,
+        size_meaning: "rough distance to the target area",
+        default_size: 120,
+        generate: day17::generate_input,
+    },
+    Generator {
+        day: 21,
+        size_meaning: "ignored",
+        default_size: 0,
+        generate: day21::generate_input,
+    },
+    Generator {
+        day: 22,
+        size_meaning: "number of reboot steps",
+        default_size: 420,
+        generate: day22::generate_input,
+    },
+];
+
+pub fn find_generator(day: u8) -> Option<&'static Generator> {
+    GENERATORS.iter().find(|generator| generator.day == day)
+}
+
+/// Generates a puzzle input for `day` from `seed`, using the size of a
+/// real puzzle input unless `size` is given. Returns `None` if there
+/// is no generator for `day`.
+pub fn generate(day: u8, seed: u64, size: Option<usize>) -> Option<String> {
+    let generator = find_generator(day)?;
+    let mut rng = Rng::new(seed);
+    Some((generator.generate)(
+        &mut rng,
+        size.unwrap_or(generator.default_size),
+    ))
+}
+
+#[test]
+fn test_generated_inputs_are_solvable() {
+    for generator in GENERATORS {
+        let solver = crate::solver::find_solver(generator.day).unwrap();
+        for (seed, size) in
+            (0..3).flat_map(|seed| [Some(0), Some(1), Some(2), None].map(|size| (seed, size)))
+        {
+            let puzzle_input = generate(generator.day, seed, size);
+            let input = solver.parse(puzzle_input.unwrap()).unwrap_or_else(|error| {
+                panic!(
+                    "day {} seed {} size {:?}: {}",
+                    generator.day, seed, size, error
+                )
+            });
+            for part in [1, 2] {
+                if let Err(error) = solver.solve(input.as_ref(), part) {
+                    panic!(
+                        "day {} part {} seed {} size {:?}: {}",
+                        generator.day, part, seed, size, error
+                    );
+                }
+            }
+        }
+    }
+}
+
+#[test]
+fn test_generation_is_deterministic() {
+    assert_eq!(generate(16, 7, None), generate(16, 7, None));
+    assert_ne!(generate(16, 7, None), generate(16, 8, None));
+    assert_eq!(generate(19, 7, None), None);
+    let mut rng = Rng::new(1);
+    assert!((0..1000).all(|_| (-3..=3).contains(&rng.range(-3..=3))));
+}
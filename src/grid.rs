@@ -0,0 +1,307 @@
+use std::fmt;
+use std::ops::{Index, IndexMut};
+use std::str::FromStr;
+
+use crate::error::{ParseContext, ParseError};
+
+/// A rectangle of cells, indexed by `(x, y)` from the top left.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Grid<T> {
+    width: usize,
+    height: usize,
+    // cells[y * width + x]
+    cells: Vec<T>,
+}
+
+/// Which locations count as next to each other.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Neighborhood {
+    /// Up, down, left and right.
+    Four,
+    /// Diagonals too.
+    Eight,
+    /// Moves of a chess knight.
+    Knight,
+}
+
+impl Neighborhood {
+    pub fn offsets(self) -> &'static [(isize, isize)] {
+        match self {
+            Neighborhood::Four => &[(-1, 0), (1, 0), (0, -1), (0, 1)],
+            Neighborhood::Eight => &[
+                (-1, -1),
+                (0, -1),
+                (1, -1),
+                (-1, 0),
+                (1, 0),
+                (-1, 1),
+                (0, 1),
+                (1, 1),
+            ],
+            Neighborhood::Knight => &[
+                (-1, -2),
+                (1, -2),
+                (-2, -1),
+                (2, -1),
+                (-2, 1),
+                (2, 1),
+                (-1, 2),
+                (1, 2),
+            ],
+        }
+    }
+
+    /// The neighbors of `(x, y)` within a `width` by `height` rectangle.
+    pub fn neighbors(
+        self,
+        (x, y): (usize, usize),
+        width: usize,
+        height: usize,
+    ) -> impl Iterator<Item = (usize, usize)> {
+        self.offsets()
+            .iter()
+            .filter_map(move |(x_offset, y_offset)| {
+                let neighbor_x = x.checked_add_signed(*x_offset)?;
+                let neighbor_y = y.checked_add_signed(*y_offset)?;
+                match neighbor_x < width && neighbor_y < height {
+                    true => Some((neighbor_x, neighbor_y)),
+                    false => None,
+                }
+            })
+    }
+
+    /// A lower bound on the number of moves from `from` to `to`.
+    pub fn min_moves(self, (x1, y1): (usize, usize), (x2, y2): (usize, usize)) -> u64 {
+        let (x_distance, y_distance) = (x1.abs_diff(x2) as u64, y1.abs_diff(y2) as u64);
+        match self {
+            Neighborhood::Four => x_distance + y_distance,
+            Neighborhood::Eight => x_distance.max(y_distance),
+            // Each move covers at most 3 in total and 2 along either axis.
+            Neighborhood::Knight => (x_distance + y_distance)
+                .div_ceil(3)
+                .max(x_distance.max(y_distance).div_ceil(2)),
+        }
+    }
+}
+
+impl FromStr for Neighborhood {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "4" => Ok(Neighborhood::Four),
+            "8" => Ok(Neighborhood::Eight),
+            "knight" => Ok(Neighborhood::Knight),
+            _ => Err(format!(
+                "unknown neighborhood: {} (expected 4, 8 or knight)",
+                s
+            )),
+        }
+    }
+}
+
+impl<T> Grid<T> {
+    /// A `width` by `height` grid with every cell set to `value`.
+    pub fn new(width: usize, height: usize, value: T) -> Self
+    where
+        T: Clone,
+    {
+        Grid {
+            width,
+            height,
+            cells: vec![value; width * height],
+        }
+    }
+
+    /// A grid with the given rows, or `None` if they are not all the
+    /// same length.
+    pub fn from_rows(rows: Vec<Vec<T>>) -> Option<Self> {
+        let height = rows.len();
+        let width = rows.first().map_or(0, Vec::len);
+        if rows.iter().any(|row| row.len() != width) {
+            return None;
+        }
+        Some(Grid {
+            width,
+            height,
+            cells: rows.into_iter().flatten().collect(),
+        })
+    }
+
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    /// The cell at `(x, y)`, or `None` if that is off the grid.
+    pub fn get(&self, x: usize, y: usize) -> Option<&T> {
+        match x < self.width && y < self.height {
+            true => Some(&self.cells[y * self.width + x]),
+            false => None,
+        }
+    }
+
+    pub fn get_mut(&mut self, x: usize, y: usize) -> Option<&mut T> {
+        match x < self.width && y < self.height {
+            true => Some(&mut self.cells[y * self.width + x]),
+            false => None,
+        }
+    }
+
+    /// Every location on the grid, row by row.
+    pub fn positions(&self) -> impl Iterator<Item = (usize, usize)> {
+        let width = self.width;
+        (0..self.height).flat_map(move |y| (0..width).map(move |x| (x, y)))
+    }
+
+    /// Every cell, row by row.
+    pub fn iter(&self) -> impl Iterator<Item = &T> {
+        self.cells.iter()
+    }
+
+    pub fn iter_mut(&mut self) -> impl Iterator<Item = &mut T> {
+        self.cells.iter_mut()
+    }
+
+    /// The locations up, down, left and right of `(x, y)` that are on
+    /// the grid.
+    pub fn neighbors_4(&self, x: usize, y: usize) -> impl Iterator<Item = (usize, usize)> {
+        Neighborhood::Four.neighbors((x, y), self.width, self.height)
+    }
+
+    /// The locations around `(x, y)`, diagonals included, that are on
+    /// the grid.
+    pub fn neighbors_8(&self, x: usize, y: usize) -> impl Iterator<Item = (usize, usize)> {
+        Neighborhood::Eight.neighbors((x, y), self.width, self.height)
+    }
+
+    /// A grid with `f` applied to every cell.
+    pub fn map<U>(&self, f: impl Fn(&T) -> U) -> Grid<U> {
+        Grid {
+            width: self.width,
+            height: self.height,
+            cells: self.cells.iter().map(f).collect(),
+        }
+    }
+
+    /// A grid made of `across` by `down` copies of this one. Each cell
+    /// of the copy in tile `(tile_x, tile_y)` is `f(cell, tile_x, tile_y)`.
+    pub fn tile(&self, across: usize, down: usize, f: impl Fn(&T, usize, usize) -> T) -> Grid<T> {
+        let width = self.width * across;
+        let height = self.height * down;
+        let mut cells = Vec::with_capacity(width * height);
+        for y in 0..height {
+            for x in 0..width {
+                let cell = &self[(x % self.width, y % self.height)];
+                cells.push(f(cell, x / self.width, y / self.height));
+            }
+        }
+        Grid {
+            width,
+            height,
+            cells,
+        }
+    }
+}
+
+impl Grid<u8> {
+    /// Parses `text` as a non-empty rectangle of decimal digits, one
+    /// row per line.
+    pub(crate) fn parse_digits(context: &ParseContext, text: &str) -> Result<Self, ParseError> {
+        let digit_rows = text
+            .trim()
+            .split("\n")
+            .map(|line| {
+                line.char_indices()
+                    .map(|(i, c)| match c.to_digit(10) {
+                        Some(digit) => Ok(digit as u8),
+                        None => Err(context.error(&line[i..i + c.len_utf8()], "a digit")),
+                    })
+                    .collect::<Result<Vec<u8>, ParseError>>()
+            })
+            .collect::<Result<Vec<Vec<u8>>, ParseError>>()?;
+        let width = digit_rows[0].len();
+        if width == 0 {
+            return Err(context.missing(text.trim(), "a row of digits"));
+        }
+        for (line, row) in text.trim().split("\n").zip(digit_rows.iter()) {
+            if row.len() != width {
+                return Err(context.error(line, &format!("a row of {} digits", width)));
+            }
+        }
+        Ok(Grid::from_rows(digit_rows).unwrap())
+    }
+}
+
+impl<T> Index<(usize, usize)> for Grid<T> {
+    type Output = T;
+
+    fn index(&self, (x, y): (usize, usize)) -> &T {
+        self.get(x, y)
+            .unwrap_or_else(|| panic!("({}, {}) is off the grid", x, y))
+    }
+}
+
+impl<T> IndexMut<(usize, usize)> for Grid<T> {
+    fn index_mut(&mut self, (x, y): (usize, usize)) -> &mut T {
+        self.get_mut(x, y)
+            .unwrap_or_else(|| panic!("({}, {}) is off the grid", x, y))
+    }
+}
+
+/// Draws each row of cells on its own line.
+impl<T: fmt::Display> fmt::Display for Grid<T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for row in self.cells.chunks(self.width.max(1)) {
+            for cell in row {
+                write!(f, "{}", cell)?;
+            }
+            writeln!(f)?;
+        }
+        Ok(())
+    }
+}
+
+#[test]
+fn test_neighbors() {
+    let grid = Grid::new(3, 2, 0);
+    assert_eq!(
+        grid.neighbors_4(0, 0).collect::<Vec<_>>(),
+        vec![(1, 0), (0, 1)]
+    );
+    assert_eq!(
+        grid.neighbors_8(1, 1).collect::<Vec<_>>(),
+        vec![(0, 0), (1, 0), (2, 0), (0, 1), (2, 1)]
+    );
+    assert_eq!(grid.get(3, 0), None);
+    assert_eq!(
+        Neighborhood::Knight
+            .neighbors((0, 0), 3, 3)
+            .collect::<Vec<_>>(),
+        vec![(2, 1), (1, 2)]
+    );
+    assert_eq!(Neighborhood::Knight.min_moves((0, 0), (4, 0)), 2);
+    assert_eq!(Neighborhood::Knight.min_moves((0, 0), (2, 1)), 1);
+    assert_eq!(Neighborhood::Eight.min_moves((0, 0), (4, 1)), 4);
+    assert_eq!(grid.positions().count(), 6);
+}
+
+#[test]
+fn test_parse_digits_and_tile() {
+    let text = "12\n34\n";
+    let grid = Grid::parse_digits(&ParseContext::new(9, text), text).unwrap();
+    assert_eq!(grid[(1, 0)], 2);
+    assert_eq!(grid.to_string(), text);
+    let tiled = grid.tile(2, 1, |digit, tile_x, _| digit + tile_x as u8 * 5);
+    assert_eq!(tiled.to_string(), "1267\n3489\n");
+
+    let text = "12\n3x\n";
+    let error = Grid::parse_digits(&ParseContext::new(9, text), text).unwrap_err();
+    assert_eq!((error.line, error.column), (2, 2));
+    let text = "12\n345\n";
+    let error = Grid::parse_digits(&ParseContext::new(9, text), text).unwrap_err();
+    assert_eq!(error.expected, "a row of 2 digits");
+}
@@ -0,0 +1,75 @@
+use std::fs;
+use std::io;
+
+/// Where puzzle inputs live, relative to the working directory.
+pub const INPUT_DIR: &str = "input";
+
+/// The path of the input for `day`: `input/{day}.txt`, or
+/// `input/{day}_{variant}.txt` for a named variant such as a sample.
+pub fn input_path(day: u8, variant: Option<&str>) -> String {
+    match variant {
+        Some(variant) => format!("{}/{}_{}.txt", INPUT_DIR, day, variant),
+        None => format!("{}/{}.txt", INPUT_DIR, day),
+    }
+}
+
+/// An input file found in [`INPUT_DIR`].
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+pub struct InputFile {
+    pub day: u8,
+    /// `None` for the main input, `input/{day}.txt`.
+    pub variant: Option<String>,
+}
+
+impl InputFile {
+    /// Recognises `{day}.txt` and `{day}_{variant}.txt`.
+    fn from_file_name(file_name: &str) -> Option<Self> {
+        let stem = file_name.strip_suffix(".txt")?;
+        let (day, variant) = match stem.split_once('_') {
+            Some((day, variant)) if !variant.is_empty() => (day, Some(variant.to_string())),
+            Some(_) => return None,
+            None => (stem, None),
+        };
+        Some(InputFile {
+            day: day.parse().ok()?,
+            variant,
+        })
+    }
+
+    pub fn path(&self) -> String {
+        input_path(self.day, self.variant.as_deref())
+    }
+}
+
+/// Lists every input file in [`INPUT_DIR`], ordered by day with the
+/// main input first and then variants by name.
+pub fn discover_inputs() -> io::Result<Vec<InputFile>> {
+    let mut input_files = Vec::new();
+    for entry in fs::read_dir(INPUT_DIR)? {
+        let file_name = entry?.file_name();
+        if let Some(input_file) = file_name.to_str().and_then(InputFile::from_file_name) {
+            input_files.push(input_file);
+        }
+    }
+    input_files.sort();
+    Ok(input_files)
+}
+
+#[test]
+fn test_input_file_names() {
+    assert_eq!(input_path(22, Some("sample1")), "input/22_sample1.txt");
+    assert_eq!(
+        InputFile::from_file_name("15_tiny.txt"),
+        Some(InputFile {
+            day: 15,
+            variant: Some(String::from("tiny"))
+        })
+    );
+    assert_eq!(
+        InputFile::from_file_name("7.txt").map(|input_file| input_file.path()),
+        Some(String::from("input/7.txt"))
+    );
+    assert_eq!(InputFile::from_file_name("7_.txt"), None);
+    assert_eq!(InputFile::from_file_name("notes.md"), None);
+    assert_eq!(InputFile::from_file_name("sample.txt"), None);
+}
@@ -0,0 +1,54 @@
+//! Solutions to Advent of Code 2021.
+//!
+//! Each implemented day has its own module containing a parser for
+//! the puzzle input, `solve_part_1` and `solve_part_2` functions that
+//! work on the parsed input, and a unit struct (e.g. [`day01::Day01`])
+//! implementing [`solver::Solver`]. Malformed input is reported as an
+//! [`error::ParseError`] instead of a panic. Every day is also listed
+//! in [`solver::SOLVERS`], which is the easiest way to solve a puzzle
+//! when the day is only known at runtime:
+//!
+//! ```
+//! use advent_of_code_2021::answer::Answer;
+//! use advent_of_code_2021::solver;
+//!
+//! let solver = solver::find_solver(1).unwrap();
+//! let input = solver.parse("199\n200\n208\n210\n200\n207\n240\n269\n260\n263\n".to_string())?;
+//! assert_eq!(solver.solve(input.as_ref(), 1)?, Answer::Integer(7));
+//! # Ok::<(), advent_of_code_2021::error::Error>(())
+//! ```
+
+pub mod answer;
+pub mod cancel;
+pub mod day01;
+pub mod day02;
+pub mod day03;
+pub mod day04;
+pub mod day05;
+pub mod day06;
+pub mod day07;
+pub mod day08;
+pub mod day09;
+pub mod day10;
+pub mod day11;
+pub mod day12;
+pub mod day13;
+pub mod day14;
+pub mod day15;
+pub mod day16;
+pub mod day17;
+pub mod day21;
+pub mod day22;
+pub mod error;
+pub mod generate;
+pub mod grid;
+pub mod inputs;
+pub mod log;
+pub mod manifest;
+pub mod mem;
+pub mod pathfinding;
+pub mod simulation;
+pub mod solver;
+
+#[cfg(test)]
+mod test_helpers;
@@ -0,0 +1,171 @@
+//! Leveled diagnostics for solvers, written to stderr so that they
+//! never mix with answers on stdout.
+//!
+//! Messages are logged with the [`log!`](crate::log!) macro, which
+//! takes a level, the day the message is about, and a format string:
+//!
+//! ```
+//! use advent_of_code_2021::log;
+//!
+//! log!(Debug, 15, "total costs are now {}", 40);
+//! ```
+//!
+//! Nothing is formatted unless the current [`Filter`] lets the message
+//! through.
+
+use std::fmt;
+use std::io::{self, Write};
+use std::sync::atomic::{AtomicU32, AtomicU8, Ordering};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Level {
+    Error = 1,
+    Warn,
+    Info,
+    Debug,
+    Trace,
+}
+
+impl Level {
+    /// The most detailed level shown when `-v` is given `verbosity`
+    /// times. Warnings and errors are always shown.
+    pub fn from_verbosity(verbosity: u64) -> Level {
+        match verbosity {
+            0 => Level::Warn,
+            1 => Level::Info,
+            2 => Level::Debug,
+            _ => Level::Trace,
+        }
+    }
+
+    fn from_u8(level: u8) -> Level {
+        match level {
+            1 => Level::Error,
+            2 => Level::Warn,
+            3 => Level::Info,
+            4 => Level::Debug,
+            _ => Level::Trace,
+        }
+    }
+}
+
+impl fmt::Display for Level {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let name = match self {
+            Level::Error => "ERROR",
+            Level::Warn => "WARN",
+            Level::Info => "INFO",
+            Level::Debug => "DEBUG",
+            Level::Trace => "TRACE",
+        };
+        // Pad so that messages line up whatever their level.
+        f.pad(name)
+    }
+}
+
+/// Which messages get written: everything up to `max_level`, plus
+/// every message about a traced day.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Filter {
+    max_level: Level,
+    traced_days: u32,
+}
+
+impl Filter {
+    pub fn new(max_level: Level) -> Self {
+        Filter {
+            max_level,
+            traced_days: 0,
+        }
+    }
+
+    /// Also lets through messages of every level about `day`.
+    pub fn trace_day(mut self, day: u8) -> Self {
+        self.traced_days |= day_bit(day);
+        self
+    }
+
+    pub fn enables(&self, level: Level, day: u8) -> bool {
+        level <= self.max_level || self.traced_days & day_bit(day) != 0
+    }
+}
+
+impl Default for Filter {
+    fn default() -> Self {
+        Filter::new(Level::Warn)
+    }
+}
+
+/// Parses a day to trace, such as `15` or `day15`. Days outside 1 to
+/// 25 are rejected, since no message is ever about them.
+pub fn parse_traced_day(text: &str) -> Result<u8, String> {
+    match text.trim_start_matches("day").parse::<u8>() {
+        Ok(day) if (1..=25).contains(&day) => Ok(day),
+        _ => Err(format!(
+            "cannot trace {} (expected a day from 1 to 25, optionally prefixed with \"day\")",
+            text
+        )),
+    }
+}
+
+fn day_bit(day: u8) -> u32 {
+    1_u32.checked_shl(day.into()).unwrap_or(0)
+}
+
+static MAX_LEVEL: AtomicU8 = AtomicU8::new(Level::Warn as u8);
+static TRACED_DAYS: AtomicU32 = AtomicU32::new(0);
+
+/// Replaces the filter used by [`log!`](crate::log!) on every thread.
+pub fn set_filter(filter: Filter) {
+    MAX_LEVEL.store(filter.max_level as u8, Ordering::Relaxed);
+    TRACED_DAYS.store(filter.traced_days, Ordering::Relaxed);
+}
+
+pub fn filter() -> Filter {
+    Filter {
+        max_level: Level::from_u8(MAX_LEVEL.load(Ordering::Relaxed)),
+        traced_days: TRACED_DAYS.load(Ordering::Relaxed),
+    }
+}
+
+pub fn enabled(level: Level, day: u8) -> bool {
+    filter().enables(level, day)
+}
+
+/// Writes a message to stderr. Use [`log!`](crate::log!) instead,
+/// which skips formatting messages that would be filtered out.
+pub fn write(level: Level, day: u8, message: fmt::Arguments) {
+    // There is nowhere left to report a failure to write to stderr.
+    let _ = writeln!(io::stderr(), "[{:<5} day {:02}] {}", level, day, message);
+}
+
+/// Logs a message about a day at the given [`Level`], if the current
+/// [`Filter`] enables it.
+#[macro_export]
+macro_rules! log {
+    ($level:ident, $day:expr, $($arg:tt)+) => {
+        if $crate::log::enabled($crate::log::Level::$level, $day) {
+            $crate::log::write($crate::log::Level::$level, $day, format_args!($($arg)+));
+        }
+    };
+}
+
+#[test]
+fn test_filter() {
+    let filter = Filter::new(Level::from_verbosity(1)).trace_day(15);
+    assert!(filter.enables(Level::Warn, 4));
+    assert!(filter.enables(Level::Info, 4));
+    assert!(!filter.enables(Level::Debug, 4));
+    assert!(filter.enables(Level::Trace, 15));
+    assert!(!Filter::default().enables(Level::Info, 15));
+    assert!(Filter::default().trace_day(200).enables(Level::Warn, 200));
+}
+
+#[test]
+fn test_parse_traced_day() {
+    assert_eq!(parse_traced_day("15"), Ok(15));
+    assert_eq!(parse_traced_day("day25"), Ok(25));
+    assert!(parse_traced_day("0").is_err());
+    assert!(parse_traced_day("day32").is_err());
+    assert!(parse_traced_day("fifteen").is_err());
+}
@@ -1,28 +1,33 @@
-use clap::{App, Arg};
+// The command-line tool's own modules. They reach the library's
+// modules through the imports below, as `crate::solver` and so on.
+mod animation;
+mod bench;
+mod crosscheck;
+mod output;
+mod pool;
+mod repl;
+mod runner;
+mod scaffold;
+mod tui;
+
+use advent_of_code_2021::error::Error;
+use advent_of_code_2021::mem::CountingAllocator;
+use advent_of_code_2021::{
+    answer, cancel, day06, day07, day09, day11, day13, day15, day16, day17, day21, day22, error,
+    generate, inputs, log, manifest, mem, simulation, solver,
+};
+use clap::{App, AppSettings, Arg, SubCommand};
 use std::fs;
+use std::io::{self, Write};
+use std::path::Path;
+use std::process;
+use std::time::Duration;
+
+use crate::output::{Format, PlainStyle, Reporter};
+use crate::runner::RunOptions;
 
-mod day01;
-mod day02;
-mod day03;
-mod day04;
-mod day05;
-mod day06;
-mod day07;
-mod day08;
-mod day09;
-mod day10;
-mod day11;
-mod day12;
-mod day13;
-mod day14;
-mod day15;
-mod day16;
-mod day17;
-mod day21;
-mod day22;
-
-#[cfg(test)]
-mod test_helpers;
+#[global_allocator]
+static ALLOCATOR: CountingAllocator = CountingAllocator;
 
 fn main() {
     let matches = App::new("Advent of Code 2021 Solver")
@@ -36,7 +41,7 @@ fn main() {
                 .value_name("DAY_NUMBER")
                 .help("The day of the problem to be solved")
                 .takes_value(true)
-                .required(true),
+                .required_unless_one(&["all", "check"]),
         )
         .arg(
             Arg::with_name("part")
@@ -45,19 +50,653 @@ fn main() {
                 .value_name("PROBLEM_PART")
                 .help("The part of the problem to be solved (1 or 2)")
                 .takes_value(true)
-                .required(true),
+                .required_unless_one(&["all", "check"]),
         )
         .arg(
             Arg::with_name("input")
                 .short("i")
                 .long("input")
                 .value_name("FILE")
-                .help("Path to a file containing the problem input")
+                .help(
+                    "Path to a file containing the problem input, or - to read stdin. \
+                     May be given more than once to solve several inputs. \
+                     Defaults to input/DAY_NUMBER.txt.",
+                )
+                .takes_value(true)
+                .multiple(true)
+                .number_of_values(1),
+        )
+        .arg(
+            Arg::with_name("variant")
+                .long("variant")
+                .value_name("NAME")
+                .help(
+                    "Use input/DAY_NUMBER_NAME.txt as the problem input, e.g. --variant sample. \
+                     May be given more than once.",
+                )
+                .takes_value(true)
+                .multiple(true)
+                .number_of_values(1)
+                .conflicts_with("input"),
+        )
+        .arg(
+            Arg::with_name("all")
+                .short("a")
+                .long("all")
+                .help("Solve both parts of every implemented day using input/DAY_NUMBER.txt")
+                .conflicts_with_all(&["day", "part", "input", "variant"]),
+        )
+        .arg(
+            Arg::with_name("check")
+                .short("c")
+                .long("check")
+                .help("Check every answer listed in the answers manifest")
+                .conflicts_with_all(&["day", "part", "input", "variant", "all"]),
+        )
+        .arg(
+            Arg::with_name("manifest")
+                .long("manifest")
+                .value_name("FILE")
+                .help("Path to the answers manifest used by --check")
+                .takes_value(true)
+                .default_value("answers.toml"),
+        )
+        .arg(
+            Arg::with_name("format")
+                .short("f")
+                .long("format")
+                .value_name("FORMAT")
+                .help("How to print solutions")
+                .takes_value(true)
+                .possible_values(&["plain", "json", "csv"])
+                .default_value("plain"),
+        )
+        .arg(
+            Arg::with_name("bench")
+                .long("bench")
+                .value_name("RUNS")
+                .help("Time parsing and solving RUNS times and print statistics instead of the solution")
+                .takes_value(true)
+                .conflicts_with_all(&["all", "check"]),
+        )
+        .arg(
+            Arg::with_name("warm-up")
+                .long("warm-up")
+                .value_name("RUNS")
+                .help("Untimed runs before benchmarking")
+                .takes_value(true)
+                .default_value("1"),
+        )
+        .arg(
+            Arg::with_name("save-bench")
+                .long("save-bench")
+                .value_name("FILE")
+                .help("Save benchmark results to FILE, replacing earlier results for the same benchmarks")
                 .takes_value(true)
-                .required(true),
+                .requires("bench"),
+        )
+        .arg(
+            Arg::with_name("compare-bench")
+                .long("compare-bench")
+                .value_name("FILE")
+                .help("Compare benchmark results with those saved in FILE and report regressions")
+                .takes_value(true)
+                .requires("bench"),
+        )
+        .arg(
+            Arg::with_name("timeout")
+                .long("timeout")
+                .value_name("SECONDS")
+                .help("Give up on a part and report TIMEOUT if it takes longer than SECONDS")
+                .takes_value(true)
+                .conflicts_with("bench"),
+        )
+        .arg(
+            Arg::with_name("jobs")
+                .short("j")
+                .long("jobs")
+                .value_name("N")
+                .help("Solve up to N parts or inputs at once; output stays in order")
+                .takes_value(true)
+                .default_value("1"),
+        )
+        .arg(
+            Arg::with_name("mem")
+                .long("mem")
+                .help("Report the peak heap usage, allocations and bytes allocated of each part")
+                .conflicts_with("bench"),
+        )
+        .arg(
+            Arg::with_name("verbose")
+                .short("v")
+                .long("verbose")
+                .help("Log more diagnostics to stderr; repeat for more detail (-vvv logs everything)")
+                .multiple(true),
+        )
+        .arg(
+            Arg::with_name("trace")
+                .long("trace")
+                .value_name("DAY")
+                .help("Log every diagnostic from DAY to stderr, e.g. --trace day15 or --trace 15")
+                .takes_value(true)
+                .multiple(true)
+                .number_of_values(1),
+        )
+        .setting(AppSettings::SubcommandsNegateReqs)
+        .subcommand(
+            SubCommand::with_name("list-inputs")
+                .about("Lists the inputs and named variants in input/ for each day"),
+        )
+        .subcommand(
+            SubCommand::with_name("new-day")
+                .about("Writes src/dayNN.rs with stubs and tests, registers it and adds empty inputs")
+                .arg(
+                    Arg::with_name("day")
+                        .value_name("DAY")
+                        .help("Day to scaffold, from 1 to 25")
+                        .required(true),
+                ),
+        )
+        .subcommand(
+            SubCommand::with_name("generate")
+                .about("Prints a random puzzle input for a day, the same every time for a given seed")
+                .arg(
+                    Arg::with_name("day")
+                        .short("d")
+                        .long("day")
+                        .value_name("DAY")
+                        .help("Day to generate an input for")
+                        .takes_value(true)
+                        .required(true),
+                )
+                .arg(
+                    Arg::with_name("seed")
+                        .short("s")
+                        .long("seed")
+                        .value_name("SEED")
+                        .help("Seed for the random number generator")
+                        .takes_value(true)
+                        .default_value("0"),
+                )
+                .arg(
+                    Arg::with_name("size")
+                        .long("size")
+                        .value_name("SIZE")
+                        .help("How big an input to generate (the meaning depends on the day); defaults to the size of a real input")
+                        .takes_value(true),
+                ),
+        )
+        .subcommand(
+            SubCommand::with_name("crosscheck")
+                .about("Compares solvers with naive reference solvers on generated or given inputs")
+                .arg(
+                    Arg::with_name("day")
+                        .short("d")
+                        .long("day")
+                        .value_name("DAY")
+                        .help("Day to check; defaults to every day with a reference solver")
+                        .takes_value(true),
+                )
+                .arg(
+                    Arg::with_name("input")
+                        .short("i")
+                        .long("input")
+                        .value_name("FILE")
+                        .help("Check this input instead of generated ones; may be repeated")
+                        .takes_value(true)
+                        .multiple(true)
+                        .number_of_values(1)
+                        .requires("day"),
+                )
+                .arg(
+                    Arg::with_name("seed")
+                        .short("s")
+                        .long("seed")
+                        .value_name("SEED")
+                        .help("Seed of the first generated input")
+                        .takes_value(true)
+                        .default_value("0"),
+                )
+                .arg(
+                    Arg::with_name("count")
+                        .short("n")
+                        .long("count")
+                        .value_name("COUNT")
+                        .help("Number of inputs to generate for each day")
+                        .takes_value(true)
+                        .default_value("100"),
+                )
+                .arg(
+                    Arg::with_name("size")
+                        .long("size")
+                        .value_name("SIZE")
+                        .help("Size of the generated inputs, as for generate")
+                        .takes_value(true),
+                ),
+        )
+        .subcommand(
+            SubCommand::with_name("repl")
+                .about("Reads commands for loading inputs, solving parts and inspecting parsed inputs"),
+        )
+        .subcommand(
+            SubCommand::with_name("tui")
+                .about("Opens a full-screen dashboard for picking and solving parts and inputs"),
+        )
+        .subcommand(
+            SubCommand::with_name("animate")
+                .about("Animates a day's simulation step by step (days 6, 11 and 13)")
+                .arg(
+                    Arg::with_name("day")
+                        .short("d")
+                        .long("day")
+                        .value_name("DAY")
+                        .help("Day to animate")
+                        .takes_value(true)
+                        .required(true),
+                )
+                .arg(
+                    Arg::with_name("input")
+                        .short("i")
+                        .long("input")
+                        .value_name("FILE")
+                        .help("Puzzle input; defaults to input/{day}.txt")
+                        .takes_value(true),
+                )
+                .arg(
+                    Arg::with_name("variant")
+                        .long("variant")
+                        .value_name("NAME")
+                        .help("Use input/{day}_{NAME}.txt")
+                        .takes_value(true)
+                        .conflicts_with("input"),
+                )
+                .arg(
+                    Arg::with_name("delay")
+                        .long("delay")
+                        .value_name("MILLISECONDS")
+                        .help("Time between steps")
+                        .takes_value(true)
+                        .default_value("250"),
+                ),
+        )
+        .subcommand(
+            SubCommand::with_name("path")
+                .about("Draws day 15's cavern with the lowest-risk path highlighted")
+                .arg(
+                    Arg::with_name("part")
+                        .short("p")
+                        .long("part")
+                        .value_name("PART")
+                        .help("1 for the cavern as given, 2 for the enlarged cavern")
+                        .takes_value(true)
+                        .possible_values(&["1", "2"])
+                        .default_value("1"),
+                )
+                .arg(
+                    Arg::with_name("tiles")
+                        .long("tiles")
+                        .value_name("FACTOR")
+                        .help("Copies of the map across and down; defaults to 1 for part 1 and 5 for part 2")
+                        .takes_value(true),
+                )
+                .arg(
+                    Arg::with_name("wrap")
+                        .long("wrap")
+                        .value_name("MODULUS")
+                        .help("Risk level at which tiles wrap back around to 1")
+                        .takes_value(true)
+                        .default_value("9"),
+                )
+                .arg(
+                    Arg::with_name("moves")
+                        .long("moves")
+                        .value_name("NEIGHBORHOOD")
+                        .help("Where each step can go: 4 or 8 directions, or like a knight")
+                        .takes_value(true)
+                        .possible_values(&["4", "8", "knight"])
+                        .default_value("4"),
+                )
+                .arg(
+                    Arg::with_name("input")
+                        .short("i")
+                        .long("input")
+                        .value_name("FILE")
+                        .help("Puzzle input; defaults to input/15.txt")
+                        .takes_value(true),
+                )
+                .arg(
+                    Arg::with_name("variant")
+                        .long("variant")
+                        .value_name("NAME")
+                        .help("Use input/15_{NAME}.txt")
+                        .takes_value(true)
+                        .conflicts_with("input"),
+                )
+                .arg(
+                    Arg::with_name("image")
+                        .long("image")
+                        .value_name("FORMAT")
+                        .help("Draw an image instead of printing the cavern in the terminal")
+                        .takes_value(true)
+                        .possible_values(&["ppm", "svg"]),
+                )
+                .arg(
+                    Arg::with_name("output")
+                        .short("o")
+                        .long("output")
+                        .value_name("FILE")
+                        .help("Where to write the image; defaults to stdout")
+                        .takes_value(true)
+                        .requires("image"),
+                )
+                .arg(
+                    Arg::with_name("cell-size")
+                        .long("cell-size")
+                        .value_name("PIXELS")
+                        .help("Width and height of each location in the image")
+                        .takes_value(true)
+                        .default_value("4"),
+                ),
         )
         .get_matches();
 
+    let mut log_filter = log::Filter::new(log::Level::from_verbosity(
+        matches.occurrences_of("verbose"),
+    ));
+    for day in matches.values_of("trace").into_iter().flatten() {
+        let day = match log::parse_traced_day(day) {
+            Ok(day) => day,
+            Err(error) => {
+                eprintln!("error: {}", error);
+                process::exit(1);
+            }
+        };
+        log_filter = log_filter.trace_day(day);
+    }
+    log::set_filter(log_filter);
+
+    if matches.subcommand_matches("list-inputs").is_some() {
+        if let Err(error) = runner::list_inputs() {
+            eprintln!("error: {}: {}", inputs::INPUT_DIR, error);
+            process::exit(1);
+        }
+        return;
+    }
+
+    let format: Format = matches
+        .value_of("format")
+        .expect("A format must be provided.")
+        .parse::<Format>()
+        .expect("The format must be plain, json or csv.");
+    let timeout = matches.value_of("timeout").map(|seconds| {
+        seconds
+            .parse::<f64>()
+            .ok()
+            .filter(|seconds| seconds.is_finite() && *seconds > 0.0)
+            .map(Duration::from_secs_f64)
+            .expect("The timeout must be a positive number of seconds.")
+    });
+    let jobs = matches
+        .value_of("jobs")
+        .expect("A number of jobs must be provided.")
+        .parse::<usize>()
+        .ok()
+        .filter(|jobs| *jobs > 0)
+        .expect("The number of jobs must be a positive integer.");
+    let run_options = RunOptions { timeout, jobs };
+
+    if let Some(matches) = matches.subcommand_matches("animate") {
+        let day = matches
+            .value_of("day")
+            .expect("A day must be provided.")
+            .parse::<u8>()
+            .expect("A day must be specified as an integer.");
+        let delay = matches
+            .value_of("delay")
+            .expect("A delay must be provided.")
+            .parse::<u64>()
+            .map(Duration::from_millis)
+            .expect("The delay must be a whole number of milliseconds.");
+        let input_path = match matches.value_of("input") {
+            Some(input_path) => input_path.to_string(),
+            None => inputs::input_path(day, matches.value_of("variant")),
+        };
+        let puzzle_input = runner::read_puzzle_input(&input_path).unwrap_or_else(|error| {
+            eprintln!("error: {}: {}", input_path, error);
+            process::exit(1);
+        });
+        let simulation = match animation::load(day, puzzle_input) {
+            Some(Ok(simulation)) => simulation,
+            Some(Err(error)) => {
+                eprintln!("error: {}: {}\n{}", input_path, error, error.snippet());
+                process::exit(1);
+            }
+            None => {
+                eprintln!(
+                    "error: day {} cannot be animated; try one of {:?}",
+                    day,
+                    animation::ANIMATED_DAYS
+                );
+                process::exit(1);
+            }
+        };
+        let title = format!("Day {} ({})", day, input_path);
+        if let Err(error) = animation::run(&title, simulation, delay) {
+            eprintln!("error: {}", error);
+            process::exit(1);
+        }
+        return;
+    }
+
+    if let Some(matches) = matches.subcommand_matches("path") {
+        let input_path = match matches.value_of("input") {
+            Some(input_path) => input_path.to_string(),
+            None => inputs::input_path(15, matches.value_of("variant")),
+        };
+        let puzzle_input = runner::read_puzzle_input(&input_path).unwrap_or_else(|error| {
+            eprintln!("error: {}: {}", input_path, error);
+            process::exit(1);
+        });
+        let cavern_map = day15::CavernMap::from(puzzle_input).unwrap_or_else(|error| {
+            eprintln!("error: {}: {}\n{}", input_path, error, error.snippet());
+            process::exit(1);
+        });
+        let default_options = match matches.value_of("part") {
+            Some("2") => day15::CavernOptions::PART_2,
+            _ => day15::CavernOptions::PART_1,
+        };
+        let options = day15::CavernOptions {
+            tile_factor: matches
+                .value_of("tiles")
+                .map_or(default_options.tile_factor, |tiles| {
+                    tiles
+                        .parse::<usize>()
+                        .ok()
+                        .filter(|tiles| *tiles > 0)
+                        .expect("The tile factor must be a positive integer.")
+                }),
+            wrap_modulus: matches
+                .value_of("wrap")
+                .expect("A wrap modulus must be provided.")
+                .parse::<u8>()
+                .ok()
+                .filter(|wrap_modulus| *wrap_modulus > 0)
+                .expect("The wrap modulus must be an integer from 1 to 255."),
+            neighborhood: matches
+                .value_of("moves")
+                .expect("A neighborhood must be provided.")
+                .parse()
+                .expect("The neighborhood must be 4, 8 or knight."),
+        };
+        let cavern = cavern_map.with_options(options);
+        let cell_size = matches
+            .value_of("cell-size")
+            .expect("A cell size must be provided.")
+            .parse::<usize>()
+            .ok()
+            .filter(|cell_size| *cell_size > 0)
+            .expect("The cell size must be a positive number of pixels.");
+        let path = cavern.lowest_risk_path().unwrap_or_else(|| {
+            eprintln!("error: the bottom right of the cavern cannot be reached");
+            process::exit(1);
+        });
+        let rendered = match matches.value_of("image") {
+            Some("ppm") => cavern.render_ppm(&path.nodes, cell_size),
+            Some(_) => cavern.render_svg(&path.nodes, cell_size).into_bytes(),
+            None => cavern.render_terminal(&path.nodes).into_bytes(),
+        };
+        let written = match matches.value_of("output") {
+            Some(output_path) => fs::write(output_path, rendered),
+            None => io::stdout().write_all(&rendered),
+        };
+        if let Err(error) = written {
+            eprintln!("error: {}", error);
+            process::exit(1);
+        }
+        eprintln!(
+            "lowest total risk {}, along a path through {} locations",
+            path.cost,
+            path.nodes.len()
+        );
+        return;
+    }
+
+    if let Some(matches) = matches.subcommand_matches("new-day") {
+        let day = matches
+            .value_of("day")
+            .expect("A day must be provided.")
+            .parse::<u8>()
+            .expect("A day must be specified as an integer.");
+        match scaffold::new_day(Path::new("."), day) {
+            Ok(written) => {
+                for path in written {
+                    println!("wrote {}", path.display());
+                }
+                println!(
+                    "Fill in input/{0}.txt and input/{0}_sample.txt, solve the parts, then record the \
+                     answers in the tests and in answers.toml.",
+                    day
+                );
+            }
+            Err(error) => {
+                eprintln!("error: {}", error);
+                process::exit(1);
+            }
+        }
+        return;
+    }
+
+    if let Some(matches) = matches.subcommand_matches("generate") {
+        let day = matches
+            .value_of("day")
+            .expect("A day must be provided.")
+            .parse::<u8>()
+            .expect("A day must be specified as an integer.");
+        let seed = matches
+            .value_of("seed")
+            .expect("A seed must be provided.")
+            .parse::<u64>()
+            .expect("The seed must be a non-negative integer.");
+        let size = matches.value_of("size").map(|size| {
+            size.parse::<usize>()
+                .expect("The size must be a non-negative integer.")
+        });
+        match generate::generate(day, seed, size) {
+            Some(puzzle_input) => print!("{}", puzzle_input),
+            None => {
+                eprintln!("error: there is no generator for day {}", day);
+                process::exit(1);
+            }
+        }
+        return;
+    }
+
+    if let Some(matches) = matches.subcommand_matches("crosscheck") {
+        let days = match matches.value_of("day") {
+            Some(day) => {
+                let day = day
+                    .parse::<u8>()
+                    .expect("A day must be specified as an integer.");
+                if !crosscheck::reference_days().contains(&day) {
+                    eprintln!("error: there is no reference solver for day {}", day);
+                    process::exit(1);
+                }
+                vec![day]
+            }
+            None => crosscheck::reference_days(),
+        };
+        let inputs = match matches.values_of("input") {
+            Some(input_paths) => crosscheck::Inputs::Files(input_paths.map(String::from).collect()),
+            None => crosscheck::Inputs::Generated {
+                first_seed: matches
+                    .value_of("seed")
+                    .expect("A seed must be provided.")
+                    .parse()
+                    .expect("The seed must be a non-negative integer."),
+                count: matches
+                    .value_of("count")
+                    .expect("A count must be provided.")
+                    .parse()
+                    .expect("The count must be a non-negative integer."),
+                size: matches.value_of("size").map(|size| {
+                    size.parse()
+                        .expect("The size must be a non-negative integer.")
+                }),
+            },
+        };
+        if !crosscheck::run(&days, &inputs) {
+            process::exit(1);
+        }
+        return;
+    }
+
+    if matches.subcommand_matches("repl").is_some() {
+        if let Err(error) = repl::run() {
+            eprintln!("error: {}", error);
+            process::exit(1);
+        }
+        return;
+    }
+
+    if matches.subcommand_matches("tui").is_some() {
+        if let Err(error) = tui::run(run_options.timeout) {
+            eprintln!("error: {}", error);
+            process::exit(1);
+        }
+        return;
+    }
+    let stdout = io::stdout();
+
+    if matches.is_present("check") {
+        let manifest_path = matches
+            .value_of("manifest")
+            .expect("A manifest must be provided.");
+        let entries = fs::read_to_string(manifest_path)
+            .map_err(|error| error.to_string())
+            .and_then(|manifest| {
+                manifest::parse_manifest(&manifest).map_err(|error| error.to_string())
+            })
+            .unwrap_or_else(|message| {
+                eprintln!("error: {}: {}", manifest_path, message);
+                process::exit(1);
+            });
+        if !runner::run_check(&entries, &run_options) {
+            process::exit(1);
+        }
+        return;
+    }
+
+    if matches.is_present("all") {
+        let mut reporter = Reporter::new(stdout.lock(), format, PlainStyle::Table)
+            .with_memory(matches.is_present("mem"));
+        let all_succeeded = runner::run_all(&mut reporter, &run_options)
+            .expect("Something went wrong while writing the output.");
+        if !all_succeeded {
+            process::exit(1);
+        }
+        return;
+    }
+
     let day: u8 = matches
         .value_of("day")
         .expect("A day must be provided.")
@@ -68,56 +707,65 @@ fn main() {
         .expect("A part must be provided.")
         .parse::<u8>()
         .expect("A part must be specified as an integer (1 or 2)");
-    let input_file_path: &str = matches
-        .value_of("input")
-        .expect("The path to an input file must be specified.");
-
-    let puzzle_input: String = fs::read_to_string(input_file_path)
-        .expect("Something went wrong while reading the input file.");
-
-    let solution: String = match (day, part) {
-        (1, 1) => day01::solve_part_1(puzzle_input).to_string(),
-        (1, 2) => day01::solve_part_2(puzzle_input).to_string(),
-        (2, 1) => day02::solve_part_1(puzzle_input).to_string(),
-        (2, 2) => day02::solve_part_2(puzzle_input).to_string(),
-        (3, 1) => day03::solve_part_1(puzzle_input).to_string(),
-        (3, 2) => day03::solve_part_2(puzzle_input).to_string(),
-        (4, 1) => day04::solve_part_1(puzzle_input).to_string(),
-        (4, 2) => day04::solve_part_2(puzzle_input).to_string(),
-        (5, 1) => day05::solve_part_1(puzzle_input).to_string(),
-        (5, 2) => day05::solve_part_2(puzzle_input).to_string(),
-        (6, 1) => day06::solve_part_1(puzzle_input).to_string(),
-        (6, 2) => day06::solve_part_2(puzzle_input).to_string(),
-        (7, 1) => day07::solve_part_1(puzzle_input).to_string(),
-        (7, 2) => day07::solve_part_2(puzzle_input).to_string(),
-        (8, 1) => day08::solve_part_1(puzzle_input).to_string(),
-        (8, 2) => day08::solve_part_2(puzzle_input).to_string(),
-        (9, 1) => day09::solve_part_1(puzzle_input).to_string(),
-        (9, 2) => day09::solve_part_2(puzzle_input).to_string(),
-        (10, 1) => day10::solve_part_1(puzzle_input).to_string(),
-        (10, 2) => day10::solve_part_2(puzzle_input).to_string(),
-        (11, 1) => day11::solve_part_1(puzzle_input).to_string(),
-        (11, 2) => day11::solve_part_2(puzzle_input).to_string(),
-        (12, 1) => day12::solve_part_1(puzzle_input).to_string(),
-        (12, 2) => day12::solve_part_2(puzzle_input).to_string(),
-        (13, 1) => day13::solve_part_1(puzzle_input).to_string(),
-        (13, 2) => day13::solve_part_2(puzzle_input),
-        (14, 1) => day14::solve_part_1(puzzle_input),
-        (14, 2) => day14::solve_part_2(puzzle_input),
-        (15, 1) => day15::solve_part_1(puzzle_input),
-        (15, 2) => day15::solve_part_2(puzzle_input),
-        (16, 1) => day16::solve_part_1(puzzle_input),
-        (16, 2) => day16::solve_part_2(puzzle_input),
-        (17, 1) => day17::solve_part_1(puzzle_input),
-        (17, 2) => day17::solve_part_2(puzzle_input),
-        (21, 1) => day21::solve_part_1(puzzle_input),
-        (21, 2) => day21::solve_part_2(puzzle_input),
-        (22, 1) => day22::solve_part_1(puzzle_input),
-        (22, 2) => day22::solve_part_2(puzzle_input),
-        _ => panic!(
-            "The solution for day {} part {} is not implemented",
-            day, part
-        ),
+    let input_paths: Vec<String> = match (matches.values_of("input"), matches.values_of("variant"))
+    {
+        (Some(input_paths), _) => input_paths.map(String::from).collect(),
+        (None, Some(variants)) => variants
+            .map(|variant| inputs::input_path(day, Some(variant)))
+            .collect(),
+        (None, None) => vec![inputs::input_path(day, None)],
+    };
+
+    let solver = solver::find_solver(day).unwrap_or_else(|| {
+        eprintln!("error: {}", Error::NotImplemented { day, part });
+        process::exit(1);
+    });
+
+    if let Some(runs) = matches.value_of("bench") {
+        let runs = runs
+            .parse::<usize>()
+            .ok()
+            .filter(|runs| *runs > 0)
+            .expect("The number of benchmark runs must be a positive integer.");
+        let warm_up_runs = matches
+            .value_of("warm-up")
+            .expect("A number of warm-up runs must be provided.")
+            .parse::<usize>()
+            .expect("The number of warm-up runs must be an integer.");
+        let baseline = matches.value_of("compare-bench").map(|path| {
+            bench::load(path).unwrap_or_else(|error| {
+                eprintln!("error: {}: {}", path, error);
+                process::exit(1);
+            })
+        });
+        let (measurements, succeeded) = runner::run_bench(
+            solver,
+            part,
+            &input_paths,
+            warm_up_runs,
+            runs,
+            baseline.as_deref(),
+        );
+        if let Some(path) = matches.value_of("save-bench") {
+            if let Err(error) = bench::save(path, &measurements) {
+                eprintln!("error: {}: {}", path, error);
+                process::exit(1);
+            }
+        }
+        if !succeeded {
+            process::exit(1);
+        }
+        return;
+    }
+
+    let plain_style = PlainStyle::Sentences {
+        show_input_path: input_paths.len() > 1,
     };
-    println!("The solution to day {} part {} is {}", day, part, solution);
+    let mut reporter =
+        Reporter::new(stdout.lock(), format, plain_style).with_memory(matches.is_present("mem"));
+    let all_succeeded = runner::run_inputs(&mut reporter, solver, part, &input_paths, &run_options)
+        .expect("Something went wrong while writing the output.");
+    if !all_succeeded {
+        process::exit(1);
+    }
 }
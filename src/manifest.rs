@@ -0,0 +1,213 @@
+use std::error;
+use std::fmt;
+
+use crate::answer::Answer;
+
+/// One entry of an expected-answers manifest such as `answers.toml`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ExpectedAnswer {
+    pub day: u8,
+    pub part: u8,
+    pub input_path: String,
+    pub answer: Answer,
+}
+
+/// Describes a malformed manifest. `line` is 1-based.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ManifestError {
+    pub line: usize,
+    pub message: String,
+}
+
+impl fmt::Display for ManifestError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "line {}: {}", self.line, self.message)
+    }
+}
+
+impl error::Error for ManifestError {}
+
+/// Fields seen so far in the current `[[answer]]` table.
+#[derive(Default)]
+struct PartialEntry {
+    line: usize,
+    day: Option<u8>,
+    part: Option<u8>,
+    input_path: Option<String>,
+    answer: Option<Answer>,
+}
+
+impl PartialEntry {
+    fn finish(self) -> Result<ExpectedAnswer, ManifestError> {
+        let missing = |key: &str| ManifestError {
+            line: self.line,
+            message: format!("this [[answer]] entry has no {}", key),
+        };
+        Ok(ExpectedAnswer {
+            day: self.day.ok_or_else(|| missing("day"))?,
+            part: self.part.ok_or_else(|| missing("part"))?,
+            input_path: self.input_path.clone().ok_or_else(|| missing("input"))?,
+            answer: self.answer.clone().ok_or_else(|| missing("answer"))?,
+        })
+    }
+}
+
+/// A value on the right hand side of `key = value`.
+enum Value {
+    Integer(i128),
+    Text(String),
+}
+
+fn parse_value(value_text: &str) -> Option<Value> {
+    match value_text.strip_prefix('"') {
+        Some(quoted) => {
+            let mut text = String::new();
+            let mut chars = quoted.chars();
+            loop {
+                match chars.next()? {
+                    '"' => break,
+                    '\\' => match chars.next()? {
+                        '"' => text.push('"'),
+                        '\\' => text.push('\\'),
+                        'n' => text.push('\n'),
+                        't' => text.push('\t'),
+                        _ => return None,
+                    },
+                    c => text.push(c),
+                }
+            }
+            match chars.as_str().trim() {
+                "" => Some(Value::Text(text)),
+                rest if rest.starts_with('#') => Some(Value::Text(text)),
+                _ => None,
+            }
+        }
+        None => {
+            let digits = value_text.split('#').next()?.trim().replace('_', "");
+            digits.parse::<i128>().ok().map(Value::Integer)
+        }
+    }
+}
+
+/// Parses a manifest of expected answers.
+///
+/// The manifest is a small subset of TOML: a list of `[[answer]]`
+/// tables, each with integer `day` and `part` keys, a string `input`
+/// key and an `answer` key that may be an integer or a string. An
+/// integer only matches an integer [`Answer`] and a string only matches
+/// an [`Answer::Text`]. Comments and blank lines are allowed anywhere.
+pub fn parse_manifest(manifest: &str) -> Result<Vec<ExpectedAnswer>, ManifestError> {
+    let mut entries = Vec::new();
+    let mut current: Option<PartialEntry> = None;
+    for (index, raw_line) in manifest.lines().enumerate() {
+        let line_number = index + 1;
+        let error = |message: String| ManifestError {
+            line: line_number,
+            message,
+        };
+        let line = raw_line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        if line.starts_with('[') {
+            if line.split('#').next().unwrap_or_default().trim() != "[[answer]]" {
+                return Err(error(format!("expected [[answer]], found {:?}", line)));
+            }
+            if let Some(entry) = current.take() {
+                entries.push(entry.finish()?);
+            }
+            current = Some(PartialEntry {
+                line: line_number,
+                ..PartialEntry::default()
+            });
+            continue;
+        }
+        let entry = current
+            .as_mut()
+            .ok_or_else(|| error(String::from("expected [[answer]] before any keys")))?;
+        let (key, value_text) = line
+            .split_once('=')
+            .ok_or_else(|| error(format!("expected key = value, found {:?}", line)))?;
+        let key = key.trim();
+        let value = parse_value(value_text.trim())
+            .ok_or_else(|| error(format!("{} has an invalid value", key)))?;
+        match (key, value) {
+            ("day", Value::Integer(day)) if (1..=25).contains(&day) => entry.day = Some(day as u8),
+            ("part", Value::Integer(part)) if (1..=2).contains(&part) => {
+                entry.part = Some(part as u8)
+            }
+            ("input", Value::Text(input_path)) => entry.input_path = Some(input_path),
+            ("answer", Value::Integer(answer)) => entry.answer = Some(Answer::from(answer)),
+            ("answer", Value::Text(answer)) => entry.answer = Some(Answer::from(answer)),
+            ("day", _) => return Err(error(String::from("day must be an integer from 1 to 25"))),
+            ("part", _) => return Err(error(String::from("part must be 1 or 2"))),
+            ("input", _) => return Err(error(String::from("input must be a string"))),
+            _ => return Err(error(format!("unknown key {:?}", key))),
+        }
+    }
+    if let Some(entry) = current {
+        entries.push(entry.finish()?);
+    }
+    Ok(entries)
+}
+
+#[test]
+fn test_parse_manifest() {
+    let manifest = "# comment\n\n[[answer]]\nday = 13\npart = 2\ninput = \"input/13.txt\"\nanswer = \"HLBUBGFR\" # text\n\n[[answer]]\nanswer = 1_676\ninput = \"in\\\"put\"\npart = 1\nday = 1\n";
+    assert_eq!(
+        parse_manifest(manifest),
+        Ok(vec![
+            ExpectedAnswer {
+                day: 13,
+                part: 2,
+                input_path: String::from("input/13.txt"),
+                answer: Answer::from("HLBUBGFR"),
+            },
+            ExpectedAnswer {
+                day: 1,
+                part: 1,
+                input_path: String::from("in\"put"),
+                answer: Answer::Integer(1676),
+            },
+        ])
+    );
+}
+
+#[test]
+fn test_parse_manifest_errors() {
+    let error = parse_manifest("[[answer]]\nday = 1\npart = 3\n").unwrap_err();
+    assert_eq!(error.to_string(), "line 3: part must be 1 or 2");
+    let error = parse_manifest("\n[[answer]]\nday = 1\npart = 1\n").unwrap_err();
+    assert_eq!(
+        error.to_string(),
+        "line 2: this [[answer]] entry has no input"
+    );
+    let error = parse_manifest("day = 1\n").unwrap_err();
+    assert_eq!(error.line, 1);
+}
+
+#[test]
+fn test_answers_manifest_covers_every_solver() {
+    let mut manifest_path = std::path::PathBuf::from(env!("CARGO_MANIFEST_DIR"));
+    manifest_path.push("answers.toml");
+    let manifest = std::fs::read_to_string(manifest_path).unwrap();
+    let entries = parse_manifest(&manifest).unwrap();
+    for solver in crate::solver::SOLVERS {
+        // Days scaffolded with new-day start with an empty placeholder
+        // input, which has no answers to record yet.
+        if crate::test_helpers::load_puzzle_input(solver.day()).is_empty() {
+            continue;
+        }
+        for part in 1..=2 {
+            let input_path = format!("input/{}.txt", solver.day());
+            assert!(
+                entries.iter().any(|entry| entry.day == solver.day()
+                    && entry.part == part
+                    && entry.input_path == input_path),
+                "answers.toml has no entry for day {} part {}",
+                solver.day(),
+                part
+            );
+        }
+    }
+}
@@ -0,0 +1,165 @@
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::cell::Cell;
+use std::fmt;
+
+/// A global allocator that counts the allocations made by each thread,
+/// so that [`measure`] can report how much memory a solver used.
+///
+/// It only counts if the binary installs it:
+///
+/// ```
+/// use advent_of_code_2021::mem::CountingAllocator;
+///
+/// #[global_allocator]
+/// static ALLOCATOR: CountingAllocator = CountingAllocator;
+/// ```
+pub struct CountingAllocator;
+
+thread_local! {
+    /// Bytes allocated by this thread and not yet freed. Memory freed
+    /// by a different thread from the one that allocated it can push
+    /// this below zero.
+    static CURRENT_BYTES: Cell<isize> = const { Cell::new(0) };
+    static PEAK_BYTES: Cell<isize> = const { Cell::new(0) };
+    static ALLOCATIONS: Cell<u64> = const { Cell::new(0) };
+    static BYTES_ALLOCATED: Cell<u64> = const { Cell::new(0) };
+}
+
+fn record_allocation(size: usize) {
+    // The counters may already be gone while a thread is shutting down.
+    let _ = CURRENT_BYTES.try_with(|current_bytes| {
+        let current = current_bytes.get() + size as isize;
+        current_bytes.set(current);
+        let _ = PEAK_BYTES.try_with(|peak_bytes| peak_bytes.set(peak_bytes.get().max(current)));
+    });
+    let _ = ALLOCATIONS.try_with(|allocations| allocations.set(allocations.get() + 1));
+    let _ = BYTES_ALLOCATED.try_with(|bytes| bytes.set(bytes.get() + size as u64));
+}
+
+fn record_deallocation(size: usize) {
+    let _ = CURRENT_BYTES
+        .try_with(|current_bytes| current_bytes.set(current_bytes.get() - size as isize));
+}
+
+unsafe impl GlobalAlloc for CountingAllocator {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        let pointer = System.alloc(layout);
+        if !pointer.is_null() {
+            record_allocation(layout.size());
+        }
+        pointer
+    }
+
+    unsafe fn alloc_zeroed(&self, layout: Layout) -> *mut u8 {
+        let pointer = System.alloc_zeroed(layout);
+        if !pointer.is_null() {
+            record_allocation(layout.size());
+        }
+        pointer
+    }
+
+    unsafe fn dealloc(&self, pointer: *mut u8, layout: Layout) {
+        System.dealloc(pointer, layout);
+        record_deallocation(layout.size());
+    }
+
+    /// Counts as freeing the old block and allocating the new one.
+    unsafe fn realloc(&self, pointer: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+        let new_pointer = System.realloc(pointer, layout, new_size);
+        if !new_pointer.is_null() {
+            record_deallocation(layout.size());
+            record_allocation(new_size);
+        }
+        new_pointer
+    }
+}
+
+/// The heap used while running something.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct MemoryUsage {
+    /// The most memory in use at once, above what was in use at the
+    /// start.
+    pub peak_bytes: u64,
+    /// How many allocations were made, counting each reallocation.
+    pub allocations: u64,
+    /// The total size of every allocation, even those freed since.
+    pub bytes_allocated: u64,
+}
+
+impl fmt::Display for MemoryUsage {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "peak {}, {} allocations, {} allocated",
+            Bytes(self.peak_bytes),
+            self.allocations,
+            Bytes(self.bytes_allocated)
+        )
+    }
+}
+
+/// Displays a number of bytes in B, KiB, MiB or GiB.
+pub struct Bytes(pub u64);
+
+impl fmt::Display for Bytes {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        const UNITS: [&str; 3] = ["KiB", "MiB", "GiB"];
+        if self.0 < 1024 {
+            return f.pad(&format!("{} B", self.0));
+        }
+        let mut size = self.0 as f64 / 1024.0;
+        let mut unit = 0;
+        while size >= 1024.0 && unit < UNITS.len() - 1 {
+            size /= 1024.0;
+            unit += 1;
+        }
+        f.pad(&format!("{:.1} {}", size, UNITS[unit]))
+    }
+}
+
+/// Runs `f` and returns its result along with the memory it used on the
+/// current thread. Everything is zero unless [`CountingAllocator`] is
+/// the global allocator.
+pub fn measure<T>(f: impl FnOnce() -> T) -> (T, MemoryUsage) {
+    let start_bytes = CURRENT_BYTES.with(Cell::get);
+    let start_peak = PEAK_BYTES.with(|peak_bytes| peak_bytes.replace(start_bytes));
+    let start_allocations = ALLOCATIONS.with(Cell::get);
+    let start_bytes_allocated = BYTES_ALLOCATED.with(Cell::get);
+
+    let result = f();
+
+    let peak = PEAK_BYTES.with(|peak_bytes| {
+        let peak = peak_bytes.get();
+        // Leave the peak as if nothing had been measured, for any
+        // enclosing measurement.
+        peak_bytes.set(peak.max(start_peak));
+        peak
+    });
+    let usage = MemoryUsage {
+        peak_bytes: (peak - start_bytes).max(0) as u64,
+        allocations: ALLOCATIONS.with(Cell::get) - start_allocations,
+        bytes_allocated: BYTES_ALLOCATED.with(Cell::get) - start_bytes_allocated,
+    };
+    (result, usage)
+}
+
+#[cfg(test)]
+#[global_allocator]
+static ALLOCATOR: CountingAllocator = CountingAllocator;
+
+#[test]
+fn test_measure() {
+    let (_, usage) = measure(|| {
+        let small = vec![0_u8; 1000];
+        drop(small);
+        let large = vec![0_u64; 1000];
+        let (_, inner_usage) = measure(|| vec![0_u8; 100]);
+        assert_eq!(inner_usage.peak_bytes, 100);
+        large
+    });
+    assert_eq!(usage.peak_bytes, 8100);
+    assert_eq!(usage.allocations, 3);
+    assert_eq!(usage.bytes_allocated, 9100);
+    assert_eq!(Bytes(8100).to_string(), "7.9 KiB");
+    assert_eq!(Bytes(512).to_string(), "512 B");
+}
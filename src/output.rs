@@ -0,0 +1,498 @@
+use std::fmt;
+use std::io::{self, Write};
+use std::str::FromStr;
+use std::time::Duration;
+
+use crate::answer::Answer;
+use crate::mem::{Bytes, MemoryUsage};
+
+/// How solutions are printed.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Format {
+    /// Prose for a single day, or a table for `--all`.
+    Plain,
+    /// A JSON array with one object per record.
+    Json,
+    /// A header line followed by one line per record.
+    Csv,
+}
+
+impl FromStr for Format {
+    type Err = String;
+
+    fn from_str(format: &str) -> Result<Self, Self::Err> {
+        match format {
+            "plain" => Ok(Format::Plain),
+            "json" => Ok(Format::Json),
+            "csv" => Ok(Format::Csv),
+            _ => Err(format!("unknown output format {:?}", format)),
+        }
+    }
+}
+
+/// Why a part could not be solved.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Failure {
+    /// A short label for the plain table, e.g. `"PARSE ERROR"`.
+    pub status: &'static str,
+    pub message: String,
+}
+
+/// The outcome of solving one part of one day for one input.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Record {
+    pub day: u8,
+    pub part: u8,
+    pub input_path: String,
+    pub answer: Result<Answer, Failure>,
+    pub parse_time: Option<Duration>,
+    pub solve_time: Option<Duration>,
+    pub parse_memory: Option<MemoryUsage>,
+    pub solve_memory: Option<MemoryUsage>,
+}
+
+/// How [`Format::Plain`] lays out records.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum PlainStyle {
+    /// One sentence per answer, mentioning the input path only if
+    /// `show_input_path` is set. Failures go to stderr.
+    Sentences { show_input_path: bool },
+    /// A table of answers and timings. Failure details go to stderr.
+    Table,
+}
+
+/// Writes [`Record`]s to `out` in the chosen [`Format`].
+///
+/// Call [`Reporter::begin`] before the first record and
+/// [`Reporter::finish`] after the last.
+pub struct Reporter<W: Write> {
+    out: W,
+    format: Format,
+    plain_style: PlainStyle,
+    show_memory: bool,
+    records_written: usize,
+    last_input: Option<(u8, String)>,
+    last_failure: Option<String>,
+}
+
+impl<W: Write> Reporter<W> {
+    pub fn new(out: W, format: Format, plain_style: PlainStyle) -> Self {
+        Reporter {
+            out,
+            format,
+            plain_style,
+            show_memory: false,
+            records_written: 0,
+            last_input: None,
+            last_failure: None,
+        }
+    }
+
+    /// Also reports the memory used by parsing and solving, which is
+    /// only counted if [`crate::mem::CountingAllocator`] is the global
+    /// allocator.
+    pub fn with_memory(mut self, show_memory: bool) -> Self {
+        self.show_memory = show_memory;
+        self
+    }
+
+    pub fn begin(&mut self) -> io::Result<()> {
+        match (self.format, self.plain_style) {
+            (Format::Plain, PlainStyle::Sentences { .. }) => Ok(()),
+            (Format::Plain, PlainStyle::Table) => {
+                write!(
+                    self.out,
+                    "{:>3} {:>4}  {:>20}  {:>12}  {:>12}",
+                    "Day", "Part", "Answer", "Parse time", "Solve time"
+                )?;
+                if self.show_memory {
+                    write!(
+                        self.out,
+                        "  {:>10}  {:>11}  {:>10}",
+                        "Peak", "Allocations", "Allocated"
+                    )?;
+                }
+                writeln!(self.out)
+            }
+            (Format::Json, _) => write!(self.out, "["),
+            (Format::Csv, _) => {
+                write!(
+                    self.out,
+                    "day,part,input,answer,answer_type,parse_seconds,solve_seconds,error"
+                )?;
+                if self.show_memory {
+                    write!(
+                        self.out,
+                        ",parse_peak_bytes,parse_allocations,parse_bytes_allocated,\
+                         solve_peak_bytes,solve_allocations,solve_bytes_allocated"
+                    )?;
+                }
+                writeln!(self.out)
+            }
+        }
+    }
+
+    pub fn report(&mut self, record: &Record) -> io::Result<()> {
+        match self.format {
+            Format::Plain => self.report_plain(record)?,
+            Format::Json => self.report_json(record)?,
+            Format::Csv => self.report_csv(record)?,
+        }
+        self.records_written += 1;
+        self.last_input = Some((record.day, record.input_path.clone()));
+        self.last_failure = record
+            .answer
+            .as_ref()
+            .err()
+            .map(|failure| failure.message.clone());
+        self.out.flush()
+    }
+
+    pub fn finish(&mut self) -> io::Result<()> {
+        match self.format {
+            Format::Json if self.records_written > 0 => writeln!(self.out, "\n]"),
+            Format::Json => writeln!(self.out, "]"),
+            _ => Ok(()),
+        }
+    }
+
+    fn report_plain(&mut self, record: &Record) -> io::Result<()> {
+        match self.plain_style {
+            PlainStyle::Sentences { show_input_path } => match &record.answer {
+                Ok(answer) if show_input_path => writeln!(
+                    self.out,
+                    "The solution to day {} part {} for {} is {}",
+                    record.day, record.part, record.input_path, answer
+                ),
+                Ok(answer) => writeln!(
+                    self.out,
+                    "The solution to day {} part {} is {}",
+                    record.day, record.part, answer
+                ),
+                Err(failure) => {
+                    eprintln!("error: {}: {}", record.input_path, failure.message);
+                    Ok(())
+                }
+            }
+            .and_then(|()| match (self.show_memory, record.parse_memory) {
+                (true, Some(parse_memory)) => {
+                    writeln!(self.out, "  Parsing used {}", parse_memory)
+                }
+                _ => Ok(()),
+            })
+            .and_then(|()| match (self.show_memory, record.solve_memory) {
+                (true, Some(solve_memory)) => {
+                    writeln!(self.out, "  Solving used {}", solve_memory)
+                }
+                _ => Ok(()),
+            }),
+            PlainStyle::Table => {
+                let same_input = self
+                    .last_input
+                    .as_ref()
+                    .is_some_and(|(day, path)| *day == record.day && *path == record.input_path);
+                let parse_column = match (same_input, record.parse_time) {
+                    (false, Some(parse_time)) => format!("{:?}", parse_time),
+                    _ => String::new(),
+                };
+                let solve_column = record
+                    .solve_time
+                    .map_or(String::new(), |solve_time| format!("{:?}", solve_time));
+                let answer_column = match &record.answer {
+                    Ok(answer) => answer.to_string(),
+                    Err(failure) => failure.status.to_string(),
+                };
+                let mut row = format!(
+                    "{:>3} {:>4}  {:>20}  {:>12}  {:>12}",
+                    record.day, record.part, answer_column, parse_column, solve_column
+                );
+                if let (true, Some(solve_memory)) = (self.show_memory, record.solve_memory) {
+                    row.push_str(&format!(
+                        "  {:>10}  {:>11}  {:>10}",
+                        Bytes(solve_memory.peak_bytes),
+                        solve_memory.allocations,
+                        Bytes(solve_memory.bytes_allocated)
+                    ));
+                }
+                writeln!(self.out, "{}", row.trim_end())?;
+                // Both parts fail the same way when the input is missing
+                // or malformed, so only explain that once.
+                if let Err(failure) = &record.answer {
+                    if !same_input || self.last_failure.as_ref() != Some(&failure.message) {
+                        eprintln!("error: {}: {}", record.input_path, failure.message);
+                    }
+                }
+                Ok(())
+            }
+        }
+    }
+
+    fn report_json(&mut self, record: &Record) -> io::Result<()> {
+        if self.records_written > 0 {
+            write!(self.out, ",")?;
+        }
+        write!(
+            self.out,
+            "\n  {{\"day\": {}, \"part\": {}, \"input\": {}, \"answer\": {}, \"answer_type\": {}, \
+             \"parse_seconds\": {}, \"solve_seconds\": {}, \"error\": {}",
+            record.day,
+            record.part,
+            JsonString(&record.input_path),
+            JsonOption(record.answer.as_ref().ok().map(JsonAnswer)),
+            JsonOption(
+                record
+                    .answer
+                    .as_ref()
+                    .ok()
+                    .map(|answer| JsonString(answer.answer_type()))
+            ),
+            JsonOption(record.parse_time.map(|time| time.as_secs_f64())),
+            JsonOption(record.solve_time.map(|time| time.as_secs_f64())),
+            JsonOption(
+                record
+                    .answer
+                    .as_ref()
+                    .err()
+                    .map(|failure| JsonString(&failure.message))
+            ),
+        )?;
+        if self.show_memory {
+            for (stage, memory) in [
+                ("parse", record.parse_memory),
+                ("solve", record.solve_memory),
+            ] {
+                write!(
+                    self.out,
+                    ", \"{stage}_peak_bytes\": {}, \"{stage}_allocations\": {}, \
+                     \"{stage}_bytes_allocated\": {}",
+                    JsonOption(memory.map(|memory| memory.peak_bytes)),
+                    JsonOption(memory.map(|memory| memory.allocations)),
+                    JsonOption(memory.map(|memory| memory.bytes_allocated)),
+                    stage = stage
+                )?;
+            }
+        }
+        write!(self.out, "}}")
+    }
+
+    fn report_csv(&mut self, record: &Record) -> io::Result<()> {
+        let seconds = |time: Option<Duration>| {
+            time.map_or(String::new(), |time| time.as_secs_f64().to_string())
+        };
+        let mut fields = vec![
+            record.day.to_string(),
+            record.part.to_string(),
+            record.input_path.clone(),
+            record
+                .answer
+                .as_ref()
+                .map_or(String::new(), |answer| answer.to_string()),
+            record
+                .answer
+                .as_ref()
+                .map_or("", |answer| answer.answer_type())
+                .to_string(),
+            seconds(record.parse_time),
+            seconds(record.solve_time),
+            record
+                .answer
+                .as_ref()
+                .err()
+                .map_or(String::new(), |failure| failure.message.clone()),
+        ];
+        if self.show_memory {
+            for memory in [record.parse_memory, record.solve_memory] {
+                fields.extend(
+                    [
+                        memory.map(|memory| memory.peak_bytes),
+                        memory.map(|memory| memory.allocations),
+                        memory.map(|memory| memory.bytes_allocated),
+                    ]
+                    .iter()
+                    .map(|count| count.map_or(String::new(), |count| count.to_string())),
+                );
+            }
+        }
+        let line = fields
+            .iter()
+            .map(|field| csv_field(field))
+            .collect::<Vec<String>>()
+            .join(",");
+        writeln!(self.out, "{}", line)
+    }
+}
+
+/// Displays a string as a quoted, escaped JSON string.
+struct JsonString<'a>(&'a str);
+
+impl fmt::Display for JsonString<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "\"")?;
+        for c in self.0.chars() {
+            match c {
+                '"' => write!(f, "\\\"")?,
+                '\\' => write!(f, "\\\\")?,
+                '\n' => write!(f, "\\n")?,
+                '\r' => write!(f, "\\r")?,
+                '\t' => write!(f, "\\t")?,
+                c if (c as u32) < 0x20 => write!(f, "\\u{:04x}", c as u32)?,
+                c => write!(f, "{}", c)?,
+            }
+        }
+        write!(f, "\"")
+    }
+}
+
+/// Displays an [`Answer::Integer`] as a JSON number and anything else
+/// as a JSON string, since big integers may not survive being read as
+/// a double.
+struct JsonAnswer<'a>(&'a Answer);
+
+impl fmt::Display for JsonAnswer<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self.0 {
+            Answer::Integer(integer) => write!(f, "{}", integer),
+            answer => write!(f, "{}", JsonString(&answer.to_string())),
+        }
+    }
+}
+
+/// Displays `None` as JSON `null`.
+struct JsonOption<T>(Option<T>);
+
+impl<T: fmt::Display> fmt::Display for JsonOption<T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match &self.0 {
+            Some(value) => write!(f, "{}", value),
+            None => write!(f, "null"),
+        }
+    }
+}
+
+/// Quotes `field` if it contains anything that would confuse a CSV
+/// reader.
+fn csv_field(field: &str) -> String {
+    match field.contains(&[',', '"', '\n', '\r'][..]) {
+        true => format!("\"{}\"", field.replace('"', "\"\"")),
+        false => field.to_string(),
+    }
+}
+
+#[cfg(test)]
+fn test_records() -> Vec<Record> {
+    vec![
+        Record {
+            day: 13,
+            part: 2,
+            input_path: String::from("input/13.txt"),
+            answer: Ok(Answer::from("HLBUBGFR")),
+            parse_time: Some(Duration::from_millis(2)),
+            solve_time: Some(Duration::from_millis(500)),
+            parse_memory: Some(MemoryUsage {
+                peak_bytes: 4096,
+                allocations: 3,
+                bytes_allocated: 5000,
+            }),
+            solve_memory: Some(MemoryUsage {
+                peak_bytes: 0,
+                allocations: 0,
+                bytes_allocated: 0,
+            }),
+        },
+        Record {
+            day: 5,
+            part: 1,
+            input_path: String::from("bad, \"input\".txt"),
+            answer: Err(Failure {
+                status: "PARSE ERROR",
+                message: String::from("expected \"x\"\n  |"),
+            }),
+            parse_time: None,
+            solve_time: None,
+            parse_memory: None,
+            solve_memory: None,
+        },
+    ]
+}
+
+#[cfg(test)]
+fn report_all(format: Format, records: &[Record]) -> String {
+    report_all_with_memory(format, records, false)
+}
+
+#[cfg(test)]
+fn report_all_with_memory(format: Format, records: &[Record], show_memory: bool) -> String {
+    let mut out = Vec::new();
+    let mut reporter = Reporter::new(&mut out, format, PlainStyle::Table).with_memory(show_memory);
+    reporter.begin().unwrap();
+    for record in records {
+        reporter.report(record).unwrap();
+    }
+    reporter.finish().unwrap();
+    String::from_utf8(out).unwrap()
+}
+
+#[test]
+fn test_json_format() {
+    assert_eq!(
+        report_all(Format::Json, &test_records()),
+        "[\n  {\"day\": 13, \"part\": 2, \"input\": \"input/13.txt\", \"answer\": \"HLBUBGFR\", \
+         \"answer_type\": \"text\", \"parse_seconds\": 0.002, \"solve_seconds\": 0.5, \"error\": null},\n  \
+         {\"day\": 5, \"part\": 1, \"input\": \"bad, \\\"input\\\".txt\", \"answer\": null, \
+         \"answer_type\": null, \"parse_seconds\": null, \"solve_seconds\": null, \
+         \"error\": \"expected \\\"x\\\"\\n  |\"}\n]\n"
+    );
+    assert_eq!(report_all(Format::Json, &[]), "[]\n");
+}
+
+#[test]
+fn test_json_answers() {
+    let mut record = test_records().remove(0);
+    record.answer = Ok(Answer::from(1228699515783640_u64));
+    let json = report_all(Format::Json, &[record.clone()]);
+    assert!(json.contains("\"answer\": 1228699515783640, \"answer_type\": \"integer\""));
+    record.answer = Ok(Answer::from(u64::MAX));
+    let json = report_all(Format::Json, &[record]);
+    assert!(json.contains("\"answer\": \"18446744073709551615\", \"answer_type\": \"big-integer\""));
+}
+
+#[test]
+fn test_csv_format() {
+    assert_eq!(
+        report_all(Format::Csv, &test_records()),
+        "day,part,input,answer,answer_type,parse_seconds,solve_seconds,error\n\
+         13,2,input/13.txt,HLBUBGFR,text,0.002,0.5,\n\
+         5,1,\"bad, \"\"input\"\".txt\",,,,,\"expected \"\"x\"\"\n  |\"\n"
+    );
+}
+
+#[test]
+fn test_memory_columns() {
+    let csv = report_all_with_memory(Format::Csv, &test_records(), true);
+    let lines = csv.lines().collect::<Vec<&str>>();
+    assert!(lines[0].ends_with(
+        ",error,parse_peak_bytes,parse_allocations,parse_bytes_allocated,\
+         solve_peak_bytes,solve_allocations,solve_bytes_allocated"
+    ));
+    assert!(lines[1].ends_with(",0.5,,4096,3,5000,0,0,0"));
+    assert!(csv.ends_with("  |\",,,,,,\n"));
+
+    let json = report_all_with_memory(Format::Json, &test_records(), true);
+    assert!(json.contains(
+        "\"error\": null, \"parse_peak_bytes\": 4096, \"parse_allocations\": 3, \
+         \"parse_bytes_allocated\": 5000, \"solve_peak_bytes\": 0"
+    ));
+    assert!(json.contains("\"solve_bytes_allocated\": null}"));
+
+    let table = report_all_with_memory(Format::Plain, &test_records(), true);
+    assert!(table
+        .lines()
+        .next()
+        .unwrap()
+        .ends_with("Peak  Allocations   Allocated"));
+    assert!(table
+        .lines()
+        .nth(1)
+        .unwrap()
+        .ends_with("500ms         0 B            0         0 B"));
+}
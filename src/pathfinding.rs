@@ -0,0 +1,207 @@
+use std::cmp::Reverse;
+use std::collections::hash_map::Entry;
+use std::collections::{BinaryHeap, HashMap};
+use std::hash::Hash;
+
+use crate::cancel;
+use crate::grid::{Grid, Neighborhood};
+
+/// A cheapest path, from the start node to a goal node inclusive.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Path<N> {
+    pub cost: u64,
+    pub nodes: Vec<N>,
+}
+
+/// Finds a cheapest path from `start` to any node for which `is_goal`
+/// holds, where `successors` gives the nodes reachable from a node and
+/// the cost of moving to each. Returns `None` if no goal is reachable.
+pub fn dijkstra<N, I>(
+    start: N,
+    successors: impl FnMut(&N) -> I,
+    is_goal: impl FnMut(&N) -> bool,
+) -> Option<Path<N>>
+where
+    N: Clone + Eq + Hash,
+    I: IntoIterator<Item = (N, u64)>,
+{
+    astar(start, successors, |_| 0, is_goal)
+}
+
+/// Like [`dijkstra`], but visits the nodes that `heuristic` estimates
+/// are closest to a goal first. The path is only guaranteed to be a
+/// cheapest one if `heuristic` never overestimates the remaining cost.
+///
+/// Calls [`cancel::checkpoint`] for every node it visits. Day 15 has no
+/// loops of its own, so this is where it stops under a `--timeout`.
+pub fn astar<N, I>(
+    start: N,
+    mut successors: impl FnMut(&N) -> I,
+    mut heuristic: impl FnMut(&N) -> u64,
+    mut is_goal: impl FnMut(&N) -> bool,
+) -> Option<Path<N>>
+where
+    N: Clone + Eq + Hash,
+    I: IntoIterator<Item = (N, u64)>,
+{
+    // Nodes are referred to by their index in `nodes`, so that the heap
+    // does not need them to be `Ord`.
+    let mut nodes = vec![start.clone()];
+    let mut indices = HashMap::from([(start.clone(), 0)]);
+    // (cheapest cost found so far, index of the node it came from)
+    let mut best = vec![(0, usize::MAX)];
+    let mut frontier = BinaryHeap::from([Reverse((heuristic(&start), 0, 0))]);
+    while let Some(Reverse((_, cost, index))) = frontier.pop() {
+        cancel::checkpoint();
+        if cost > best[index].0 {
+            // A cheaper way here has already been visited.
+            continue;
+        }
+        if is_goal(&nodes[index]) {
+            let mut path = vec![nodes[index].clone()];
+            let mut step = index;
+            while best[step].1 != usize::MAX {
+                step = best[step].1;
+                path.push(nodes[step].clone());
+            }
+            path.reverse();
+            return Some(Path { cost, nodes: path });
+        }
+        for (successor, step_cost) in successors(&nodes[index]) {
+            let successor_cost = cost + step_cost;
+            let successor_index = match indices.entry(successor) {
+                Entry::Occupied(entry) => {
+                    let successor_index = *entry.get();
+                    if successor_cost >= best[successor_index].0 {
+                        continue;
+                    }
+                    best[successor_index] = (successor_cost, index);
+                    successor_index
+                }
+                Entry::Vacant(entry) => {
+                    nodes.push(entry.key().clone());
+                    best.push((successor_cost, index));
+                    *entry.insert(nodes.len() - 1)
+                }
+            };
+            let estimate = successor_cost + heuristic(&nodes[successor_index]);
+            frontier.push(Reverse((estimate, successor_cost, successor_index)));
+        }
+    }
+    None
+}
+
+/// Finds a cheapest path across `grid` from `start` to `goal`, moving
+/// up, down, left and right. Moving onto a cell costs `entry_cost` of
+/// that cell, and cells for which it is `None` cannot be entered.
+pub fn shortest_path_on_grid<T>(
+    grid: &Grid<T>,
+    start: (usize, usize),
+    goal: (usize, usize),
+    entry_cost: impl Fn(&T) -> Option<u64>,
+) -> Option<Path<(usize, usize)>> {
+    let cheapest_entry_cost = grid.iter().filter_map(&entry_cost).min().unwrap_or(0);
+    shortest_path_in_rectangle(
+        (grid.width(), grid.height()),
+        Neighborhood::Four,
+        start,
+        goal,
+        |location| entry_cost(&grid[location]),
+        cheapest_entry_cost,
+    )
+}
+
+/// Finds a cheapest path from `start` to `goal` within a `width` by
+/// `height` rectangle, moving between neighbors in `neighborhood`.
+/// Moving onto a location costs `entry_cost` of that location, and
+/// locations for which it is `None` cannot be entered. Nothing needs to
+/// be stored for locations the search never reaches.
+///
+/// Uses [`astar`], with the fewest moves to the goal times
+/// `cheapest_entry_cost` as the heuristic, so `cheapest_entry_cost`
+/// must be no more than any entry cost.
+pub fn shortest_path_in_rectangle(
+    (width, height): (usize, usize),
+    neighborhood: Neighborhood,
+    start: (usize, usize),
+    goal: (usize, usize),
+    entry_cost: impl Fn((usize, usize)) -> Option<u64>,
+    cheapest_entry_cost: u64,
+) -> Option<Path<(usize, usize)>> {
+    astar(
+        start,
+        |&location| {
+            neighborhood
+                .neighbors(location, width, height)
+                .filter_map(|neighbor| Some((neighbor, entry_cost(neighbor)?)))
+                .collect::<Vec<_>>()
+        },
+        |&location| neighborhood.min_moves(location, goal) * cheapest_entry_cost,
+        |&location| location == goal,
+    )
+}
+
+#[test]
+fn test_dijkstra_and_astar() {
+    let edges = [
+        ("a", "b", 7),
+        ("a", "c", 2),
+        ("c", "b", 3),
+        ("b", "d", 1),
+        ("e", "a", 1),
+    ];
+    let successors = |node: &&str| {
+        edges
+            .iter()
+            .filter(|(from, _, _)| from == node)
+            .map(|(_, to, cost)| (*to, *cost))
+            .collect::<Vec<_>>()
+    };
+    let expected = Path {
+        cost: 6,
+        nodes: vec!["a", "c", "b", "d"],
+    };
+    assert_eq!(
+        dijkstra("a", successors, |node| *node == "d"),
+        Some(expected)
+    );
+    assert_eq!(dijkstra("a", successors, |node| *node == "e"), None);
+    assert_eq!(
+        dijkstra("a", successors, |node| *node == "a").map(|path| path.cost),
+        Some(0)
+    );
+}
+
+#[test]
+fn test_shortest_path_on_grid() {
+    let grid = Grid::from_rows(vec![
+        vec![1, 1, 1, 1],
+        vec![0, 0, 0, 1],
+        vec![1, 1, 1, 1],
+        vec![1, 0, 0, 0],
+    ])
+    .unwrap();
+    let entry_cost = |cell: &u64| match cell {
+        0 => None,
+        cell => Some(*cell),
+    };
+    let path = shortest_path_on_grid(&grid, (0, 0), (0, 3), entry_cost).unwrap();
+    assert_eq!(path.cost, 9);
+    assert_eq!(path.nodes.len(), 10);
+    assert_eq!(
+        shortest_path_on_grid(&grid, (0, 0), (3, 3), entry_cost),
+        None
+    );
+}
+
+#[test]
+fn test_shortest_path_in_rectangle() {
+    let path =
+        shortest_path_in_rectangle((5, 5), Neighborhood::Knight, (0, 0), (4, 4), |_| Some(2), 2)
+            .unwrap();
+    assert_eq!(path.cost, 8);
+    assert_eq!(path.nodes.len(), 5);
+    let path =
+        shortest_path_in_rectangle((3, 3), Neighborhood::Knight, (0, 0), (1, 1), |_| Some(1), 1);
+    assert_eq!(path, None);
+}
@@ -0,0 +1,105 @@
+use std::collections::BTreeMap;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::mpsc;
+use std::thread;
+
+/// Calls `job` on each of `items` using up to `jobs` worker threads, and
+/// hands the results to `emit` in the same order as `items`. Each
+/// result is emitted as soon as it and every result before it are
+/// ready.
+///
+/// Stops handing out items and returns the error as soon as `emit`
+/// fails. With one job, everything runs on the calling thread.
+pub fn run_ordered<T, R, E>(
+    jobs: usize,
+    items: &[T],
+    job: impl Fn(&T) -> R + Sync,
+    mut emit: impl FnMut(R) -> Result<(), E>,
+) -> Result<(), E>
+where
+    T: Sync,
+    R: Send,
+{
+    let jobs = jobs.clamp(1, items.len().max(1));
+    if jobs == 1 {
+        for item in items {
+            emit(job(item))?;
+        }
+        return Ok(());
+    }
+
+    let next_item = AtomicUsize::new(0);
+    let (sender, receiver) = mpsc::channel();
+    thread::scope(|scope| {
+        for _ in 0..jobs {
+            let sender = sender.clone();
+            let (next_item, job) = (&next_item, &job);
+            scope.spawn(move || loop {
+                let index = next_item.fetch_add(1, Ordering::Relaxed);
+                if index >= items.len() {
+                    break;
+                }
+                // Sending fails once emitting has failed and the
+                // receiver is gone.
+                if sender.send((index, job(&items[index]))).is_err() {
+                    break;
+                }
+            });
+        }
+        drop(sender);
+
+        let mut finished = BTreeMap::new();
+        let mut next_to_emit = 0;
+        for (index, result) in receiver {
+            finished.insert(index, result);
+            while let Some(result) = finished.remove(&next_to_emit) {
+                emit(result)?;
+                next_to_emit += 1;
+            }
+        }
+        Ok(())
+    })
+}
+
+#[test]
+fn test_run_ordered_keeps_order() {
+    use std::time::Duration;
+
+    let delays = [30, 0, 20, 5, 10, 0, 25];
+    for jobs in [1, 3, 16] {
+        let mut results = Vec::new();
+        run_ordered(
+            jobs,
+            &delays,
+            |delay| {
+                thread::sleep(Duration::from_millis(*delay));
+                *delay
+            },
+            |delay| -> Result<(), ()> {
+                results.push(delay);
+                Ok(())
+            },
+        )
+        .unwrap();
+        assert_eq!(results, delays);
+    }
+}
+
+#[test]
+fn test_run_ordered_stops_on_error() {
+    let mut emitted = 0;
+    let result = run_ordered(
+        2,
+        &[1, 2, 3, 4],
+        |number| *number,
+        |number| {
+            emitted += 1;
+            match number {
+                2 => Err("stop"),
+                _ => Ok(()),
+            }
+        },
+    );
+    assert_eq!(result, Err("stop"));
+    assert_eq!(emitted, 2);
+}
@@ -0,0 +1,316 @@
+use std::any::Any;
+use std::io::{self, BufRead, Write};
+use std::panic::{self, AssertUnwindSafe};
+use std::time::Instant;
+
+use crate::day09::HeightMap;
+use crate::day11::OctopusMap;
+use crate::day16::{self, Packet};
+use crate::day22::RebootStep;
+use crate::inputs;
+use crate::runner;
+use crate::solver::{self, DynSolver};
+
+/// Commands that only work once a particular day is loaded, as
+/// `(day, usage, description)`.
+pub const DAY_COMMANDS: &[(u8, &str, &str)] = &[
+    (9, "basin at X,Y", "size of the basin containing X,Y"),
+    (
+        11,
+        "octopus step N",
+        "simulate N more steps and show the octopodes",
+    ),
+    (16, "packet tree", "show the packet and its sub-packets"),
+    (22, "cuboid count", "count the cuboids turned on and off"),
+];
+
+const HELP: &str = "\
+load DAY [FILE]          parse input/{DAY}.txt, or FILE
+load DAY variant NAME    parse input/{DAY}_{NAME}.txt
+part 1|2                 solve a part of the loaded input
+help                     list commands
+quit                     leave";
+
+/// What to do after a command.
+#[derive(Debug, PartialEq)]
+pub enum Response {
+    /// Print this on stdout.
+    Output(String),
+    /// Print this on stderr.
+    Error(String),
+    Quit,
+}
+
+/// An input parsed by its day's solver.
+struct Loaded {
+    solver: &'static dyn DynSolver,
+    input_path: String,
+    input: Box<dyn Any>,
+    /// State kept by day commands, e.g. the octopodes stepped by
+    /// `octopus step`, so that solving still uses the input as parsed.
+    scratch: Option<Box<dyn Any>>,
+}
+
+/// The state of a REPL session: the input loaded, if any.
+#[derive(Default)]
+pub struct Repl {
+    loaded: Option<Loaded>,
+}
+
+impl Repl {
+    pub fn new() -> Self {
+        Repl::default()
+    }
+
+    /// The prompt to show before reading a command.
+    pub fn prompt(&self) -> String {
+        match &self.loaded {
+            Some(loaded) => format!("day {}> ", loaded.solver.day()),
+            None => String::from("> "),
+        }
+    }
+
+    /// Runs one line of input.
+    pub fn execute(&mut self, line: &str) -> Response {
+        let words = line.split_whitespace().collect::<Vec<&str>>();
+        match words[..] {
+            [] => Response::Output(String::new()),
+            ["quit"] | ["exit"] => Response::Quit,
+            ["help"] => Response::Output(self.help()),
+            ["load", day] => self.load(day, None),
+            ["load", day, input_path] => self.load(day, Some(input_path.to_string())),
+            ["load", day, "variant", variant] => {
+                let input_path = day
+                    .parse::<u8>()
+                    .map(|day| inputs::input_path(day, Some(variant)));
+                match input_path {
+                    Ok(input_path) => self.load(day, Some(input_path)),
+                    Err(_) => Response::Error(format!("not a day: {}", day)),
+                }
+            }
+            ["part", part] | ["solve", part] => self.solve(part),
+            _ => match &mut self.loaded {
+                Some(loaded) => day_command(
+                    loaded.solver.day(),
+                    loaded.input.as_ref(),
+                    &mut loaded.scratch,
+                    &words,
+                )
+                .unwrap_or_else(|| {
+                    Response::Error(format!(
+                        "unknown command for day {}: {} (try help)",
+                        loaded.solver.day(),
+                        line.trim()
+                    ))
+                }),
+                None => Response::Error(format!("unknown command: {} (try help)", line.trim())),
+            },
+        }
+    }
+
+    fn help(&self) -> String {
+        let mut help = String::from(HELP);
+        for (day, usage, description) in DAY_COMMANDS {
+            let available = match &self.loaded {
+                Some(loaded) => loaded.solver.day() == *day,
+                None => true,
+            };
+            if available {
+                help.push_str(&format!("\n{:<24} {} (day {})", usage, description, day));
+            }
+        }
+        help
+    }
+
+    fn load(&mut self, day: &str, input_path: Option<String>) -> Response {
+        let day = match day.parse::<u8>() {
+            Ok(day) => day,
+            Err(_) => return Response::Error(format!("not a day: {}", day)),
+        };
+        let solver = match solver::find_solver(day) {
+            Some(solver) => solver,
+            None => return Response::Error(format!("day {} is not implemented", day)),
+        };
+        let input_path = input_path.unwrap_or_else(|| inputs::input_path(day, None));
+        let puzzle_input = match runner::read_puzzle_input(&input_path) {
+            Ok(puzzle_input) => puzzle_input,
+            Err(error) => return Response::Error(format!("{}: {}", input_path, error)),
+        };
+        let start = Instant::now();
+        match solver.parse(puzzle_input) {
+            Ok(input) => {
+                let message = format!(
+                    "loaded day {} from {} in {:?}",
+                    day,
+                    input_path,
+                    start.elapsed()
+                );
+                self.loaded = Some(Loaded {
+                    solver,
+                    input_path,
+                    input,
+                    scratch: None,
+                });
+                Response::Output(message)
+            }
+            Err(parse_error) => Response::Error(format!(
+                "{}: {}\n{}",
+                input_path,
+                parse_error,
+                parse_error.snippet()
+            )),
+        }
+    }
+
+    fn solve(&self, part: &str) -> Response {
+        let loaded = match &self.loaded {
+            Some(loaded) => loaded,
+            None => return Response::Error(String::from("nothing loaded (try load DAY)")),
+        };
+        let part = match part {
+            "1" => 1,
+            "2" => 2,
+            _ => return Response::Error(format!("not a part: {}", part)),
+        };
+        let start = Instant::now();
+        let answer = panic::catch_unwind(AssertUnwindSafe(|| {
+            loaded.solver.solve(loaded.input.as_ref(), part)
+        }));
+        let solve_time = start.elapsed();
+        match answer {
+            Ok(Ok(answer)) => Response::Output(format!(
+                "day {} part {} ({}): {} in {:?}",
+                loaded.solver.day(),
+                part,
+                loaded.input_path,
+                answer,
+                solve_time
+            )),
+            Ok(Err(error)) => Response::Error(error.to_string()),
+            Err(_) => Response::Error(String::from("the solver panicked")),
+        }
+    }
+}
+
+/// Runs a command from [`DAY_COMMANDS`] on an input for `day`, or
+/// returns `None` if there is no such command. Commands that change
+/// what they inspect work on their own copy in `scratch`.
+fn day_command(
+    day: u8,
+    input: &dyn Any,
+    scratch: &mut Option<Box<dyn Any>>,
+    words: &[&str],
+) -> Option<Response> {
+    let response = match (day, words) {
+        (9, ["basin", "at", location]) => {
+            let height_map = input.downcast_ref::<HeightMap>()?;
+            match parse_location(location) {
+                Some((x, y)) => match height_map.basin_size_at(x, y) {
+                    Some(basin_size) => Response::Output(format!(
+                        "the basin at {},{} has size {}",
+                        x, y, basin_size
+                    )),
+                    None => Response::Output(format!("{},{} is not in a basin", x, y)),
+                },
+                None => Response::Error(format!("not a location: {} (expected X,Y)", location)),
+            }
+        }
+        (11, ["octopus", "step", steps]) => {
+            let octopus_map = input.downcast_ref::<OctopusMap>()?;
+            match steps.parse::<u64>() {
+                Ok(steps) => {
+                    let octopus_map = scratch
+                        .get_or_insert_with(|| Box::new(octopus_map.clone()))
+                        .downcast_mut::<OctopusMap>()?;
+                    let flashes = octopus_map.simulate_steps(steps);
+                    Response::Output(format!("{}{} flashes", octopus_map, flashes))
+                }
+                Err(_) => Response::Error(format!("not a number of steps: {}", steps)),
+            }
+        }
+        (16, ["packet", "tree"]) => {
+            let packet = input.downcast_ref::<Packet>()?;
+            Response::Output(day16::format_packet_tree(packet).trim_end().to_string())
+        }
+        (22, ["cuboid", "count"]) => {
+            let reboot_steps = input.downcast_ref::<Vec<RebootStep>>()?;
+            let turned_on = reboot_steps.iter().filter(|step| step.turn_on).count();
+            Response::Output(format!(
+                "{} cuboids: {} turned on, {} turned off",
+                reboot_steps.len(),
+                turned_on,
+                reboot_steps.len() - turned_on
+            ))
+        }
+        _ => return None,
+    };
+    Some(response)
+}
+
+fn parse_location(location: &str) -> Option<(usize, usize)> {
+    let (x, y) = location.split_once(',')?;
+    Some((x.parse().ok()?, y.parse().ok()?))
+}
+
+/// Reads commands from stdin until `quit` or the end of input.
+pub fn run() -> io::Result<()> {
+    let mut repl = Repl::new();
+    let stdin = io::stdin();
+    let mut lines = stdin.lock().lines();
+    loop {
+        print!("{}", repl.prompt());
+        io::stdout().flush()?;
+        let line = match lines.next() {
+            Some(line) => line?,
+            None => break,
+        };
+        match repl.execute(&line) {
+            Response::Output(output) if output.is_empty() => {}
+            Response::Output(output) => println!("{}", output),
+            Response::Error(message) => eprintln!("error: {}", message),
+            Response::Quit => break,
+        }
+    }
+    Ok(())
+}
+
+#[test]
+fn test_repl_commands() {
+    let mut repl = Repl::new();
+    assert!(matches!(repl.execute("part 1"), Response::Error(_)));
+    assert!(matches!(
+        repl.execute("load 11 variant sample"),
+        Response::Output(_)
+    ));
+    assert_eq!(repl.prompt(), "day 11> ");
+    match repl.execute("part 1") {
+        Response::Output(output) => assert!(output.contains(": 1656 in ")),
+        response => panic!("unexpected response {:?}", response),
+    }
+    match repl.execute("octopus step 2") {
+        Response::Output(output) => assert!(output.ends_with("\n35 flashes")),
+        response => panic!("unexpected response {:?}", response),
+    }
+    match repl.execute("octopus step 1") {
+        Response::Output(output) => assert!(output.ends_with("\n45 flashes")),
+        response => panic!("unexpected response {:?}", response),
+    }
+    // Stepping works on a copy, so solving still uses the input as loaded.
+    match repl.execute("part 1") {
+        Response::Output(output) => assert!(output.contains(": 1656 in ")),
+        response => panic!("unexpected response {:?}", response),
+    }
+    assert!(matches!(repl.execute("packet tree"), Response::Error(_)));
+
+    repl.execute("load 9");
+    assert_eq!(
+        repl.execute("basin at 0,0"),
+        Response::Output(String::from("0,0 is not in a basin"))
+    );
+    assert_eq!(
+        repl.execute("basin at 1,1"),
+        Response::Output(String::from("the basin at 1,1 has size 36"))
+    );
+    assert!(matches!(repl.execute("basin at 0"), Response::Error(_)));
+    assert_eq!(repl.execute("quit"), Response::Quit);
+}
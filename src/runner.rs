@@ -0,0 +1,684 @@
+use std::any::Any;
+use std::convert::Infallible;
+use std::fs;
+use std::io::{self, Read, Write};
+use std::panic::{self, AssertUnwindSafe};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc::{self, RecvTimeoutError};
+use std::sync::Arc;
+use std::thread;
+use std::time::{Duration, Instant};
+
+use crate::bench::{self, Measurement};
+use crate::cancel;
+use crate::error::Error;
+use crate::inputs;
+use crate::manifest::ExpectedAnswer;
+use crate::mem::{self, MemoryUsage};
+use crate::output::{Failure, Record, Reporter};
+use crate::pool;
+use crate::solver;
+use crate::solver::{DynSolver, SOLVERS};
+
+/// Runs `f`, returning its result, how long it took and the memory it
+/// used, or `None` if it panicked.
+fn measure_and_catch_panic<T>(f: impl FnOnce() -> T) -> Option<(T, Duration, MemoryUsage)> {
+    let start = Instant::now();
+    let (result, memory) = mem::measure(|| panic::catch_unwind(AssertUnwindSafe(f)));
+    Some((result.ok()?, start.elapsed(), memory))
+}
+
+/// Reads puzzle input from the file at `path`, or from stdin if `path`
+/// is `-`.
+pub fn read_puzzle_input(path: &str) -> io::Result<String> {
+    match path {
+        "-" => {
+            let mut puzzle_input = String::new();
+            io::stdin().read_to_string(&mut puzzle_input)?;
+            Ok(puzzle_input)
+        }
+        _ => fs::read_to_string(path),
+    }
+}
+
+/// Reads the input at `input_path`, parses it with `solver` and solves
+/// each of `parts`, handing a [`Record`] for each part to `emit` as
+/// soon as it is ready.
+fn solve_input(
+    solver: &dyn DynSolver,
+    parts: &[u8],
+    input_path: &str,
+    emit: &mut dyn FnMut(Record),
+) {
+    let day = solver.day();
+    let record = |part: u8,
+                  answer,
+                  parse: Option<(Duration, MemoryUsage)>,
+                  solve: Option<(Duration, MemoryUsage)>| Record {
+        day,
+        part,
+        input_path: input_path.to_string(),
+        answer,
+        parse_time: parse.map(|(time, _)| time),
+        solve_time: solve.map(|(time, _)| time),
+        parse_memory: parse.map(|(_, memory)| memory),
+        solve_memory: solve.map(|(_, memory)| memory),
+    };
+    let mut fail_every_part = |status: &'static str, message: String| {
+        for part in parts {
+            let failure = Failure {
+                status,
+                message: message.clone(),
+            };
+            emit(record(*part, Err(failure), None, None));
+        }
+    };
+
+    let puzzle_input = match read_puzzle_input(input_path) {
+        Ok(puzzle_input) => puzzle_input,
+        Err(error) => return fail_every_part("NO INPUT", error.to_string()),
+    };
+    let (input, parse): (Box<dyn Any>, _) =
+        match measure_and_catch_panic(|| solver.parse(puzzle_input)) {
+            Some((Ok(input), parse_time, parse_memory)) => (input, (parse_time, parse_memory)),
+            Some((Err(parse_error), _, _)) => {
+                let message = format!("{}\n{}", parse_error, parse_error.snippet());
+                return fail_every_part("PARSE ERROR", message);
+            }
+            None => {
+                let message = String::from("the parser panicked");
+                return fail_every_part("PANICKED (parse)", message);
+            }
+        };
+    for part in parts {
+        let (answer, solve) = match measure_and_catch_panic(|| solver.solve(input.as_ref(), *part))
+        {
+            Some((Ok(answer), solve_time, solve_memory)) => {
+                (Ok(answer), Some((solve_time, solve_memory)))
+            }
+            Some((Err(error), solve_time, solve_memory)) => {
+                let failure = Failure {
+                    status: "ERROR",
+                    message: error.to_string(),
+                };
+                (Err(failure), Some((solve_time, solve_memory)))
+            }
+            None => {
+                let failure = Failure {
+                    status: "PANICKED",
+                    message: String::from("the solver panicked"),
+                };
+                (Err(failure), None)
+            }
+        };
+        emit(record(*part, answer, Some(parse), solve));
+    }
+}
+
+/// Reads the input at `input_path`, parses it with `solver` and solves
+/// each of `parts`, returning a [`Record`] for each part.
+///
+/// With a `timeout`, the work happens on a worker thread and any part
+/// that takes longer than `timeout` (including reading and parsing the
+/// input, for the first part) is reported as TIMEOUT along with every
+/// part after it. The worker is then asked to stop at its next
+/// [`cancel::checkpoint`]; solvers without checkpoints keep running in
+/// the background until they finish.
+pub fn run_input(
+    solver: &'static dyn DynSolver,
+    parts: &[u8],
+    input_path: &str,
+    timeout: Option<Duration>,
+) -> Vec<Record> {
+    let mut records = Vec::new();
+    let timeout = match timeout {
+        Some(timeout) => timeout,
+        None => {
+            solve_input(solver, parts, input_path, &mut |record| {
+                records.push(record)
+            });
+            return records;
+        }
+    };
+
+    let cancellation_flag = Arc::new(AtomicBool::new(false));
+    let (sender, receiver) = mpsc::channel();
+    {
+        let cancellation_flag = Arc::clone(&cancellation_flag);
+        let parts = parts.to_vec();
+        let input_path = input_path.to_string();
+        thread::spawn(move || {
+            cancel::set_cancellation_flag(cancellation_flag);
+            solve_input(solver, &parts, &input_path, &mut |record| {
+                // The runner stops listening once it gives up.
+                let _ = sender.send(record);
+            });
+        });
+    }
+    for (index, part) in parts.iter().enumerate() {
+        let failure = match receiver.recv_timeout(timeout) {
+            Ok(record) => {
+                records.push(record);
+                continue;
+            }
+            Err(RecvTimeoutError::Timeout) => Failure {
+                status: "TIMEOUT",
+                message: format!("gave up on part {} after {:?}", part, timeout),
+            },
+            Err(RecvTimeoutError::Disconnected) => Failure {
+                status: "PANICKED",
+                message: String::from("the worker thread panicked"),
+            },
+        };
+        cancellation_flag.store(true, Ordering::Relaxed);
+        for part in parts[index..].iter() {
+            records.push(Record {
+                day: solver.day(),
+                part: *part,
+                input_path: input_path.to_string(),
+                answer: Err(failure.clone()),
+                parse_time: None,
+                solve_time: None,
+                parse_memory: None,
+                solve_memory: None,
+            });
+        }
+        break;
+    }
+    records
+}
+
+/// How to run a batch of solvers.
+#[derive(Debug, Clone, Copy)]
+pub struct RunOptions {
+    /// How long each part may take before it is reported as TIMEOUT.
+    pub timeout: Option<Duration>,
+    /// How many parts to solve at once.
+    pub jobs: usize,
+}
+
+impl Default for RunOptions {
+    fn default() -> Self {
+        RunOptions {
+            timeout: None,
+            jobs: 1,
+        }
+    }
+}
+
+/// Solves both parts of every implemented day using `input/{day}.txt`,
+/// handing each [`Record`] to `reporter` in order of day and part.
+///
+/// Each part is a separate job, so with more than one job the input is
+/// read and parsed once per part, and every part gets its own timings.
+///
+/// Returns false if any day's input was missing or malformed, or any
+/// solver failed or panicked.
+pub fn run_all<W: Write>(reporter: &mut Reporter<W>, options: &RunOptions) -> io::Result<bool> {
+    let mut all_succeeded = true;
+    let jobs = SOLVERS
+        .iter()
+        .flat_map(|solver| {
+            let input_path = inputs::input_path(solver.day(), None);
+            [1, 2]
+                .iter()
+                .map(move |part| (*solver, *part, input_path.clone()))
+        })
+        .collect::<Vec<(&'static dyn DynSolver, u8, String)>>();
+    reporter.begin()?;
+    pool::run_ordered(
+        options.jobs,
+        &jobs,
+        |(solver, part, input_path)| run_input(*solver, &[*part], input_path, options.timeout),
+        |records| -> io::Result<()> {
+            for record in records {
+                all_succeeded &= record.answer.is_ok();
+                reporter.report(&record)?;
+            }
+            Ok(())
+        },
+    )?;
+    reporter.finish()?;
+    Ok(all_succeeded)
+}
+
+/// Solves `part` of `solver`'s puzzle for each of `input_paths` on
+/// `options.jobs` threads, handing each [`Record`] to `reporter` in the
+/// order of `input_paths`.
+///
+/// Returns false if any input was missing or malformed, or the solver
+/// failed or panicked on any of them.
+pub fn run_inputs<W: Write>(
+    reporter: &mut Reporter<W>,
+    solver: &'static dyn DynSolver,
+    part: u8,
+    input_paths: &[String],
+    options: &RunOptions,
+) -> io::Result<bool> {
+    let mut all_succeeded = true;
+    reporter.begin()?;
+    pool::run_ordered(
+        options.jobs,
+        input_paths,
+        |input_path| run_input(solver, &[part], input_path, options.timeout),
+        |records| -> io::Result<()> {
+            for record in records {
+                all_succeeded &= record.answer.is_ok();
+                reporter.report(&record)?;
+            }
+            Ok(())
+        },
+    )?;
+    reporter.finish()?;
+    Ok(all_succeeded)
+}
+
+/// Solves every entry of a manifest and prints PASS, FAIL or MISSING
+/// for each, followed by a summary. An entry is MISSING if its input
+/// file cannot be read or its day has no solver.
+///
+/// Entries are solved on `options.jobs` threads but always printed in
+/// manifest order.
+///
+/// Returns false if any entry failed.
+pub fn run_check(entries: &[ExpectedAnswer], options: &RunOptions) -> bool {
+    let (mut passed, mut failed, mut missing) = (0, 0, 0);
+    let solve_entry = |entry: &ExpectedAnswer| {
+        solver::find_solver(entry.day).map(|solver| {
+            run_input(solver, &[entry.part], &entry.input_path, options.timeout).remove(0)
+        })
+    };
+    let mut report_entry =
+        |(entry, record): (&ExpectedAnswer, Option<Record>)| -> Result<(), Infallible> {
+            let label = format!(
+                "day {} part {} ({})",
+                entry.day, entry.part, entry.input_path
+            );
+            let record = match record {
+                Some(record) => record,
+                None => {
+                    println!("MISSING {}: no solver for day {}", label, entry.day);
+                    missing += 1;
+                    return Ok(());
+                }
+            };
+            match record.answer {
+                Ok(answer) if answer == entry.answer => {
+                    println!("PASS    {}: {}", label, answer);
+                    passed += 1;
+                }
+                Ok(answer) => {
+                    println!(
+                        "FAIL    {}: expected {}, got {}",
+                        label, entry.answer, answer
+                    );
+                    failed += 1;
+                }
+                Err(failure) if failure.status == "NO INPUT" => {
+                    println!("MISSING {}: {}", label, failure.message);
+                    missing += 1;
+                }
+                Err(failure) => {
+                    println!(
+                        "FAIL    {}: expected {}, got {}",
+                        label, entry.answer, failure.status
+                    );
+                    eprintln!("error: {}: {}", entry.input_path, failure.message);
+                    failed += 1;
+                }
+            }
+            Ok(())
+        };
+    let entries = entries.iter().collect::<Vec<&ExpectedAnswer>>();
+    pool::run_ordered(
+        options.jobs,
+        &entries,
+        |entry| (*entry, solve_entry(entry)),
+        &mut report_entry,
+    )
+    .unwrap_or_else(|never| match never {});
+    println!("{} passed, {} failed, {} missing", passed, failed, missing);
+    failed == 0
+}
+
+/// Benchmarks `part` of `solver` on each of `input_paths` and prints
+/// the timings, compared with `baseline` if one is given.
+///
+/// Returns the measurements, and false if any input could not be read
+/// or solved or any benchmark regressed against `baseline`.
+/// Diagnostics are printed to stderr.
+pub fn run_bench(
+    solver: &dyn DynSolver,
+    part: u8,
+    input_paths: &[String],
+    warm_up_runs: usize,
+    runs: usize,
+    baseline: Option<&[Measurement]>,
+) -> (Vec<Measurement>, bool) {
+    let mut measurements = Vec::new();
+    let mut succeeded = true;
+    for input_path in input_paths {
+        let result = read_puzzle_input(input_path)
+            .map_err(|error| error.to_string())
+            .and_then(|puzzle_input| {
+                bench::bench(solver, part, input_path, &puzzle_input, warm_up_runs, runs).map_err(
+                    |error| match &error {
+                        Error::Parse(parse_error) => {
+                            format!("{}\n{}", parse_error, parse_error.snippet())
+                        }
+                        _ => error.to_string(),
+                    },
+                )
+            });
+        let new_measurements = match result {
+            Ok(new_measurements) => new_measurements,
+            Err(message) => {
+                eprintln!("error: {}: {}", input_path, message);
+                succeeded = false;
+                continue;
+            }
+        };
+        println!(
+            "Day {} part {} ({}), {} runs after {} warm-up runs",
+            solver.day(),
+            part,
+            input_path,
+            runs,
+            warm_up_runs
+        );
+        println!(
+            "{:>5}  {:>12}  {:>12}  {:>12}  {:>12}  vs. baseline",
+            "", "min", "median", "mean", "stddev"
+        );
+        for measurement in new_measurements.iter() {
+            let stats = &measurement.stats;
+            let comparison = baseline.and_then(|baseline| bench::compare(measurement, baseline));
+            let comparison_column = match comparison {
+                Some(comparison) => format!(
+                    "{:+.1}% (median was {:?}){}",
+                    comparison.change_percent,
+                    comparison.baseline_median,
+                    match comparison.is_regression() {
+                        true => " REGRESSION",
+                        false => "",
+                    }
+                ),
+                None if baseline.is_some() => String::from("no baseline"),
+                None => String::new(),
+            };
+            succeeded &= !comparison.is_some_and(|comparison| comparison.is_regression());
+            let row = format!(
+                "{:>5}  {:>12}  {:>12}  {:>12}  {:>12}  {}",
+                measurement.stage.to_string(),
+                format!("{:?}", stats.min),
+                format!("{:?}", stats.median),
+                format!("{:?}", stats.mean),
+                format!("{:?}", stats.stddev),
+                comparison_column
+            );
+            println!("{}", row.trim_end());
+        }
+        measurements.extend(new_measurements);
+    }
+    (measurements, succeeded)
+}
+
+/// Prints the inputs found for each day, and which days have a solver
+/// but no input.
+pub fn list_inputs() -> io::Result<()> {
+    let input_files = inputs::discover_inputs()?;
+    let mut days = input_files
+        .iter()
+        .map(|input_file| input_file.day)
+        .chain(SOLVERS.iter().map(|solver| solver.day()))
+        .collect::<Vec<u8>>();
+    days.sort_unstable();
+    days.dedup();
+    println!("{:>3}  {:>6}  {:>7}  Variants", "Day", "Solver", "Default");
+    for day in days {
+        let day_files = input_files
+            .iter()
+            .filter(|input_file| input_file.day == day)
+            .collect::<Vec<&inputs::InputFile>>();
+        let has_default = day_files
+            .iter()
+            .any(|input_file| input_file.variant.is_none());
+        let variants = day_files
+            .iter()
+            .filter_map(|input_file| input_file.variant.as_deref())
+            .collect::<Vec<&str>>()
+            .join(", ");
+        let row = format!(
+            "{:>3}  {:>6}  {:>7}  {}",
+            day,
+            match solver::find_solver(day) {
+                Some(_) => "yes",
+                None => "no",
+            },
+            match has_default {
+                true => "yes",
+                false => "no",
+            },
+            variants
+        );
+        println!("{}", row.trim_end());
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+struct NeverFinishes;
+
+#[cfg(test)]
+impl solver::Solver for NeverFinishes {
+    const DAY: u8 = 1;
+    type Input = ();
+
+    fn parse(&self, _puzzle_input: String) -> Result<(), crate::error::ParseError> {
+        Ok(())
+    }
+
+    fn solve_part_1(&self, _input: &()) -> Result<crate::answer::Answer, Error> {
+        Ok(1.into())
+    }
+
+    fn solve_part_2(&self, _input: &()) -> Result<crate::answer::Answer, Error> {
+        loop {
+            cancel::checkpoint();
+            thread::yield_now();
+        }
+    }
+}
+
+#[test]
+fn test_run_input_times_out() {
+    let input_path = inputs::input_path(1, None);
+    let records = run_input(
+        &NeverFinishes,
+        &[1, 2, 1],
+        &input_path,
+        Some(Duration::from_millis(200)),
+    );
+    let statuses = records
+        .iter()
+        .map(|record| match &record.answer {
+            Ok(answer) => answer.to_string(),
+            Err(failure) => failure.status.to_string(),
+        })
+        .collect::<Vec<String>>();
+    assert_eq!(statuses, vec!["1", "TIMEOUT", "TIMEOUT"]);
+}
+
+/// Sets its flag when dropped, so that a test can tell when a solver
+/// on a worker thread has returned or been cancelled.
+#[cfg(test)]
+struct SetOnDrop(&'static AtomicBool);
+
+#[cfg(test)]
+impl Drop for SetOnDrop {
+    fn drop(&mut self) {
+        self.0.store(true, Ordering::Relaxed);
+    }
+}
+
+/// Waits for a solver that `run_input` gave up on to stop, failing if
+/// it is still running after 5 seconds.
+#[cfg(test)]
+fn assert_stops(stopped: &AtomicBool) {
+    let started_waiting = Instant::now();
+    while !stopped.load(Ordering::Relaxed) {
+        assert!(started_waiting.elapsed() < Duration::from_secs(5));
+        thread::sleep(Duration::from_millis(10));
+    }
+}
+
+/// Searches day 15's cavern tiled 40 times across and down, and sets
+/// [`LARGE_CAVERN_STOPPED`] once it returns or is cancelled.
+#[cfg(test)]
+struct LargeTiledCavern;
+
+#[cfg(test)]
+static LARGE_CAVERN_STOPPED: AtomicBool = AtomicBool::new(false);
+
+#[cfg(test)]
+impl solver::Solver for LargeTiledCavern {
+    const DAY: u8 = 15;
+    type Input = crate::day15::CavernMap;
+
+    fn parse(
+        &self,
+        puzzle_input: String,
+    ) -> Result<crate::day15::CavernMap, crate::error::ParseError> {
+        crate::day15::CavernMap::from(puzzle_input)
+    }
+
+    fn solve_part_1(
+        &self,
+        cavern_map: &crate::day15::CavernMap,
+    ) -> Result<crate::answer::Answer, Error> {
+        let _set_on_drop = SetOnDrop(&LARGE_CAVERN_STOPPED);
+        let options = crate::day15::CavernOptions {
+            tile_factor: 40,
+            ..crate::day15::CavernOptions::PART_2
+        };
+        Ok(crate::day15::lowest_total_risk(cavern_map, options)
+            .unwrap()
+            .into())
+    }
+
+    fn solve_part_2(
+        &self,
+        _cavern_map: &crate::day15::CavernMap,
+    ) -> Result<crate::answer::Answer, Error> {
+        Err(Error::NotImplemented { day: 15, part: 2 })
+    }
+}
+
+#[test]
+fn test_run_input_cancels_large_tiled_cavern() {
+    let input_path = inputs::input_path(15, None);
+    let records = run_input(
+        &LargeTiledCavern,
+        &[1],
+        &input_path,
+        Some(Duration::from_millis(200)),
+    );
+    assert_eq!(records[0].answer.as_ref().unwrap_err().status, "TIMEOUT");
+    // Searching the whole cavern takes far longer than this, so the
+    // search must have stopped at one of its checkpoints.
+    assert_stops(&LARGE_CAVERN_STOPPED);
+}
+
+/// Solves day 11's part 2, and sets [`OCTOPODES_STOPPED`] once it
+/// returns or is cancelled.
+#[cfg(test)]
+struct WatchedOctopodes;
+
+#[cfg(test)]
+static OCTOPODES_STOPPED: AtomicBool = AtomicBool::new(false);
+
+#[cfg(test)]
+impl solver::Solver for WatchedOctopodes {
+    const DAY: u8 = 11;
+    type Input = crate::day11::OctopusMap;
+
+    fn parse(
+        &self,
+        puzzle_input: String,
+    ) -> Result<crate::day11::OctopusMap, crate::error::ParseError> {
+        solver::Solver::parse(&crate::day11::Day11, puzzle_input)
+    }
+
+    fn solve_part_1(
+        &self,
+        _octopus_map: &crate::day11::OctopusMap,
+    ) -> Result<crate::answer::Answer, Error> {
+        Err(Error::NotImplemented { day: 11, part: 1 })
+    }
+
+    fn solve_part_2(
+        &self,
+        octopus_map: &crate::day11::OctopusMap,
+    ) -> Result<crate::answer::Answer, Error> {
+        let _set_on_drop = SetOnDrop(&OCTOPODES_STOPPED);
+        Ok(crate::day11::solve_part_2(octopus_map).into())
+    }
+}
+
+#[test]
+fn test_run_input_cancels_octopodes_that_never_synchronize() {
+    // These two octopodes never flash in the same step.
+    let input_path = std::env::temp_dir().join("advent_of_code_2021_11_unsynchronized.txt");
+    fs::write(&input_path, "19\n").unwrap();
+    let records = run_input(
+        &WatchedOctopodes,
+        &[2],
+        input_path.to_str().unwrap(),
+        Some(Duration::from_millis(200)),
+    );
+    assert_eq!(records[0].answer.as_ref().unwrap_err().status, "TIMEOUT");
+    assert_stops(&OCTOPODES_STOPPED);
+}
+
+#[test]
+fn test_run_inputs_reports_in_input_order() {
+    let input_path = inputs::input_path(1, None);
+    let input_paths = [
+        input_path.clone(),
+        String::from("input/1_missing.txt"),
+        input_path.clone(),
+    ];
+    let options = RunOptions {
+        jobs: 3,
+        ..RunOptions::default()
+    };
+    let mut out = Vec::new();
+    let mut reporter = Reporter::new(
+        &mut out,
+        crate::output::Format::Csv,
+        crate::output::PlainStyle::Table,
+    );
+    let solver = solver::find_solver(1).unwrap();
+    let all_succeeded = run_inputs(&mut reporter, solver, 1, &input_paths, &options).unwrap();
+    assert!(!all_succeeded);
+    let rows = String::from_utf8(out).unwrap();
+    let inputs_and_answers = rows
+        .lines()
+        .skip(1)
+        .map(|row| {
+            row.split(',')
+                .skip(2)
+                .take(2)
+                .collect::<Vec<&str>>()
+                .join(",")
+        })
+        .collect::<Vec<String>>();
+    assert_eq!(
+        inputs_and_answers,
+        vec![
+            format!("{},1676", input_path),
+            String::from("input/1_missing.txt,"),
+            format!("{},1676", input_path),
+        ]
+    );
+}
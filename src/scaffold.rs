@@ -0,0 +1,335 @@
+use std::fmt;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use crate::inputs;
+
+/// Why a new day could not be scaffolded.
+#[derive(Debug)]
+pub enum ScaffoldError {
+    InvalidDay(u8),
+    AlreadyExists(PathBuf),
+    /// A file to be edited does not look the way it is expected to.
+    UnrecognizedLayout {
+        path: PathBuf,
+        expected: &'static str,
+    },
+    Io(PathBuf, io::Error),
+}
+
+impl fmt::Display for ScaffoldError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ScaffoldError::InvalidDay(day) => write!(f, "day {} is not from 1 to 25", day),
+            ScaffoldError::AlreadyExists(path) => write!(f, "{} already exists", path.display()),
+            ScaffoldError::UnrecognizedLayout { path, expected } => {
+                write!(f, "{}: expected {}", path.display(), expected)
+            }
+            ScaffoldError::Io(path, error) => write!(f, "{}: {}", path.display(), error),
+        }
+    }
+}
+
+/// Writes `src/dayNN.rs` with parse and solve stubs and ignored tests,
+/// adds it to `src/lib.rs` and [`crate::solver::SOLVERS`], and creates
+/// empty `input/{day}.txt` and `input/{day}_sample.txt` files if they
+/// do not exist, all under `root`.
+///
+/// Returns the paths of the files written.
+pub fn new_day(root: &Path, day: u8) -> Result<Vec<PathBuf>, ScaffoldError> {
+    if !(1..=25).contains(&day) {
+        return Err(ScaffoldError::InvalidDay(day));
+    }
+    let module_path = root.join(format!("src/day{:02}.rs", day));
+    if module_path.exists() {
+        return Err(ScaffoldError::AlreadyExists(module_path));
+    }
+    // Work out every edit before writing anything, so that a surprise
+    // in one file leaves the others untouched.
+    let lib_path = root.join("src/lib.rs");
+    let lib_source = register_module(&read(&lib_path)?, day).ok_or_else(|| {
+        ScaffoldError::UnrecognizedLayout {
+            path: lib_path.clone(),
+            expected: "\"pub mod dayNN;\" lines",
+        }
+    })?;
+    let solver_path = root.join("src/solver.rs");
+    let solver_source = register_solver(&read(&solver_path)?, day).ok_or_else(|| {
+        ScaffoldError::UnrecognizedLayout {
+            path: solver_path.clone(),
+            expected: "\"use crate::{dayNN, ...};\" lines and a SOLVERS list",
+        }
+    })?;
+
+    let mut written = Vec::new();
+    for (path, contents) in [
+        (module_path, module_source(day)),
+        (lib_path, lib_source),
+        (solver_path, solver_source),
+    ] {
+        write(&path, &contents)?;
+        written.push(path);
+    }
+    for variant in [None, Some("sample")] {
+        let input_path = root.join(inputs::input_path(day, variant));
+        if !input_path.exists() {
+            write(&input_path, "")?;
+            written.push(input_path);
+        }
+    }
+    Ok(written)
+}
+
+fn read(path: &Path) -> Result<String, ScaffoldError> {
+    fs::read_to_string(path).map_err(|error| ScaffoldError::Io(path.to_path_buf(), error))
+}
+
+fn write(path: &Path, contents: &str) -> Result<(), ScaffoldError> {
+    fs::write(path, contents).map_err(|error| ScaffoldError::Io(path.to_path_buf(), error))
+}
+
+/// Parses the day out of `line` if it is `{prefix}NN{suffix}`, ignoring
+/// surrounding whitespace.
+fn day_in_line(line: &str, prefix: &str, suffix: &str) -> Option<u8> {
+    line.trim()
+        .strip_prefix(prefix)?
+        .strip_suffix(suffix)?
+        .parse()
+        .ok()
+}
+
+/// Inserts `line` among the lines for which `line_day` gives a day,
+/// keeping them in order. Returns `None` if there are no such lines or
+/// `day` is already there.
+fn insert_in_order(
+    source: &str,
+    day: u8,
+    line: &str,
+    line_day: impl Fn(&str) -> Option<u8>,
+) -> Option<String> {
+    let lines = source.lines().collect::<Vec<&str>>();
+    let existing_days = lines
+        .iter()
+        .enumerate()
+        .filter_map(|(index, line)| Some((index, line_day(line)?)))
+        .collect::<Vec<(usize, u8)>>();
+    if existing_days
+        .iter()
+        .any(|(_, existing_day)| *existing_day == day)
+    {
+        return None;
+    }
+    let index = match existing_days
+        .iter()
+        .find(|(_, existing_day)| *existing_day > day)
+    {
+        Some((index, _)) => *index,
+        None => existing_days.last()?.0 + 1,
+    };
+    let mut lines = lines;
+    lines.insert(index, line);
+    Some(lines.join("\n") + "\n")
+}
+
+/// Adds `pub mod dayNN;` to the source of `src/lib.rs`.
+pub fn register_module(lib_source: &str, day: u8) -> Option<String> {
+    insert_in_order(
+        lib_source,
+        day,
+        &format!("pub mod day{:02};", day),
+        |line| day_in_line(line, "pub mod day", ";"),
+    )
+}
+
+/// Adds day `day` to the `use crate::{dayNN, ...};` lines and to
+/// `SOLVERS` in the source of `src/solver.rs`.
+pub fn register_solver(solver_source: &str, day: u8) -> Option<String> {
+    let solver_source = insert_in_order(
+        solver_source,
+        day,
+        &format!("    &day{:02}::Day{:02},", day, day),
+        |line| {
+            let (module, _) = line.trim().strip_prefix("&day")?.split_once("::")?;
+            module.parse().ok()
+        },
+    )?;
+
+    let lines = solver_source.lines().collect::<Vec<&str>>();
+    let is_use_line = |line: &str| line.starts_with("use crate::{day");
+    let first_use_line = lines.iter().position(|line| is_use_line(line))?;
+    let use_line_count = lines[first_use_line..]
+        .iter()
+        .take_while(|line| is_use_line(line))
+        .count();
+    let mut days = Vec::new();
+    for line in &lines[first_use_line..first_use_line + use_line_count] {
+        let modules = line.strip_prefix("use crate::{")?.strip_suffix("};")?;
+        for module in modules.split(", ") {
+            days.push(module.strip_prefix("day")?.parse::<u8>().ok()?);
+        }
+    }
+    if days.contains(&day) {
+        return None;
+    }
+    days.push(day);
+    days.sort_unstable();
+    let use_lines = days
+        .chunks(10)
+        .map(|chunk| {
+            let modules = chunk
+                .iter()
+                .map(|day| format!("day{:02}", day))
+                .collect::<Vec<String>>()
+                .join(", ");
+            format!("use crate::{{{}}};", modules)
+        })
+        .collect::<Vec<String>>();
+
+    let mut lines = lines
+        .iter()
+        .map(|line| line.to_string())
+        .collect::<Vec<String>>();
+    lines.splice(first_use_line..first_use_line + use_line_count, use_lines);
+    Some(lines.join("\n") + "\n")
+}
+
+/// The source of a new day module with parse and solve stubs. The
+/// tests are ignored until the expected answers are filled in.
+pub fn module_source(day: u8) -> String {
+    MODULE_TEMPLATE
+        .replace("{day:02}", &format!("{:02}", day))
+        .replace("{day}", &day.to_string())
+}
+
+const MODULE_TEMPLATE: &str = r#"use crate::answer::Answer;
+use crate::error::{Error, ParseContext, ParseError};
+use crate::solver::Solver;
+
+pub fn parse_input(puzzle_input: String) -> Result<Vec<String>, ParseError> {
+    let context = ParseContext::new({day}, &puzzle_input);
+    puzzle_input
+        .trim()
+        .split("\n")
+        .map(|line| match line.is_empty() {
+            true => Err(context.error(line, "a non-empty line")),
+            false => Ok(line.to_string()),
+        })
+        .collect()
+}
+
+/// Returns `None` until part 1 is solved.
+pub fn solve_part_1(_lines: &[String]) -> Option<u64> {
+    None
+}
+
+/// Returns `None` until part 2 is solved.
+pub fn solve_part_2(_lines: &[String]) -> Option<u64> {
+    None
+}
+
+pub struct Day{day:02};
+
+impl Solver for Day{day:02} {
+    const DAY: u8 = {day};
+    type Input = Vec<String>;
+
+    fn parse(&self, puzzle_input: String) -> Result<Vec<String>, ParseError> {
+        parse_input(puzzle_input)
+    }
+
+    fn solve_part_1(&self, lines: &Vec<String>) -> Result<Answer, Error> {
+        solve_part_1(lines)
+            .map(Answer::from)
+            .ok_or(Error::NotImplemented { day: {day}, part: 1 })
+    }
+
+    fn solve_part_2(&self, lines: &Vec<String>) -> Result<Answer, Error> {
+        solve_part_2(lines)
+            .map(Answer::from)
+            .ok_or(Error::NotImplemented { day: {day}, part: 2 })
+    }
+}
+
+#[cfg(test)]
+use super::test_helpers;
+
+#[test]
+#[ignore = "part 1 is not solved yet"]
+fn test_part_1_sample() {
+    let lines = parse_input(test_helpers::load_puzzle_input_variant({day}, Some("sample"))).unwrap();
+    assert_eq!(solve_part_1(&lines), Some(0));
+}
+
+#[test]
+#[ignore = "part 2 is not solved yet"]
+fn test_part_2_sample() {
+    let lines = parse_input(test_helpers::load_puzzle_input_variant({day}, Some("sample"))).unwrap();
+    assert_eq!(solve_part_2(&lines), Some(0));
+}
+
+#[test]
+#[ignore = "part 1 is not solved yet"]
+fn test_part_1() {
+    let lines = parse_input(test_helpers::load_puzzle_input({day})).unwrap();
+    assert_eq!(solve_part_1(&lines), Some(0));
+}
+
+#[test]
+#[ignore = "part 2 is not solved yet"]
+fn test_part_2() {
+    let lines = parse_input(test_helpers::load_puzzle_input({day})).unwrap();
+    assert_eq!(solve_part_2(&lines), Some(0));
+}
+"#;
+
+#[test]
+fn test_register_module() {
+    let lib_source = "pub mod cancel;\npub mod day01;\npub mod day22;\npub mod error;\n";
+    assert_eq!(
+        register_module(lib_source, 18).unwrap(),
+        "pub mod cancel;\npub mod day01;\npub mod day18;\npub mod day22;\npub mod error;\n"
+    );
+    assert_eq!(
+        register_module(lib_source, 25).unwrap(),
+        "pub mod cancel;\npub mod day01;\npub mod day22;\npub mod day25;\npub mod error;\n"
+    );
+    assert_eq!(register_module(lib_source, 22), None);
+}
+
+#[test]
+fn test_register_solver() {
+    let solver_source = "\
+use crate::{day01, day02, day03, day04, day05, day06, day07, day08, day09, day11};
+use crate::{day22};
+
+pub static SOLVERS: &[&dyn DynSolver] = &[
+    &day01::Day01,
+    &day22::Day22,
+];
+";
+    assert_eq!(
+        register_solver(solver_source, 10).unwrap(),
+        "\
+use crate::{day01, day02, day03, day04, day05, day06, day07, day08, day09, day10};
+use crate::{day11, day22};
+
+pub static SOLVERS: &[&dyn DynSolver] = &[
+    &day01::Day01,
+    &day10::Day10,
+    &day22::Day22,
+];
+"
+    );
+    assert_eq!(register_solver(solver_source, 7), None);
+    assert_eq!(register_solver(solver_source, 22), None);
+}
+
+#[test]
+fn test_module_source() {
+    let source = module_source(8);
+    assert!(source.contains("pub struct Day08;"));
+    assert!(source.contains("const DAY: u8 = 8;"));
+    assert!(source.contains("load_puzzle_input_variant(8, Some(\"sample\"))"));
+    assert!(!source.contains("{day"));
+}
@@ -0,0 +1,30 @@
+use std::fmt;
+
+use termion::color;
+
+/// A step-based process whose state is drawn with its `Display` impl.
+pub trait Simulation: fmt::Display {
+    /// Advances the simulation by one step. Returns false, leaving the
+    /// state unchanged, if it has finished.
+    fn step(&mut self) -> bool;
+
+    /// The colour to draw `character` of the `Display` output in, or
+    /// `None` for the terminal's default.
+    fn colour(&self, character: char) -> Option<color::Rgb> {
+        default_colour(character)
+    }
+}
+
+/// Draws digits in shades from dark blue for 1 up to light blue for 9,
+/// with 0 in bright yellow, and `#` in white.
+pub fn default_colour(character: char) -> Option<color::Rgb> {
+    match character {
+        '0' => Some(color::Rgb(255, 240, 120)),
+        '1'..='9' => {
+            let shade = (character as u8 - b'0') * 20;
+            Some(color::Rgb(40 + shade / 2, 60 + shade / 2, 80 + shade))
+        }
+        '#' => Some(color::Rgb(255, 255, 255)),
+        _ => None,
+    }
+}
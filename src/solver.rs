@@ -0,0 +1,86 @@
+use std::any::Any;
+
+use crate::answer::Answer;
+use crate::error::{Error, ParseError};
+
+use crate::{day01, day02, day03, day04, day05, day06, day07, day08, day09, day10};
+use crate::{day11, day12, day13, day14, day15, day16, day17, day21, day22};
+
+/// A solution to both parts of one day's puzzle.
+///
+/// The puzzle input is parsed once by [`Solver::parse`] and the parsed
+/// form is then handed to each part. Malformed input is reported as a
+/// [`ParseError`] rather than a panic, and well-formed input that has
+/// no answer as an [`Error::Unsolvable`].
+pub trait Solver {
+    /// The day of the puzzle this solver solves.
+    const DAY: u8;
+
+    /// The parsed form of the puzzle input shared by both parts.
+    type Input: 'static;
+
+    fn parse(&self, puzzle_input: String) -> Result<Self::Input, ParseError>;
+    fn solve_part_1(&self, input: &Self::Input) -> Result<Answer, Error>;
+    fn solve_part_2(&self, input: &Self::Input) -> Result<Answer, Error>;
+}
+
+/// Object-safe view of a [`Solver`], so that solvers with different
+/// input types can live side by side in [`SOLVERS`].
+pub trait DynSolver: Sync {
+    fn day(&self) -> u8;
+    fn parse(&self, puzzle_input: String) -> Result<Box<dyn Any>, ParseError>;
+
+    /// Solves `part` of the puzzle using input previously returned by
+    /// [`DynSolver::parse`]. Returns [`Error::NotImplemented`] if `part`
+    /// is not 1 or 2.
+    fn solve(&self, input: &dyn Any, part: u8) -> Result<Answer, Error>;
+}
+
+impl<S: Solver + Sync> DynSolver for S {
+    fn day(&self) -> u8 {
+        S::DAY
+    }
+
+    fn parse(&self, puzzle_input: String) -> Result<Box<dyn Any>, ParseError> {
+        Ok(Box::new(Solver::parse(self, puzzle_input)?))
+    }
+
+    fn solve(&self, input: &dyn Any, part: u8) -> Result<Answer, Error> {
+        let input = input
+            .downcast_ref::<S::Input>()
+            .expect("Input was not parsed by this solver");
+        match part {
+            1 => self.solve_part_1(input),
+            2 => self.solve_part_2(input),
+            _ => Err(Error::NotImplemented { day: S::DAY, part }),
+        }
+    }
+}
+
+/// Every implemented day, in order.
+pub static SOLVERS: &[&dyn DynSolver] = &[
+    &day01::Day01,
+    &day02::Day02,
+    &day03::Day03,
+    &day04::Day04,
+    &day05::Day05,
+    &day06::Day06,
+    &day07::Day07,
+    &day08::Day08,
+    &day09::Day09,
+    &day10::Day10,
+    &day11::Day11,
+    &day12::Day12,
+    &day13::Day13,
+    &day14::Day14,
+    &day15::Day15,
+    &day16::Day16,
+    &day17::Day17,
+    &day21::Day21,
+    &day22::Day22,
+];
+
+/// Looks up the solver for `day`, if that day has been implemented.
+pub fn find_solver(day: u8) -> Option<&'static dyn DynSolver> {
+    SOLVERS.iter().copied().find(|solver| solver.day() == day)
+}
@@ -1,8 +1,16 @@
 use std::fs;
 use std::path;
 
+use crate::inputs;
+
 pub(crate) fn load_puzzle_input(day: u8) -> String {
+    load_puzzle_input_variant(day, None)
+}
+
+/// Loads `input/{day}_{variant}.txt`, or `input/{day}.txt` if
+/// `variant` is `None`.
+pub(crate) fn load_puzzle_input_variant(day: u8, variant: Option<&str>) -> String {
     let mut puzzle_input_path = path::PathBuf::from(env!("CARGO_MANIFEST_DIR"));
-    puzzle_input_path.push(format!("input/{}.txt", day));
+    puzzle_input_path.push(inputs::input_path(day, variant));
     fs::read_to_string(puzzle_input_path).unwrap()
 }
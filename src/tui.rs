@@ -0,0 +1,341 @@
+use std::collections::{HashMap, VecDeque};
+use std::io::{self, Write};
+use std::time::Duration;
+
+use termion::cursor;
+use termion::event::Key;
+use termion::input::TermRead;
+use termion::raw::IntoRawMode;
+use termion::screen::AlternateScreen;
+use termion::{clear, style};
+
+use crate::inputs::{self, InputFile};
+use crate::output::Record;
+use crate::runner;
+use crate::solver::{DynSolver, SOLVERS};
+
+/// How many runs the dashboard remembers.
+pub const HISTORY_LENGTH: usize = 10;
+
+const HELP: &str = "up/down: choose part  left/right: choose input  enter: solve  q: quit";
+
+/// A part of a day that can be solved.
+struct Row {
+    solver: &'static dyn DynSolver,
+    part: u8,
+}
+
+/// The state of the dashboard: which part and input are selected, the
+/// latest result for each, and the most recent runs.
+pub struct Dashboard {
+    rows: Vec<Row>,
+    /// The input paths available for each day, main input first.
+    input_paths: HashMap<u8, Vec<String>>,
+    selected_inputs: HashMap<u8, usize>,
+    selected_row: usize,
+    latest_records: HashMap<(u8, u8, String), Record>,
+    history: VecDeque<Record>,
+    timeout: Option<Duration>,
+}
+
+impl Dashboard {
+    pub fn new(input_files: &[InputFile], timeout: Option<Duration>) -> Self {
+        let mut input_paths = HashMap::new();
+        for solver in SOLVERS {
+            // Offer the main input even if it is missing, so that
+            // solving it explains what is wrong.
+            let mut day_input_paths = vec![inputs::input_path(solver.day(), None)];
+            day_input_paths.extend(
+                input_files
+                    .iter()
+                    .filter(|input_file| input_file.day == solver.day())
+                    .filter(|input_file| input_file.variant.is_some())
+                    .map(|input_file| input_file.path()),
+            );
+            input_paths.insert(solver.day(), day_input_paths);
+        }
+        Dashboard {
+            rows: SOLVERS
+                .iter()
+                .flat_map(|solver| {
+                    [1, 2].iter().map(move |part| Row {
+                        solver: *solver,
+                        part: *part,
+                    })
+                })
+                .collect(),
+            input_paths,
+            selected_inputs: HashMap::new(),
+            selected_row: 0,
+            latest_records: HashMap::new(),
+            history: VecDeque::with_capacity(HISTORY_LENGTH),
+            timeout,
+        }
+    }
+
+    /// Moves the selection `offset` rows down, stopping at either end.
+    pub fn move_selection(&mut self, offset: isize) {
+        let last_row = self.rows.len().saturating_sub(1) as isize;
+        self.selected_row = (self.selected_row as isize + offset).clamp(0, last_row) as usize;
+    }
+
+    /// Selects the next (or with a negative `offset`, previous) input
+    /// for the selected day, wrapping around.
+    pub fn cycle_input(&mut self, offset: isize) {
+        let day = self.selected_day();
+        let input_count = self.input_paths[&day].len() as isize;
+        let selected_input = self.selected_inputs.entry(day).or_insert(0);
+        *selected_input = (*selected_input as isize + offset).rem_euclid(input_count) as usize;
+    }
+
+    fn selected_day(&self) -> u8 {
+        self.rows[self.selected_row].solver.day()
+    }
+
+    fn input_path(&self, day: u8) -> &str {
+        let selected_input = self.selected_inputs.get(&day).copied().unwrap_or(0);
+        &self.input_paths[&day][selected_input]
+    }
+
+    /// The day, part and input path that [`Dashboard::solve_selected`]
+    /// would solve.
+    pub fn selected(&self) -> (u8, u8, &str) {
+        let row = &self.rows[self.selected_row];
+        (
+            row.solver.day(),
+            row.part,
+            self.input_path(row.solver.day()),
+        )
+    }
+
+    /// Solves the selected part with the selected input and records
+    /// the result.
+    pub fn solve_selected(&mut self) {
+        let row = &self.rows[self.selected_row];
+        let input_path = self.input_path(row.solver.day()).to_string();
+        for record in runner::run_input(row.solver, &[row.part], &input_path, self.timeout) {
+            self.add_record(record);
+        }
+    }
+
+    fn add_record(&mut self, record: Record) {
+        if self.history.len() == HISTORY_LENGTH {
+            self.history.pop_back();
+        }
+        self.history.push_front(record.clone());
+        self.latest_records
+            .insert((record.day, record.part, record.input_path.clone()), record);
+    }
+
+    /// The most recent runs, newest first.
+    pub fn history(&self) -> impl Iterator<Item = &Record> {
+        self.history.iter()
+    }
+
+    /// Redraws the whole screen, with `status` on the bottom line in
+    /// place of the key help if given.
+    pub fn draw<W: Write>(
+        &self,
+        out: &mut W,
+        (width, height): (u16, u16),
+        status: Option<&str>,
+    ) -> io::Result<()> {
+        let mut lines = vec![
+            format!("{}Advent of Code 2021{}", style::Bold, style::Reset),
+            format!(
+                "{:>3} {:>4}  {:<24}  {:>20}  {:>12}",
+                "Day", "Part", "Input", "Answer", "Solve"
+            ),
+        ];
+        // Keep room for the history, its heading, a blank line above it
+        // and the help line.
+        let list_height = (height as usize)
+            .saturating_sub(lines.len() + HISTORY_LENGTH + 3)
+            .max(1);
+        let first_visible_row = self
+            .selected_row
+            .saturating_sub(list_height - 1)
+            .min(self.rows.len().saturating_sub(list_height));
+        for (index, row) in self
+            .rows
+            .iter()
+            .enumerate()
+            .skip(first_visible_row)
+            .take(list_height)
+        {
+            let day = row.solver.day();
+            let input_path = self.input_path(day);
+            let input_count = self.input_paths[&day].len();
+            let input = match input_count {
+                1 => input_path.to_string(),
+                _ => format!("< {} >", input_path),
+            };
+            let (answer, solve_time) =
+                match self
+                    .latest_records
+                    .get(&(day, row.part, input_path.to_string()))
+                {
+                    Some(record) => describe(record),
+                    None => (String::new(), String::new()),
+                };
+            let line = format!(
+                "{:>3} {:>4}  {:<24}  {:>20}  {:>12}",
+                day, row.part, input, answer, solve_time
+            );
+            lines.push(match index == self.selected_row {
+                true => format!("{}{}{}", style::Invert, line, style::Reset),
+                false => line,
+            });
+        }
+        lines.push(String::new());
+        lines.push(format!("{}Recent runs{}", style::Bold, style::Reset));
+        for record in self.history() {
+            let (answer, solve_time) = describe(record);
+            lines.push(format!(
+                "day {} part {} ({}): {} {}",
+                record.day, record.part, record.input_path, answer, solve_time
+            ));
+        }
+
+        write!(out, "{}", clear::All)?;
+        for (y, line) in lines
+            .iter()
+            .enumerate()
+            .take((height as usize).saturating_sub(1))
+        {
+            write!(
+                out,
+                "{}{}",
+                cursor::Goto(1, y as u16 + 1),
+                truncate(line, width)
+            )?;
+        }
+        write!(
+            out,
+            "{}{}",
+            cursor::Goto(1, height),
+            truncate(status.unwrap_or(HELP), width)
+        )?;
+        out.flush()
+    }
+}
+
+/// The answer or failure status, and the solve time, of a run.
+fn describe(record: &Record) -> (String, String) {
+    let answer = match &record.answer {
+        Ok(answer) => answer.to_string(),
+        Err(failure) => failure.status.to_string(),
+    };
+    let solve_time = record
+        .solve_time
+        .map(|solve_time| format!("{:?}", solve_time))
+        .unwrap_or_default();
+    (answer, solve_time)
+}
+
+/// Cuts `line` down to `width` visible characters, passing escape
+/// sequences through untouched.
+fn truncate(line: &str, width: u16) -> String {
+    let mut truncated = String::with_capacity(line.len());
+    let mut visible = 0;
+    let mut in_escape = false;
+    for character in line.chars() {
+        if character == '\x1b' {
+            in_escape = true;
+        }
+        if in_escape {
+            truncated.push(character);
+            in_escape = !character.is_ascii_alphabetic();
+        } else if visible < width {
+            truncated.push(character);
+            visible += 1;
+        }
+    }
+    truncated
+}
+
+/// Runs the dashboard until the user quits. Parts are solved on the
+/// spot, with `timeout` if one is given.
+pub fn run(timeout: Option<Duration>) -> io::Result<()> {
+    let input_files = inputs::discover_inputs().unwrap_or_default();
+    let mut dashboard = Dashboard::new(&input_files, timeout);
+    let stdin = io::stdin();
+    let mut screen = AlternateScreen::from(io::stdout().into_raw_mode()?);
+    write!(screen, "{}", cursor::Hide)?;
+    dashboard.draw(&mut screen, termion::terminal_size()?, None)?;
+    for key in stdin.keys() {
+        match key? {
+            Key::Char('q') | Key::Esc | Key::Ctrl('c') => break,
+            Key::Up | Key::Char('k') => dashboard.move_selection(-1),
+            Key::Down | Key::Char('j') => dashboard.move_selection(1),
+            Key::PageUp => dashboard.move_selection(-10),
+            Key::PageDown => dashboard.move_selection(10),
+            Key::Home => dashboard.move_selection(isize::MIN / 2),
+            Key::End => dashboard.move_selection(isize::MAX / 2),
+            Key::Left | Key::Char('h') => dashboard.cycle_input(-1),
+            Key::Right | Key::Char('l') => dashboard.cycle_input(1),
+            Key::Char('\n') => {
+                let (day, part, input_path) = dashboard.selected();
+                let status = format!("Solving day {} part {} ({})...", day, part, input_path);
+                dashboard.draw(&mut screen, termion::terminal_size()?, Some(&status))?;
+                dashboard.solve_selected();
+            }
+            _ => continue,
+        }
+        dashboard.draw(&mut screen, termion::terminal_size()?, None)?;
+    }
+    write!(screen, "{}", cursor::Show)?;
+    screen.flush()
+}
+
+#[test]
+fn test_dashboard_navigation() {
+    let input_files = [
+        InputFile {
+            day: 1,
+            variant: None,
+        },
+        InputFile {
+            day: 1,
+            variant: Some(String::from("sample")),
+        },
+    ];
+    let mut dashboard = Dashboard::new(&input_files, None);
+    assert_eq!(dashboard.selected(), (1, 1, "input/1.txt"));
+    dashboard.move_selection(-1);
+    dashboard.move_selection(1);
+    assert_eq!(dashboard.selected(), (1, 2, "input/1.txt"));
+    dashboard.cycle_input(1);
+    assert_eq!(dashboard.selected(), (1, 2, "input/1_sample.txt"));
+    dashboard.move_selection(-1);
+    assert_eq!(dashboard.selected(), (1, 1, "input/1_sample.txt"));
+    dashboard.cycle_input(-1);
+    dashboard.cycle_input(-1);
+    assert_eq!(dashboard.selected(), (1, 1, "input/1_sample.txt"));
+    dashboard.move_selection(isize::MAX / 2);
+    assert_eq!(dashboard.selected(), (22, 2, "input/22.txt"));
+}
+
+#[test]
+fn test_dashboard_history() {
+    let mut dashboard = Dashboard::new(&[], None);
+    for _ in 0..HISTORY_LENGTH {
+        dashboard.solve_selected();
+    }
+    dashboard.move_selection(1);
+    dashboard.solve_selected();
+    let history = dashboard.history().collect::<Vec<&Record>>();
+    assert_eq!(history.len(), HISTORY_LENGTH);
+    assert_eq!((history[0].day, history[0].part), (1, 2));
+    assert_eq!(
+        history[0].answer.as_ref().map(|answer| answer.to_string()),
+        Ok(String::from("1706"))
+    );
+    assert_eq!(history[1].part, 1);
+
+    let mut screen = Vec::new();
+    dashboard.draw(&mut screen, (80, 30), None).unwrap();
+    let screen = String::from_utf8(screen).unwrap();
+    assert!(screen.contains("1706"));
+    assert!(screen.contains(HELP));
+}